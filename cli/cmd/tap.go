@@ -155,7 +155,7 @@ func renderTapEvent(event *common.TapEvent) string {
 	httpEvent := http.Event
 	switch ev := httpEvent.(type) {
 	case *common.TapEvent_Http_RequestInit_:
-		return fmt.Sprintf("req id=%d:%d %s :method=%s :authority=%s :path=%s",
+		req := fmt.Sprintf("req id=%d:%d %s :method=%s :authority=%s :path=%s",
 			ev.RequestInit.Id.Base,
 			ev.RequestInit.Id.Stream,
 			flow,
@@ -163,6 +163,13 @@ func renderTapEvent(event *common.TapEvent) string {
 			ev.RequestInit.Authority,
 			ev.RequestInit.Path,
 		)
+		if ev.RequestInit.RequestId != "" {
+			req += " x-request-id=" + ev.RequestInit.RequestId
+		}
+		if ev.RequestInit.Route != "" {
+			req += " route=" + ev.RequestInit.Route
+		}
+		return req
 	case *common.TapEvent_Http_ResponseInit_:
 		return fmt.Sprintf("rsp id=%d:%d %s :status=%d latency=%dµs",
 			ev.ResponseInit.Id.Base,
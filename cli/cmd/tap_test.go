@@ -228,6 +228,30 @@ func TestEventToString(t *testing.T) {
 		}
 	})
 
+	t.Run("Converts HTTP request init event with a request ID and route to string", func(t *testing.T) {
+		event := toTapEvent(&common.TapEvent_Http{
+			Event: &common.TapEvent_Http_RequestInit_{
+				RequestInit: &common.TapEvent_Http_RequestInit{
+					Method: &common.HttpMethod{
+						Type: &common.HttpMethod_Registered_{
+							Registered: common.HttpMethod_GET,
+						},
+					},
+					Authority: "hello.default:7777",
+					Path:      "/users/42",
+					RequestId: "f81d4fae-7dec-11d0-a765-00a0c91e6bf6",
+					Route:     "get-user",
+				},
+			},
+		})
+
+		expectedOutput := "req id=7:8 src=1.2.3.4:5555 dst=2.3.4.5:6666 :method=GET :authority=hello.default:7777 :path=/users/42 x-request-id=f81d4fae-7dec-11d0-a765-00a0c91e6bf6 route=get-user"
+		output := renderTapEvent(event)
+		if output != expectedOutput {
+			t.Fatalf("Expecting command output to be [%s], got [%s]", expectedOutput, output)
+		}
+	})
+
 	t.Run("Converts HTTP response init event to string", func(t *testing.T) {
 		event := toTapEvent(&common.TapEvent_Http{
 			Event: &common.TapEvent_Http_ResponseInit_{
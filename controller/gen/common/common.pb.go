@@ -932,6 +932,11 @@ type TapEvent_Http_RequestInit struct {
 	Scheme    *Scheme                 `protobuf:"bytes,3,opt,name=scheme" json:"scheme,omitempty"`
 	Authority string                  `protobuf:"bytes,4,opt,name=authority" json:"authority,omitempty"`
 	Path      string                  `protobuf:"bytes,5,opt,name=path" json:"path,omitempty"`
+	// Correlates the request with the application's view of it, if the
+	// request carries an `x-request-id` header.
+	RequestId string `protobuf:"bytes,6,opt,name=request_id,json=requestId" json:"request_id,omitempty"`
+	// The name of the route that the request matched, if any.
+	Route string `protobuf:"bytes,7,opt,name=route" json:"route,omitempty"`
 }
 
 func (m *TapEvent_Http_RequestInit) Reset()                    { *m = TapEvent_Http_RequestInit{} }
@@ -974,6 +979,20 @@ func (m *TapEvent_Http_RequestInit) GetPath() string {
 	return ""
 }
 
+func (m *TapEvent_Http_RequestInit) GetRequestId() string {
+	if m != nil {
+		return m.RequestId
+	}
+	return ""
+}
+
+func (m *TapEvent_Http_RequestInit) GetRoute() string {
+	if m != nil {
+		return m.Route
+	}
+	return ""
+}
+
 type TapEvent_Http_ResponseInit struct {
 	Id               *TapEvent_Http_StreamId   `protobuf:"bytes,1,opt,name=id" json:"id,omitempty"`
 	SinceRequestInit *google_protobuf.Duration `protobuf:"bytes,2,opt,name=since_request_init,json=sinceRequestInit" json:"since_request_init,omitempty"`
@@ -1078,59 +1097,60 @@ func init() {
 func init() { proto.RegisterFile("common/common.proto", fileDescriptor0) }
 
 var fileDescriptor0 = []byte{
-	// 863 bytes of a gzipped FileDescriptorProto
-	0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0xc4, 0x55, 0xcf, 0x6f, 0xe3, 0x44,
-	0x14, 0x8e, 0x1d, 0xc7, 0x49, 0x5e, 0xda, 0xca, 0x4c, 0xab, 0x55, 0x89, 0x58, 0xd8, 0x8d, 0x28,
-	0xda, 0xf6, 0xe0, 0xa2, 0x2c, 0x8a, 0xc4, 0xb1, 0x49, 0xad, 0x26, 0x02, 0x5a, 0x33, 0x31, 0xe7,
-	0xc8, 0xb5, 0x67, 0x13, 0x4b, 0x8d, 0xc7, 0xcc, 0x8c, 0x2b, 0xf5, 0x9f, 0xe1, 0x0a, 0x47, 0xfe,
-	0x1f, 0xfe, 0x0c, 0xc4, 0x1d, 0xcd, 0x8f, 0x38, 0x6e, 0x76, 0x69, 0x11, 0x1c, 0xf6, 0x94, 0x79,
-	0x6f, 0xbe, 0xf7, 0xe5, 0xfb, 0xe6, 0xcd, 0xf3, 0xc0, 0x61, 0x42, 0xd7, 0x6b, 0x9a, 0x9f, 0xeb,
-	0x1f, 0xbf, 0x60, 0x54, 0x50, 0x74, 0x90, 0xd0, 0x3c, 0x2d, 0x33, 0xe1, 0xeb, 0x6c, 0xff, 0xf3,
-	0x25, 0xa5, 0xcb, 0x3b, 0x72, 0xae, 0x76, 0x6f, 0xcb, 0x77, 0xe7, 0x69, 0xc9, 0x62, 0x91, 0x6d,
-	0xf0, 0x83, 0x3f, 0x2d, 0x80, 0xa9, 0x10, 0xc5, 0x0f, 0x44, 0xac, 0x68, 0x8a, 0xae, 0x00, 0x18,
-	0x59, 0x66, 0x5c, 0x10, 0x46, 0xd2, 0x63, 0xeb, 0x95, 0xf5, 0xe6, 0x60, 0x78, 0xe2, 0x3f, 0xe6,
-	0xf4, 0xb7, 0x78, 0x1f, 0x57, 0xe0, 0x69, 0x03, 0xd7, 0x4a, 0xd1, 0x97, 0xb0, 0x57, 0xe6, 0x35,
-	0x2a, 0xfb, 0x95, 0xf5, 0xa6, 0x3b, 0x6d, 0xe0, 0x47, 0xd9, 0x41, 0x0e, 0xb0, 0x65, 0x40, 0x6d,
-	0x68, 0x5e, 0x05, 0x91, 0xd7, 0x40, 0x1d, 0x70, 0xc2, 0x9b, 0x79, 0xe4, 0x59, 0x32, 0x15, 0xfe,
-	0x14, 0x79, 0x36, 0x02, 0x70, 0x2f, 0x83, 0xef, 0x83, 0x28, 0xf0, 0x9a, 0xa8, 0x0b, 0xad, 0xf0,
-	0x22, 0x9a, 0x4c, 0x3d, 0x07, 0xf5, 0xa0, 0x7d, 0x13, 0x46, 0xb3, 0x9b, 0xeb, 0xb9, 0xd7, 0x92,
-	0xc1, 0xe4, 0xe6, 0xfa, 0x3a, 0x98, 0x44, 0x9e, 0x2b, 0x39, 0xa6, 0xc1, 0xc5, 0xa5, 0xd7, 0x96,
-	0xf0, 0x08, 0x5f, 0x4c, 0x02, 0xaf, 0x33, 0x76, 0xc1, 0x11, 0x0f, 0x05, 0x19, 0xfc, 0x62, 0x81,
-	0x3b, 0x4f, 0x56, 0x64, 0x4d, 0xd0, 0xe4, 0x03, 0x8e, 0x5f, 0xef, 0x3a, 0xd6, 0xd8, 0xff, 0xeb,
-	0xf6, 0xf5, 0x23, 0xb7, 0x52, 0x60, 0x14, 0x85, 0x5e, 0x43, 0x0a, 0x94, 0xab, 0xb9, 0x67, 0x55,
-	0x02, 0xe7, 0xd0, 0x9d, 0x85, 0x17, 0x69, 0xca, 0x08, 0xe7, 0xe8, 0x08, 0x9c, 0xac, 0xb8, 0xff,
-	0x46, 0x89, 0x6b, 0x4f, 0x1b, 0x58, 0x45, 0xe8, 0x4c, 0x65, 0x47, 0xea, 0xbf, 0x7a, 0xc3, 0xa3,
-	0x5d, 0xc9, 0xb3, 0xf0, 0x7e, 0x64, 0xb0, 0xa3, 0xb1, 0x03, 0x76, 0x56, 0x0c, 0xbe, 0x06, 0x47,
-	0x66, 0xd1, 0x11, 0xb4, 0xde, 0x65, 0x8c, 0x0b, 0x45, 0xe8, 0x62, 0x1d, 0x20, 0x04, 0xce, 0x5d,
-	0xcc, 0x85, 0xe2, 0x73, 0xb1, 0x5a, 0x0f, 0xbe, 0x03, 0x88, 0x92, 0x62, 0xa3, 0xe3, 0x54, 0xb2,
-	0xa8, 0xa2, 0xde, 0xf0, 0xd3, 0xf7, 0xff, 0xcf, 0xc0, 0xb0, 0x9d, 0x15, 0x92, 0xac, 0xa0, 0x4c,
-	0x93, 0xed, 0x63, 0xb5, 0x1e, 0x7c, 0x0b, 0xbd, 0x4b, 0xc2, 0x45, 0x96, 0xab, 0xfb, 0x87, 0x5e,
-	0x80, 0xcb, 0xd5, 0xb1, 0x2a, 0xc6, 0x2e, 0x36, 0x91, 0x2a, 0x8d, 0xc5, 0x4a, 0x9f, 0x21, 0x56,
-	0xeb, 0x41, 0x0a, 0xcd, 0x80, 0x72, 0x74, 0x06, 0xde, 0x92, 0x15, 0xc9, 0x82, 0x8b, 0x58, 0x94,
-	0x7c, 0x91, 0xd0, 0x54, 0x17, 0xef, 0x4f, 0x1b, 0xf8, 0x40, 0xee, 0xcc, 0xd5, 0xc6, 0x84, 0xa6,
-	0x44, 0x62, 0x19, 0xe1, 0x44, 0x2c, 0x08, 0x63, 0x94, 0x69, 0xac, 0xbd, 0xc1, 0xaa, 0x9d, 0x40,
-	0x6e, 0x48, 0xec, 0xb8, 0x05, 0x4d, 0x92, 0xa7, 0x83, 0xdf, 0xba, 0xd0, 0x89, 0xe2, 0x22, 0xb8,
-	0x27, 0xb9, 0x40, 0x43, 0x70, 0x39, 0x2d, 0x59, 0x42, 0x8c, 0xe1, 0xfe, 0xae, 0xe1, 0xed, 0xc1,
-	0x60, 0x83, 0x94, 0x35, 0x22, 0x66, 0x4b, 0x22, 0x4c, 0x53, 0x9e, 0xac, 0xd1, 0x48, 0xf4, 0x16,
-	0x9c, 0x95, 0x10, 0xc5, 0x71, 0x53, 0x55, 0xbc, 0x7c, 0xaf, 0xc2, 0xe8, 0x51, 0x43, 0x27, 0xfb,
-	0x29, 0xc1, 0xfd, 0xbf, 0xda, 0xe0, 0xc8, 0x04, 0xba, 0x86, 0x3d, 0x46, 0x7e, 0x2e, 0x09, 0x17,
-	0x8b, 0x2c, 0xcf, 0x84, 0xd1, 0x7a, 0xfa, 0x24, 0x8b, 0x8f, 0x75, 0xc5, 0x2c, 0xcf, 0xc4, 0xb4,
-	0x81, 0x7b, 0x6c, 0x1b, 0xa2, 0x1f, 0x61, 0x9f, 0x11, 0x5e, 0xd0, 0x9c, 0x13, 0x4d, 0xa8, 0x8d,
-	0x9c, 0x3d, 0x47, 0xa8, 0x4b, 0x0c, 0xe3, 0x1e, 0xab, 0xc5, 0x5a, 0xa2, 0xa1, 0x24, 0x79, 0x6a,
-	0x8c, 0x9e, 0xfe, 0x3b, 0xc6, 0x20, 0x4f, 0xb5, 0xc4, 0x2a, 0xec, 0x8f, 0xa0, 0x33, 0x17, 0x8c,
-	0xc4, 0xeb, 0x59, 0x2a, 0xef, 0xca, 0x6d, 0xcc, 0xcd, 0x25, 0xc0, 0x6a, 0xad, 0xee, 0x95, 0xda,
-	0x57, 0xda, 0x1d, 0x6c, 0xa2, 0xfe, 0x1f, 0x16, 0xf4, 0x6a, 0xce, 0xd1, 0x08, 0xec, 0x2c, 0x35,
-	0x07, 0xf6, 0xd5, 0xd3, 0x6a, 0x36, 0xff, 0x87, 0xed, 0x2c, 0x95, 0x4d, 0x5e, 0xab, 0x8f, 0xdf,
-	0x3f, 0x35, 0x79, 0xfb, 0x79, 0xc4, 0x06, 0x89, 0xfc, 0xea, 0xae, 0x6b, 0xf7, 0x2f, 0x3e, 0xfc,
-	0x81, 0xa9, 0x66, 0xe0, 0x33, 0xe8, 0xc6, 0xa5, 0x58, 0x51, 0x96, 0x89, 0x87, 0x63, 0x47, 0x0d,
-	0xc2, 0x36, 0x51, 0x4d, 0x48, 0x6b, 0x3b, 0x21, 0xfd, 0xdf, 0x2d, 0xd8, 0xab, 0xb7, 0xe1, 0x3f,
-	0xdb, 0xbb, 0x02, 0xc4, 0xb3, 0x3c, 0x21, 0x8b, 0x47, 0xf7, 0xca, 0x36, 0x43, 0xaf, 0x5f, 0x13,
-	0x7f, 0xf3, 0x9a, 0xf8, 0x97, 0xe6, 0x35, 0xc1, 0x9e, 0x2a, 0xaa, 0x9f, 0xef, 0x17, 0xd0, 0x93,
-	0x77, 0xd5, 0x0c, 0xab, 0x32, 0xbe, 0x8f, 0x41, 0xa6, 0xf4, 0x94, 0xf6, 0x7f, 0xb5, 0x65, 0x43,
-	0xaa, 0xc6, 0x7e, 0x7c, 0xc5, 0x33, 0x38, 0xdc, 0x10, 0xd5, 0x47, 0xa0, 0xf9, 0x1c, 0xd3, 0x27,
-	0x86, 0xa9, 0x76, 0xfa, 0x27, 0x70, 0x50, 0x91, 0xdc, 0x3e, 0x08, 0xc2, 0x55, 0x17, 0x1d, 0x5c,
-	0x4d, 0xd7, 0x58, 0x26, 0xd1, 0x09, 0x34, 0x09, 0xe5, 0xaa, 0x91, 0xbd, 0xe1, 0xe1, 0xae, 0xe7,
-	0x80, 0x72, 0x2c, 0xf7, 0xc7, 0x6d, 0x68, 0x11, 0x69, 0xbe, 0x5a, 0x9c, 0xbd, 0x84, 0x4e, 0x28,
-	0x75, 0x24, 0xf4, 0xae, 0xf6, 0x90, 0xb4, 0xa1, 0x19, 0x4d, 0x42, 0xcf, 0xba, 0x75, 0x95, 0xc8,
-	0xb7, 0x7f, 0x07, 0x00, 0x00, 0xff, 0xff, 0x37, 0x93, 0x7d, 0x00, 0x23, 0x08, 0x00, 0x00,
+	// 880 bytes of a gzipped FileDescriptorProto
+	0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0xc5, 0x55, 0xdd, 0x6e, 0xdb, 0x36,
+	0x14, 0xae, 0x6d, 0xf9, 0xef, 0x38, 0x09, 0x54, 0xa6, 0x08, 0x3c, 0x63, 0x59, 0x5b, 0xa3, 0x29,
+	0x9a, 0x5c, 0x38, 0x85, 0x33, 0x18, 0xd8, 0x65, 0xec, 0x08, 0x89, 0xd1, 0x2d, 0x51, 0x65, 0xf5,
+	0xda, 0x50, 0x24, 0xd6, 0x11, 0x10, 0x8b, 0x2a, 0x45, 0x05, 0xc8, 0x5b, 0xec, 0x09, 0x7a, 0x5b,
+	0x60, 0x57, 0x7b, 0xa1, 0x3d, 0xc5, 0x5e, 0x60, 0xe4, 0x21, 0x2d, 0x2b, 0x69, 0x9b, 0x0e, 0xdb,
+	0x45, 0xaf, 0x44, 0x1e, 0x7e, 0xe7, 0xd3, 0x77, 0xfe, 0x48, 0xd8, 0x0e, 0xd9, 0x72, 0xc9, 0x92,
+	0x43, 0xfd, 0x19, 0xa4, 0x9c, 0x09, 0x46, 0xb6, 0x42, 0x96, 0x44, 0x79, 0x2c, 0x06, 0xda, 0xda,
+	0xfb, 0x69, 0xc1, 0xd8, 0xe2, 0x9a, 0x1e, 0xe2, 0xe9, 0x65, 0xfe, 0xfe, 0x30, 0xca, 0x79, 0x20,
+	0xe2, 0x15, 0xbe, 0xff, 0x77, 0x05, 0xe0, 0x4c, 0x88, 0xf4, 0x37, 0x2a, 0xae, 0x58, 0x44, 0x4e,
+	0x01, 0x38, 0x5d, 0xc4, 0x99, 0xa0, 0x9c, 0x46, 0xdd, 0xca, 0xb3, 0xca, 0xab, 0xad, 0xe1, 0xde,
+	0xe0, 0x2e, 0xe7, 0x60, 0x8d, 0x1f, 0x78, 0x05, 0xf8, 0xec, 0x91, 0x57, 0x72, 0x25, 0x2f, 0x60,
+	0x23, 0x4f, 0x4a, 0x54, 0x55, 0x49, 0xd5, 0x96, 0x98, 0x3b, 0xd6, 0x7e, 0x02, 0xb0, 0x66, 0x20,
+	0x4d, 0xa8, 0x9d, 0x3a, 0xbe, 0xfd, 0x88, 0xb4, 0xc0, 0x72, 0x2f, 0x66, 0xbe, 0x5d, 0x51, 0x26,
+	0xf7, 0x9d, 0x6f, 0x57, 0x09, 0x40, 0xe3, 0xc4, 0xf9, 0xd5, 0xf1, 0x1d, 0xbb, 0x46, 0xda, 0x50,
+	0x77, 0x8f, 0xfd, 0xc9, 0x99, 0x6d, 0x91, 0x0e, 0x34, 0x2f, 0x5c, 0x7f, 0x7a, 0x71, 0x3e, 0xb3,
+	0xeb, 0x6a, 0x33, 0xb9, 0x38, 0x3f, 0x77, 0x26, 0xbe, 0xdd, 0x50, 0x1c, 0x67, 0xce, 0xf1, 0x89,
+	0xdd, 0x54, 0x70, 0xdf, 0x3b, 0x9e, 0x38, 0x76, 0x6b, 0xdc, 0x00, 0x4b, 0xdc, 0xa6, 0xb4, 0xff,
+	0xb1, 0x02, 0x8d, 0x59, 0x78, 0x45, 0x97, 0x94, 0x4c, 0xbe, 0x10, 0xf1, 0xf3, 0xfb, 0x11, 0x6b,
+	0xec, 0xff, 0x8d, 0xf6, 0xf9, 0x9d, 0x68, 0x95, 0x40, 0xdf, 0x77, 0x65, 0xb8, 0x52, 0xa0, 0x5a,
+	0xcd, 0xec, 0x4a, 0x21, 0x70, 0x06, 0xed, 0xa9, 0x7b, 0x1c, 0x45, 0x9c, 0x66, 0x19, 0x79, 0x02,
+	0x56, 0x9c, 0xde, 0xfc, 0x8c, 0xe2, 0x9a, 0x92, 0x15, 0x77, 0xe4, 0x00, 0xad, 0x23, 0xfc, 0x57,
+	0x67, 0xf8, 0xe4, 0xbe, 0xe4, 0xa9, 0x7b, 0x33, 0x32, 0xd8, 0xd1, 0xd8, 0x82, 0x6a, 0x9c, 0xf6,
+	0x5f, 0x83, 0xa5, 0xac, 0x92, 0xaf, 0xfe, 0x3e, 0xe6, 0x99, 0x40, 0xc2, 0x86, 0xa7, 0x37, 0x84,
+	0x80, 0x75, 0x1d, 0x48, 0x63, 0x15, 0x8d, 0xb8, 0xee, 0xbf, 0x01, 0xf0, 0xc3, 0x74, 0xa5, 0x63,
+	0x5f, 0xb1, 0xa0, 0x53, 0x67, 0xf8, 0xc3, 0xe7, 0xff, 0x33, 0x30, 0x4f, 0x82, 0x14, 0x59, 0xca,
+	0xb8, 0x26, 0xdb, 0xf4, 0x70, 0xdd, 0xff, 0x05, 0x3a, 0x27, 0x34, 0x13, 0x71, 0x82, 0xfd, 0x47,
+	0x76, 0xa0, 0x91, 0x61, 0x5a, 0x91, 0xb1, 0xed, 0x99, 0x1d, 0xba, 0x06, 0xe2, 0x4a, 0xe7, 0xd0,
+	0xc3, 0x75, 0x3f, 0x82, 0x9a, 0xc3, 0x32, 0x19, 0xb2, 0xbd, 0xe0, 0x69, 0x38, 0xcf, 0x44, 0x20,
+	0xf2, 0x6c, 0x1e, 0xb2, 0x48, 0x3b, 0x6f, 0xca, 0x40, 0xb7, 0xd4, 0xc9, 0x0c, 0x0f, 0x26, 0xd2,
+	0xae, 0xb0, 0x52, 0x0d, 0x15, 0x73, 0xca, 0x39, 0xe3, 0x1a, 0x5b, 0x5d, 0x61, 0xf1, 0xc4, 0x51,
+	0x07, 0x0a, 0x3b, 0xae, 0x43, 0x8d, 0x26, 0x51, 0xff, 0xaf, 0x36, 0xb4, 0xfc, 0x20, 0x75, 0x6e,
+	0x68, 0x22, 0xc8, 0x50, 0xca, 0x63, 0x39, 0x0f, 0xa9, 0x09, 0xb8, 0x77, 0x3f, 0xe0, 0x75, 0x62,
+	0x3c, 0x83, 0x54, 0x3e, 0x22, 0xe0, 0x0b, 0x2a, 0x4c, 0x51, 0x1e, 0xf4, 0xd1, 0x48, 0x72, 0x04,
+	0xd6, 0x95, 0x9c, 0xa7, 0x6e, 0x0d, 0x3d, 0x76, 0x3f, 0xf3, 0x30, 0x7a, 0x70, 0xe8, 0x54, 0x3d,
+	0x15, 0xb8, 0xf7, 0x87, 0x6a, 0x1e, 0xb9, 0x20, 0xe7, 0xb0, 0xc1, 0xe9, 0x87, 0x5c, 0xa6, 0x75,
+	0x1e, 0x27, 0xb1, 0x30, 0x5a, 0xf7, 0x1f, 0x64, 0x91, 0x6d, 0x8c, 0x1e, 0x53, 0xe9, 0x20, 0x19,
+	0x3b, 0x7c, 0xbd, 0x25, 0x6f, 0x61, 0x53, 0xaa, 0x4b, 0x59, 0x92, 0x51, 0x4d, 0xa8, 0x03, 0x39,
+	0xf8, 0x16, 0xa1, 0x76, 0x31, 0x8c, 0x1b, 0xbc, 0xb4, 0xd7, 0x12, 0x0d, 0xa5, 0xcc, 0xb2, 0x09,
+	0x74, 0xff, 0xdf, 0x31, 0x3a, 0x49, 0xa4, 0x25, 0x16, 0xdb, 0xde, 0x08, 0x5a, 0x33, 0xc1, 0x69,
+	0xb0, 0x9c, 0x46, 0xaa, 0x57, 0x2e, 0x83, 0xcc, 0x34, 0x81, 0x87, 0x6b, 0xec, 0x2b, 0x3c, 0x47,
+	0xed, 0x96, 0x67, 0x76, 0xbd, 0xdf, 0xab, 0xd0, 0x29, 0x45, 0x4e, 0x46, 0xb2, 0x9b, 0x23, 0x93,
+	0xb0, 0x97, 0x0f, 0xab, 0x59, 0xfd, 0x4f, 0xb6, 0x76, 0xa4, 0x8a, 0xbc, 0xc4, 0xcb, 0xef, 0x6b,
+	0x45, 0x5e, 0x5f, 0x8f, 0x9e, 0x41, 0x92, 0x41, 0xd1, 0xeb, 0x3a, 0xfa, 0x9d, 0x2f, 0x5f, 0x30,
+	0xc5, 0x0c, 0xfc, 0x08, 0xed, 0x20, 0x97, 0x9e, 0x3c, 0x16, 0xb7, 0x5d, 0x0b, 0x07, 0x61, 0x6d,
+	0x28, 0x26, 0xa4, 0xbe, 0x9e, 0x10, 0xb2, 0xab, 0xae, 0x31, 0xd3, 0x08, 0x51, 0xb7, 0xa1, 0x5d,
+	0x56, 0x95, 0x8d, 0xd4, 0xc8, 0x73, 0x96, 0x0b, 0xda, 0x6d, 0xe2, 0x89, 0xde, 0xf4, 0xfe, 0xac,
+	0xc0, 0x46, 0xb9, 0x76, 0xff, 0x39, 0x27, 0xa7, 0x40, 0xb2, 0x38, 0x09, 0xe9, 0xfc, 0x4e, 0x33,
+	0x56, 0xcd, 0x4d, 0xa1, 0x9f, 0xa0, 0xc1, 0xea, 0x09, 0x1a, 0x9c, 0x98, 0x27, 0xc8, 0xb3, 0xd1,
+	0xa9, 0x5c, 0x94, 0xa7, 0xd0, 0x51, 0x0d, 0x6e, 0x26, 0x1c, 0xb3, 0xb5, 0xe9, 0x81, 0x32, 0xe9,
+	0xd1, 0xee, 0x7d, 0xc2, 0x2a, 0x16, 0xdd, 0xf0, 0xfd, 0x15, 0x4f, 0x61, 0x7b, 0x45, 0x54, 0x9e,
+	0x9b, 0xda, 0xb7, 0x98, 0x1e, 0x1b, 0xa6, 0x52, 0xf6, 0xf7, 0x60, 0xab, 0x20, 0xb9, 0xbc, 0x15,
+	0x34, 0xc3, 0xd2, 0x5b, 0x5e, 0x31, 0x92, 0x63, 0x65, 0x94, 0xb0, 0x1a, 0x65, 0x19, 0x56, 0xbf,
+	0x33, 0xdc, 0xbe, 0x1f, 0xb3, 0xbc, 0x27, 0x3d, 0x75, 0x3e, 0x6e, 0x42, 0x9d, 0xaa, 0xe0, 0x8b,
+	0xc5, 0xc1, 0x2e, 0xb4, 0x5c, 0xa5, 0x23, 0x64, 0xd7, 0xa5, 0xd7, 0x47, 0x3e, 0xb1, 0xfe, 0xc4,
+	0xb5, 0x2b, 0x97, 0x0d, 0x14, 0x79, 0xf4, 0x0f, 0x3a, 0x45, 0x34, 0x10, 0x58, 0x08, 0x00, 0x00,
 }
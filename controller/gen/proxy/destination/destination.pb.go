@@ -9,6 +9,7 @@ It is generated from these files:
 
 It has these top-level messages:
 	Update
+	Balancer
 	AddrSet
 	WeightedAddrSet
 	WeightedAddr
@@ -36,10 +37,39 @@ var _ = math.Inf
 // proto package needs to be updated.
 const _ = proto.ProtoPackageIsVersion2 // please upgrade the proto package
 
+type Balancer_Strategy int32
+
+const (
+	// Lets the proxy choose.
+	Balancer_DEFAULT        Balancer_Strategy = 0
+	Balancer_ROUND_ROBIN    Balancer_Strategy = 1
+	Balancer_LEAST_REQUESTS Balancer_Strategy = 2
+	Balancer_PEAK_EWMA      Balancer_Strategy = 3
+)
+
+var Balancer_Strategy_name = map[int32]string{
+	0: "DEFAULT",
+	1: "ROUND_ROBIN",
+	2: "LEAST_REQUESTS",
+	3: "PEAK_EWMA",
+}
+var Balancer_Strategy_value = map[string]int32{
+	"DEFAULT":        0,
+	"ROUND_ROBIN":    1,
+	"LEAST_REQUESTS": 2,
+	"PEAK_EWMA":      3,
+}
+
+func (x Balancer_Strategy) String() string {
+	return proto.EnumName(Balancer_Strategy_name, int32(x))
+}
+func (Balancer_Strategy) EnumDescriptor() ([]byte, []int) { return fileDescriptor0, []int{1, 0} }
+
 type Update struct {
 	// Types that are valid to be assigned to Update:
 	//	*Update_Add
 	//	*Update_Remove
+	//	*Update_Balancer
 	Update isUpdate_Update `protobuf_oneof:"update"`
 }
 
@@ -58,9 +88,13 @@ type Update_Add struct {
 type Update_Remove struct {
 	Remove *AddrSet `protobuf:"bytes,2,opt,name=remove,oneof"`
 }
+type Update_Balancer struct {
+	Balancer *Balancer `protobuf:"bytes,3,opt,name=balancer,oneof"`
+}
 
-func (*Update_Add) isUpdate_Update()    {}
-func (*Update_Remove) isUpdate_Update() {}
+func (*Update_Add) isUpdate_Update()      {}
+func (*Update_Remove) isUpdate_Update()   {}
+func (*Update_Balancer) isUpdate_Update() {}
 
 func (m *Update) GetUpdate() isUpdate_Update {
 	if m != nil {
@@ -83,11 +117,19 @@ func (m *Update) GetRemove() *AddrSet {
 	return nil
 }
 
+func (m *Update) GetBalancer() *Balancer {
+	if x, ok := m.GetUpdate().(*Update_Balancer); ok {
+		return x.Balancer
+	}
+	return nil
+}
+
 // XXX_OneofFuncs is for the internal use of the proto package.
 func (*Update) XXX_OneofFuncs() (func(msg proto.Message, b *proto.Buffer) error, func(msg proto.Message, tag, wire int, b *proto.Buffer) (bool, error), func(msg proto.Message) (n int), []interface{}) {
 	return _Update_OneofMarshaler, _Update_OneofUnmarshaler, _Update_OneofSizer, []interface{}{
 		(*Update_Add)(nil),
 		(*Update_Remove)(nil),
+		(*Update_Balancer)(nil),
 	}
 }
 
@@ -105,6 +147,11 @@ func _Update_OneofMarshaler(msg proto.Message, b *proto.Buffer) error {
 		if err := b.EncodeMessage(x.Remove); err != nil {
 			return err
 		}
+	case *Update_Balancer:
+		b.EncodeVarint(3<<3 | proto.WireBytes)
+		if err := b.EncodeMessage(x.Balancer); err != nil {
+			return err
+		}
 	case nil:
 	default:
 		return fmt.Errorf("Update.Update has unexpected type %T", x)
@@ -131,6 +178,14 @@ func _Update_OneofUnmarshaler(msg proto.Message, tag, wire int, b *proto.Buffer)
 		err := b.DecodeMessage(msg)
 		m.Update = &Update_Remove{msg}
 		return true, err
+	case 3: // update.balancer
+		if wire != proto.WireBytes {
+			return true, proto.ErrInternalBadWireType
+		}
+		msg := new(Balancer)
+		err := b.DecodeMessage(msg)
+		m.Update = &Update_Balancer{msg}
+		return true, err
 	default:
 		return false, nil
 	}
@@ -150,6 +205,11 @@ func _Update_OneofSizer(msg proto.Message) (n int) {
 		n += proto.SizeVarint(2<<3 | proto.WireBytes)
 		n += proto.SizeVarint(uint64(s))
 		n += s
+	case *Update_Balancer:
+		s := proto.Size(x.Balancer)
+		n += proto.SizeVarint(3<<3 | proto.WireBytes)
+		n += proto.SizeVarint(uint64(s))
+		n += s
 	case nil:
 	default:
 		panic(fmt.Sprintf("proto: unexpected type %T in oneof", x))
@@ -157,6 +217,24 @@ func _Update_OneofSizer(msg proto.Message) (n int) {
 	return n
 }
 
+// Configures how the proxy balances requests across the destination's
+// addresses. The most recent configuration applies.
+type Balancer struct {
+	Strategy Balancer_Strategy `protobuf:"varint,1,opt,name=strategy,enum=conduit.proxy.destination.Balancer_Strategy" json:"strategy,omitempty"`
+}
+
+func (m *Balancer) Reset()                    { *m = Balancer{} }
+func (m *Balancer) String() string            { return proto.CompactTextString(m) }
+func (*Balancer) ProtoMessage()               {}
+func (*Balancer) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{1} }
+
+func (m *Balancer) GetStrategy() Balancer_Strategy {
+	if m != nil {
+		return m.Strategy
+	}
+	return Balancer_DEFAULT
+}
+
 type AddrSet struct {
 	Addrs []*conduit_common.TcpAddress `protobuf:"bytes,1,rep,name=addrs" json:"addrs,omitempty"`
 }
@@ -164,7 +242,7 @@ type AddrSet struct {
 func (m *AddrSet) Reset()                    { *m = AddrSet{} }
 func (m *AddrSet) String() string            { return proto.CompactTextString(m) }
 func (*AddrSet) ProtoMessage()               {}
-func (*AddrSet) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{1} }
+func (*AddrSet) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{2} }
 
 func (m *AddrSet) GetAddrs() []*conduit_common.TcpAddress {
 	if m != nil {
@@ -180,7 +258,7 @@ type WeightedAddrSet struct {
 func (m *WeightedAddrSet) Reset()                    { *m = WeightedAddrSet{} }
 func (m *WeightedAddrSet) String() string            { return proto.CompactTextString(m) }
 func (*WeightedAddrSet) ProtoMessage()               {}
-func (*WeightedAddrSet) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{2} }
+func (*WeightedAddrSet) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{3} }
 
 func (m *WeightedAddrSet) GetAddrs() []*WeightedAddr {
 	if m != nil {
@@ -192,12 +270,19 @@ func (m *WeightedAddrSet) GetAddrs() []*WeightedAddr {
 type WeightedAddr struct {
 	Addr   *conduit_common.TcpAddress `protobuf:"bytes,1,opt,name=addr" json:"addr,omitempty"`
 	Weight uint32                     `protobuf:"varint,3,opt,name=weight" json:"weight,omitempty"`
+	// Describes the endpoint. Well-known keys are:
+	//
+	// - `pod`: the name of the endpoint's pod;
+	// - `zone`: the zone in which the endpoint runs;
+	// - `meshed`: "true" if the endpoint runs a proxy;
+	// - `h2`: "true" if the endpoint accepts HTTP/2.
+	Metadata map[string]string `protobuf:"bytes,4,rep,name=metadata" json:"metadata,omitempty" protobuf_key:"bytes,1,opt,name=key" protobuf_val:"bytes,2,opt,name=value"`
 }
 
 func (m *WeightedAddr) Reset()                    { *m = WeightedAddr{} }
 func (m *WeightedAddr) String() string            { return proto.CompactTextString(m) }
 func (*WeightedAddr) ProtoMessage()               {}
-func (*WeightedAddr) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{3} }
+func (*WeightedAddr) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{4} }
 
 func (m *WeightedAddr) GetAddr() *conduit_common.TcpAddress {
 	if m != nil {
@@ -213,11 +298,20 @@ func (m *WeightedAddr) GetWeight() uint32 {
 	return 0
 }
 
+func (m *WeightedAddr) GetMetadata() map[string]string {
+	if m != nil {
+		return m.Metadata
+	}
+	return nil
+}
+
 func init() {
 	proto.RegisterType((*Update)(nil), "conduit.proxy.destination.Update")
+	proto.RegisterType((*Balancer)(nil), "conduit.proxy.destination.Balancer")
 	proto.RegisterType((*AddrSet)(nil), "conduit.proxy.destination.AddrSet")
 	proto.RegisterType((*WeightedAddrSet)(nil), "conduit.proxy.destination.WeightedAddrSet")
 	proto.RegisterType((*WeightedAddr)(nil), "conduit.proxy.destination.WeightedAddr")
+	proto.RegisterEnum("conduit.proxy.destination.Balancer_Strategy", Balancer_Strategy_name, Balancer_Strategy_value)
 }
 
 // Reference imports to suppress errors if they are not otherwise used.
@@ -326,23 +420,34 @@ var _Destination_serviceDesc = grpc.ServiceDesc{
 func init() { proto.RegisterFile("proxy/destination/destination.proto", fileDescriptor0) }
 
 var fileDescriptor0 = []byte{
-	// 281 bytes of a gzipped FileDescriptorProto
-	0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0x8c, 0x91, 0x4b, 0x4b, 0xc4, 0x30,
-	0x14, 0x85, 0x27, 0x56, 0xa3, 0xdc, 0x2a, 0x42, 0x04, 0xa9, 0x75, 0x53, 0xe3, 0xc2, 0xe2, 0x22,
-	0x33, 0xd4, 0xa5, 0x0f, 0x50, 0x04, 0x75, 0x27, 0xf5, 0xb9, 0xad, 0xbd, 0x41, 0xbb, 0x68, 0x53,
-	0xd2, 0x8c, 0x8f, 0x7f, 0xe1, 0x4f, 0x96, 0xa6, 0xd1, 0xa9, 0x03, 0x16, 0x57, 0x79, 0xdc, 0x73,
-	0xbe, 0x9c, 0x9b, 0x0b, 0xbb, 0xb5, 0x56, 0xef, 0x1f, 0x63, 0x94, 0x8d, 0x29, 0xaa, 0xcc, 0x14,
-	0xaa, 0xea, 0xef, 0x45, 0xad, 0x95, 0x51, 0x6c, 0x2b, 0x57, 0x15, 0x4e, 0x0b, 0x23, 0xac, 0x58,
-	0xf4, 0x04, 0xe1, 0x46, 0xae, 0xca, 0x52, 0x55, 0xe3, 0x6e, 0xe9, 0xf4, 0xfc, 0x93, 0x00, 0xbd,
-	0xab, 0x31, 0x33, 0x92, 0x9d, 0x80, 0x97, 0x21, 0x06, 0x24, 0x22, 0xb1, 0x9f, 0xec, 0x8b, 0x3f,
-	0x41, 0xe2, 0x41, 0x16, 0xcf, 0x2f, 0x46, 0xe2, 0x29, 0xa2, 0xbe, 0x91, 0xe6, 0x72, 0x94, 0xb6,
-	0x46, 0x76, 0x04, 0x54, 0xcb, 0x52, 0xbd, 0xca, 0x60, 0xc1, 0x22, 0xf8, 0x00, 0x62, 0x66, 0x75,
-	0x9e, 0xb3, 0x15, 0xa0, 0x53, 0x9b, 0x83, 0x1f, 0xc2, 0xb2, 0x2b, 0xb3, 0x09, 0x2c, 0x65, 0x88,
-	0xba, 0x09, 0x48, 0xe4, 0xc5, 0x7e, 0x12, 0xfe, 0x10, 0x5d, 0x0f, 0xb7, 0x79, 0xdd, 0x4a, 0x65,
-	0xd3, 0xa4, 0x9d, 0x90, 0x5f, 0xc3, 0xfa, 0x5c, 0x3c, 0x76, 0xfc, 0x1b, 0xb2, 0xf7, 0xcf, 0xce,
-	0xbe, 0x89, 0xf7, 0xb0, 0xda, 0xbf, 0x66, 0x02, 0x16, 0xdb, 0x82, 0xfb, 0xa7, 0xa1, 0x48, 0x56,
-	0xc7, 0x36, 0x81, 0xbe, 0x59, 0x7f, 0xe0, 0x45, 0x24, 0x5e, 0x4b, 0xdd, 0x29, 0x79, 0x04, 0xff,
-	0x7c, 0xf6, 0x34, 0xbb, 0x02, 0xef, 0x42, 0x1a, 0xb6, 0x3d, 0xcf, 0xeb, 0x69, 0xc2, 0x9d, 0x81,
-	0xe8, 0xdd, 0x10, 0xf9, 0x68, 0x42, 0x9e, 0xa8, 0x1d, 0xed, 0xc1, 0x57, 0x00, 0x00, 0x00, 0xff,
-	0xff, 0xf8, 0xcd, 0x94, 0x88, 0x31, 0x02, 0x00, 0x00,
+	// 451 bytes of a gzipped FileDescriptorProto
+	0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0x8d, 0x53, 0xdb, 0x4e, 0xc2, 0x40,
+	0x14, 0xa4, 0x54, 0x6b, 0x39, 0x08, 0x36, 0x47, 0x63, 0x10, 0x5f, 0xb4, 0x3c, 0x68, 0x8c, 0xa9,
+	0x04, 0x63, 0x62, 0xbc, 0x25, 0x25, 0xd4, 0x4b, 0xbc, 0xc1, 0x16, 0xa2, 0x6f, 0xa4, 0xd2, 0x0d,
+	0x12, 0xa5, 0x25, 0xed, 0xe2, 0xe5, 0x73, 0xfc, 0x26, 0xff, 0xc1, 0xef, 0x70, 0xd9, 0x16, 0x44,
+	0x13, 0x09, 0x4f, 0xdd, 0x3d, 0x67, 0x66, 0x3a, 0x67, 0x67, 0x17, 0x0a, 0xbd, 0xc0, 0x7f, 0x7b,
+	0xdf, 0x71, 0x69, 0xc8, 0x3a, 0x9e, 0xc3, 0x3a, 0xbe, 0x37, 0xbe, 0x36, 0x78, 0x97, 0xf9, 0xb8,
+	0xd2, 0xf2, 0x3d, 0xb7, 0xdf, 0x61, 0x86, 0x00, 0x1b, 0x63, 0x80, 0xfc, 0x62, 0xcb, 0xef, 0x76,
+	0x39, 0x29, 0xfa, 0x44, 0x78, 0xfd, 0x53, 0x02, 0xa5, 0xd1, 0x73, 0x1d, 0x46, 0xf1, 0x04, 0x64,
+	0xc7, 0x75, 0x73, 0xd2, 0x9a, 0xb4, 0x99, 0x2e, 0x6d, 0x19, 0xff, 0x0a, 0x19, 0x77, 0xb4, 0xd3,
+	0x7e, 0x64, 0xd4, 0x35, 0x5d, 0x37, 0xb0, 0x29, 0x3b, 0x4f, 0x90, 0x01, 0x11, 0x8f, 0x40, 0x09,
+	0x68, 0xd7, 0x7f, 0xa1, 0xb9, 0xa4, 0x90, 0xd0, 0x27, 0x48, 0xfc, 0x50, 0x63, 0x0e, 0x9a, 0xa0,
+	0x3e, 0x38, 0xcf, 0x8e, 0xd7, 0xa2, 0x41, 0x4e, 0x16, 0xfc, 0xc2, 0x04, 0x7e, 0x39, 0x86, 0x72,
+	0x81, 0x11, 0xad, 0xac, 0x82, 0xd2, 0x17, 0xa3, 0xe8, 0x1f, 0x12, 0xa8, 0x43, 0x08, 0x9e, 0x83,
+	0x1a, 0xb2, 0x80, 0x97, 0xdb, 0xef, 0x62, 0xb8, 0x6c, 0x69, 0x7b, 0x0a, 0x65, 0xc3, 0x8e, 0x39,
+	0x64, 0xc4, 0xd6, 0x2f, 0x41, 0x1d, 0x56, 0x31, 0x0d, 0x73, 0x15, 0xeb, 0xd4, 0x6c, 0x5c, 0xd5,
+	0xb5, 0x04, 0x2e, 0x40, 0x9a, 0xdc, 0x36, 0x6e, 0x2a, 0x4d, 0x72, 0x5b, 0xbe, 0xb8, 0xd1, 0x24,
+	0x44, 0xc8, 0x5e, 0x59, 0xa6, 0x5d, 0x6f, 0x12, 0xab, 0xd6, 0xb0, 0xec, 0xba, 0xad, 0x25, 0x31,
+	0x03, 0xa9, 0xaa, 0x65, 0x5e, 0x36, 0xad, 0xbb, 0x6b, 0x53, 0x93, 0xf5, 0x43, 0x98, 0x8b, 0x4f,
+	0x01, 0x8b, 0x30, 0xcb, 0x0f, 0x30, 0x08, 0xb9, 0x3d, 0x99, 0x0f, 0x9e, 0x1f, 0xd9, 0x8b, 0xa3,
+	0xaa, 0xb7, 0x7a, 0x03, 0x28, 0x0d, 0x43, 0x12, 0x01, 0xf5, 0x2a, 0x2c, 0xfc, 0x49, 0x01, 0x8f,
+	0x7f, 0x8b, 0x6c, 0x4c, 0x19, 0xe0, 0x50, 0xf1, 0x4b, 0x82, 0xf9, 0xf1, 0x3a, 0x1a, 0x30, 0x33,
+	0xe8, 0xc4, 0xf7, 0x61, 0x92, 0x27, 0x81, 0xc3, 0x65, 0x50, 0x5e, 0x05, 0x5f, 0xc4, 0x97, 0x21,
+	0xf1, 0x0e, 0x6b, 0xa0, 0x76, 0x29, 0x73, 0x78, 0x2e, 0x4e, 0x6e, 0x46, 0x58, 0xdb, 0x9b, 0xd2,
+	0x9a, 0x71, 0x1d, 0xf3, 0x2c, 0x8f, 0x05, 0x3c, 0x87, 0xa1, 0x4c, 0xfe, 0x10, 0x32, 0xbf, 0x5a,
+	0xa8, 0x81, 0xfc, 0x44, 0xa3, 0x74, 0x53, 0x64, 0xb0, 0xc4, 0x25, 0x98, 0x7d, 0x71, 0x9e, 0xfb,
+	0xd1, 0x5d, 0x4c, 0x91, 0x68, 0x73, 0x90, 0xdc, 0x97, 0x4a, 0xf7, 0x90, 0xae, 0xfc, 0xfc, 0x10,
+	0x2f, 0x40, 0x3e, 0xe3, 0xa7, 0xb7, 0xfa, 0x77, 0xbe, 0x31, 0x4c, 0x7e, 0x7d, 0x82, 0xe1, 0xe8,
+	0xf1, 0xe8, 0x89, 0xa2, 0xf4, 0xa0, 0x88, 0x27, 0xb5, 0xfb, 0x0d, 0xff, 0xce, 0x23, 0xe0, 0xa9,
+	0x03, 0x00, 0x00,
 }
@@ -9,6 +9,7 @@ It is generated from these files:
 
 It has these top-level messages:
 	ReportRequest
+	Overflow
 	Process
 	ServerTransport
 	ClientTransport
@@ -80,6 +81,21 @@ type ReportRequest struct {
 	// across all ResponseScopes, we only need to report the max values for these
 	// buckets a single time.
 	HistogramBucketBoundsTenthMs []uint32 `protobuf:"varint,6,rep,packed,name=histogram_bucket_bounds_tenth_ms,json=histogramBucketBoundsTenthMs" json:"histogram_bucket_bounds_tenth_ms,omitempty"`
+	// The inclusive upper bound of each bucket in the response latency histogram,
+	// in microseconds. This describes the same buckets as
+	// `histogram_bucket_bounds_tenth_ms`, at a higher resolution: bounds in that
+	// field are rounded up to the nearest tenth of a millisecond.
+	HistogramBucketBoundsUs []uint64 `protobuf:"varint,10,rep,packed,name=histogram_bucket_bounds_us,json=histogramBucketBoundsUs" json:"histogram_bucket_bounds_us,omitempty"`
+	// The inclusive upper bound of each bucket in the connection duration
+	// histogram, in milliseconds. The last bucket is unbounded.
+	ConnectionDurationBucketBoundsMs []uint64 `protobuf:"varint,7,rep,packed,name=connection_duration_bucket_bounds_ms,json=connectionDurationBucketBoundsMs" json:"connection_duration_bucket_bounds_ms,omitempty"`
+	// Counts the events that could not be attributed to their own scope because
+	// the proxy was already tracking its maximum number of scopes.
+	Overflow *Overflow `protobuf:"bytes,8,opt,name=overflow" json:"overflow,omitempty"`
+	// The number of telemetry events that the proxy dropped since its last report
+	// because its event buffer was full. Aggregated metrics undercount traffic by
+	// (roughly) this many events.
+	DroppedEvents uint64 `protobuf:"varint,9,opt,name=dropped_events,json=droppedEvents" json:"dropped_events,omitempty"`
 }
 
 func (m *ReportRequest) Reset()                    { *m = ReportRequest{} }
@@ -129,6 +145,72 @@ func (m *ReportRequest) GetHistogramBucketBoundsTenthMs() []uint32 {
 	return nil
 }
 
+func (m *ReportRequest) GetHistogramBucketBoundsUs() []uint64 {
+	if m != nil {
+		return m.HistogramBucketBoundsUs
+	}
+	return nil
+}
+
+func (m *ReportRequest) GetConnectionDurationBucketBoundsMs() []uint64 {
+	if m != nil {
+		return m.ConnectionDurationBucketBoundsMs
+	}
+	return nil
+}
+
+func (m *ReportRequest) GetOverflow() *Overflow {
+	if m != nil {
+		return m.Overflow
+	}
+	return nil
+}
+
+func (m *ReportRequest) GetDroppedEvents() uint64 {
+	if m != nil {
+		return m.DroppedEvents
+	}
+	return 0
+}
+
+// Scopes are limited in number so that a proxy's memory use does not grow with
+// the number of peers it sees between reports. Once the limit is reached,
+// events for new transports are aggregated into a single overflow scope, which
+// is reported without its identifying field (i.e. `source_ip` or
+// `target_addr`). Requests for new scopes are only counted here, since every
+// request scope has a `ctx`.
+type Overflow struct {
+	ServerTransports uint32 `protobuf:"varint,1,opt,name=server_transports,json=serverTransports" json:"server_transports,omitempty"`
+	ClientTransports uint32 `protobuf:"varint,2,opt,name=client_transports,json=clientTransports" json:"client_transports,omitempty"`
+	Requests         uint32 `protobuf:"varint,3,opt,name=requests" json:"requests,omitempty"`
+}
+
+func (m *Overflow) Reset()                    { *m = Overflow{} }
+func (m *Overflow) String() string            { return proto.CompactTextString(m) }
+func (*Overflow) ProtoMessage()               {}
+func (*Overflow) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{1} }
+
+func (m *Overflow) GetServerTransports() uint32 {
+	if m != nil {
+		return m.ServerTransports
+	}
+	return 0
+}
+
+func (m *Overflow) GetClientTransports() uint32 {
+	if m != nil {
+		return m.ClientTransports
+	}
+	return 0
+}
+
+func (m *Overflow) GetRequests() uint32 {
+	if m != nil {
+		return m.Requests
+	}
+	return 0
+}
+
 type Process struct {
 	Node               string `protobuf:"bytes,1,opt,name=node" json:"node,omitempty"`
 	ScheduledInstance  string `protobuf:"bytes,2,opt,name=scheduled_instance,json=scheduledInstance" json:"scheduled_instance,omitempty"`
@@ -138,7 +220,7 @@ type Process struct {
 func (m *Process) Reset()                    { *m = Process{} }
 func (m *Process) String() string            { return proto.CompactTextString(m) }
 func (*Process) ProtoMessage()               {}
-func (*Process) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{1} }
+func (*Process) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{2} }
 
 func (m *Process) GetNode() string {
 	if m != nil {
@@ -162,16 +244,26 @@ func (m *Process) GetScheduledNamespace() string {
 }
 
 type ServerTransport struct {
-	SourceIp    *conduit_common.IPAddress `protobuf:"bytes,1,opt,name=source_ip,json=sourceIp" json:"source_ip,omitempty"`
-	Connects    uint32                    `protobuf:"varint,2,opt,name=connects" json:"connects,omitempty"`
-	Disconnects []*TransportSummary       `protobuf:"bytes,3,rep,name=disconnects" json:"disconnects,omitempty"`
-	Protocol    conduit_common.Protocol   `protobuf:"varint,4,opt,name=protocol,enum=conduit.common.Protocol" json:"protocol,omitempty"`
+	SourceIp *conduit_common.IPAddress `protobuf:"bytes,1,opt,name=source_ip,json=sourceIp" json:"source_ip,omitempty"`
+	Connects uint32                    `protobuf:"varint,2,opt,name=connects" json:"connects,omitempty"`
+	// Deprecated: disconnects are reported as `disconnect_duration_counts`.
+	Disconnects []*TransportSummary     `protobuf:"bytes,3,rep,name=disconnects" json:"disconnects,omitempty"`
+	Protocol    conduit_common.Protocol `protobuf:"varint,4,opt,name=protocol,enum=conduit.common.Protocol" json:"protocol,omitempty"`
+	// The number of connections closed, as a histogram of their durations with
+	// buckets given by `connection_duration_bucket_bounds_ms` in `ReportRequest`.
+	DisconnectDurationCounts []uint32 `protobuf:"varint,5,rep,packed,name=disconnect_duration_counts,json=disconnectDurationCounts" json:"disconnect_duration_counts,omitempty"`
+	// The number of requests from the source that were refused by a rate limit.
+	RateLimited uint32 `protobuf:"varint,6,opt,name=rate_limited,json=rateLimited" json:"rate_limited,omitempty"`
+	// The number of requests, and of connections that are not HTTP, from the
+	// source that the inbound authorization policy allowed or denied.
+	AuthzAllowed uint32 `protobuf:"varint,7,opt,name=authz_allowed,json=authzAllowed" json:"authz_allowed,omitempty"`
+	AuthzDenied  uint32 `protobuf:"varint,8,opt,name=authz_denied,json=authzDenied" json:"authz_denied,omitempty"`
 }
 
 func (m *ServerTransport) Reset()                    { *m = ServerTransport{} }
 func (m *ServerTransport) String() string            { return proto.CompactTextString(m) }
 func (*ServerTransport) ProtoMessage()               {}
-func (*ServerTransport) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{2} }
+func (*ServerTransport) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{3} }
 
 func (m *ServerTransport) GetSourceIp() *conduit_common.IPAddress {
 	if m != nil {
@@ -201,17 +293,55 @@ func (m *ServerTransport) GetProtocol() conduit_common.Protocol {
 	return conduit_common.Protocol_HTTP
 }
 
+func (m *ServerTransport) GetDisconnectDurationCounts() []uint32 {
+	if m != nil {
+		return m.DisconnectDurationCounts
+	}
+	return nil
+}
+
+func (m *ServerTransport) GetRateLimited() uint32 {
+	if m != nil {
+		return m.RateLimited
+	}
+	return 0
+}
+
+func (m *ServerTransport) GetAuthzAllowed() uint32 {
+	if m != nil {
+		return m.AuthzAllowed
+	}
+	return 0
+}
+
+func (m *ServerTransport) GetAuthzDenied() uint32 {
+	if m != nil {
+		return m.AuthzDenied
+	}
+	return 0
+}
+
 type ClientTransport struct {
-	TargetAddr  *conduit_common.TcpAddress `protobuf:"bytes,1,opt,name=target_addr,json=targetAddr" json:"target_addr,omitempty"`
-	Connects    uint32                     `protobuf:"varint,2,opt,name=connects" json:"connects,omitempty"`
-	Disconnects []*TransportSummary        `protobuf:"bytes,3,rep,name=disconnects" json:"disconnects,omitempty"`
-	Protocol    conduit_common.Protocol    `protobuf:"varint,4,opt,name=protocol,enum=conduit.common.Protocol" json:"protocol,omitempty"`
+	TargetAddr *conduit_common.TcpAddress `protobuf:"bytes,1,opt,name=target_addr,json=targetAddr" json:"target_addr,omitempty"`
+	Connects   uint32                     `protobuf:"varint,2,opt,name=connects" json:"connects,omitempty"`
+	// Deprecated: disconnects are reported as `disconnect_duration_counts`.
+	Disconnects []*TransportSummary     `protobuf:"bytes,3,rep,name=disconnects" json:"disconnects,omitempty"`
+	Protocol    conduit_common.Protocol `protobuf:"varint,4,opt,name=protocol,enum=conduit.common.Protocol" json:"protocol,omitempty"`
+	// The number of connections closed, as a histogram of their durations with
+	// buckets given by `connection_duration_bucket_bounds_ms` in `ReportRequest`.
+	DisconnectDurationCounts []uint32 `protobuf:"varint,5,rep,packed,name=disconnect_duration_counts,json=disconnectDurationCounts" json:"disconnect_duration_counts,omitempty"`
+	// The server name indicated by TLS clients of the target, if its
+	// connections were proxied as TLS.
+	TlsServerName string `protobuf:"bytes,6,opt,name=tls_server_name,json=tlsServerName" json:"tls_server_name,omitempty"`
+	// The protocol that connections to the target were upgraded to from
+	// HTTP/1, if they were proxied after an upgrade.
+	Upgrade string `protobuf:"bytes,7,opt,name=upgrade" json:"upgrade,omitempty"`
 }
 
 func (m *ClientTransport) Reset()                    { *m = ClientTransport{} }
 func (m *ClientTransport) String() string            { return proto.CompactTextString(m) }
 func (*ClientTransport) ProtoMessage()               {}
-func (*ClientTransport) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{3} }
+func (*ClientTransport) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{4} }
 
 func (m *ClientTransport) GetTargetAddr() *conduit_common.TcpAddress {
 	if m != nil {
@@ -241,6 +371,27 @@ func (m *ClientTransport) GetProtocol() conduit_common.Protocol {
 	return conduit_common.Protocol_HTTP
 }
 
+func (m *ClientTransport) GetDisconnectDurationCounts() []uint32 {
+	if m != nil {
+		return m.DisconnectDurationCounts
+	}
+	return nil
+}
+
+func (m *ClientTransport) GetTlsServerName() string {
+	if m != nil {
+		return m.TlsServerName
+	}
+	return ""
+}
+
+func (m *ClientTransport) GetUpgrade() string {
+	if m != nil {
+		return m.Upgrade
+	}
+	return ""
+}
+
 type TransportSummary struct {
 	DurationMs uint64 `protobuf:"varint,1,opt,name=duration_ms,json=durationMs" json:"duration_ms,omitempty"`
 	BytesSent  uint64 `protobuf:"varint,2,opt,name=bytes_sent,json=bytesSent" json:"bytes_sent,omitempty"`
@@ -249,7 +400,7 @@ type TransportSummary struct {
 func (m *TransportSummary) Reset()                    { *m = TransportSummary{} }
 func (m *TransportSummary) String() string            { return proto.CompactTextString(m) }
 func (*TransportSummary) ProtoMessage()               {}
-func (*TransportSummary) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{4} }
+func (*TransportSummary) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{5} }
 
 func (m *TransportSummary) GetDurationMs() uint64 {
 	if m != nil {
@@ -274,7 +425,7 @@ type RequestScope struct {
 func (m *RequestScope) Reset()                    { *m = RequestScope{} }
 func (m *RequestScope) String() string            { return proto.CompactTextString(m) }
 func (*RequestScope) ProtoMessage()               {}
-func (*RequestScope) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{5} }
+func (*RequestScope) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{6} }
 
 func (m *RequestScope) GetCtx() *RequestCtx {
 	if m != nil {
@@ -301,12 +452,21 @@ type RequestCtx struct {
 	SourceIp   *conduit_common.IPAddress  `protobuf:"bytes,1,opt,name=source_ip,json=sourceIp" json:"source_ip,omitempty"`
 	TargetAddr *conduit_common.TcpAddress `protobuf:"bytes,2,opt,name=target_addr,json=targetAddr" json:"target_addr,omitempty"`
 	Authority  string                     `protobuf:"bytes,3,opt,name=authority" json:"authority,omitempty"`
+	// The name of the route that the requests matched. Empty for requests that
+	// did not match a route.
+	Route string `protobuf:"bytes,4,opt,name=route" json:"route,omitempty"`
+	// The name of the destination that served the requests, such as the backend
+	// chosen by a traffic split. Empty for destinations that were not resolved
+	// by name.
+	DestinationName string `protobuf:"bytes,5,opt,name=destination_name,json=destinationName" json:"destination_name,omitempty"`
+	// Whether the requests were mirrored copies, whose responses were ignored.
+	Mirrored bool `protobuf:"varint,6,opt,name=mirrored" json:"mirrored,omitempty"`
 }
 
 func (m *RequestCtx) Reset()                    { *m = RequestCtx{} }
 func (m *RequestCtx) String() string            { return proto.CompactTextString(m) }
 func (*RequestCtx) ProtoMessage()               {}
-func (*RequestCtx) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{6} }
+func (*RequestCtx) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{7} }
 
 func (m *RequestCtx) GetSourceIp() *conduit_common.IPAddress {
 	if m != nil {
@@ -329,6 +489,27 @@ func (m *RequestCtx) GetAuthority() string {
 	return ""
 }
 
+func (m *RequestCtx) GetRoute() string {
+	if m != nil {
+		return m.Route
+	}
+	return ""
+}
+
+func (m *RequestCtx) GetDestinationName() string {
+	if m != nil {
+		return m.DestinationName
+	}
+	return ""
+}
+
+func (m *RequestCtx) GetMirrored() bool {
+	if m != nil {
+		return m.Mirrored
+	}
+	return false
+}
+
 type ResponseScope struct {
 	Ctx *ResponseCtx `protobuf:"bytes,1,opt,name=ctx" json:"ctx,omitempty"`
 	// Response latencies (time from request headers sent to response headers
@@ -338,12 +519,25 @@ type ResponseScope struct {
 	// latency falling into that bucket was observed.
 	ResponseLatencyCounts []uint32    `protobuf:"varint,2,rep,packed,name=response_latency_counts,json=responseLatencyCounts" json:"response_latency_counts,omitempty"`
 	Ends                  []*EosScope `protobuf:"bytes,3,rep,name=ends" json:"ends,omitempty"`
+	// Stream durations (time from request headers sent to the end of the
+	// response stream), as a histogram with the same buckets as
+	// `response_latency_counts`. For streaming responses, this may be much longer
+	// than the response latency.
+	StreamDurationCounts []uint32 `protobuf:"varint,4,rep,packed,name=stream_duration_counts,json=streamDurationCounts" json:"stream_duration_counts,omitempty"`
+	// The total number of response body bytes and data frames sent to the client.
+	BytesSent  uint64 `protobuf:"varint,5,opt,name=bytes_sent,json=bytesSent" json:"bytes_sent,omitempty"`
+	FramesSent uint64 `protobuf:"varint,6,opt,name=frames_sent,json=framesSent" json:"frames_sent,omitempty"`
+	// The total number of request body bytes and data frames sent to the server
+	// before the responses ended. Streams that are still sending their request
+	// body when the response ends are only counted in part.
+	RequestBytesSent  uint64 `protobuf:"varint,7,opt,name=request_bytes_sent,json=requestBytesSent" json:"request_bytes_sent,omitempty"`
+	RequestFramesSent uint64 `protobuf:"varint,8,opt,name=request_frames_sent,json=requestFramesSent" json:"request_frames_sent,omitempty"`
 }
 
 func (m *ResponseScope) Reset()                    { *m = ResponseScope{} }
 func (m *ResponseScope) String() string            { return proto.CompactTextString(m) }
 func (*ResponseScope) ProtoMessage()               {}
-func (*ResponseScope) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{7} }
+func (*ResponseScope) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{8} }
 
 func (m *ResponseScope) GetCtx() *ResponseCtx {
 	if m != nil {
@@ -366,6 +560,41 @@ func (m *ResponseScope) GetEnds() []*EosScope {
 	return nil
 }
 
+func (m *ResponseScope) GetStreamDurationCounts() []uint32 {
+	if m != nil {
+		return m.StreamDurationCounts
+	}
+	return nil
+}
+
+func (m *ResponseScope) GetBytesSent() uint64 {
+	if m != nil {
+		return m.BytesSent
+	}
+	return 0
+}
+
+func (m *ResponseScope) GetFramesSent() uint64 {
+	if m != nil {
+		return m.FramesSent
+	}
+	return 0
+}
+
+func (m *ResponseScope) GetRequestBytesSent() uint64 {
+	if m != nil {
+		return m.RequestBytesSent
+	}
+	return 0
+}
+
+func (m *ResponseScope) GetRequestFramesSent() uint64 {
+	if m != nil {
+		return m.RequestFramesSent
+	}
+	return 0
+}
+
 type ResponseCtx struct {
 	HttpStatusCode uint32 `protobuf:"varint,1,opt,name=http_status_code,json=httpStatusCode" json:"http_status_code,omitempty"`
 }
@@ -373,7 +602,7 @@ type ResponseCtx struct {
 func (m *ResponseCtx) Reset()                    { *m = ResponseCtx{} }
 func (m *ResponseCtx) String() string            { return proto.CompactTextString(m) }
 func (*ResponseCtx) ProtoMessage()               {}
-func (*ResponseCtx) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{8} }
+func (*ResponseCtx) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{9} }
 
 func (m *ResponseCtx) GetHttpStatusCode() uint32 {
 	if m != nil {
@@ -390,7 +619,7 @@ type EosScope struct {
 func (m *EosScope) Reset()                    { *m = EosScope{} }
 func (m *EosScope) String() string            { return proto.CompactTextString(m) }
 func (*EosScope) ProtoMessage()               {}
-func (*EosScope) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{9} }
+func (*EosScope) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{10} }
 
 func (m *EosScope) GetCtx() *EosCtx {
 	if m != nil {
@@ -417,7 +646,7 @@ type EosCtx struct {
 func (m *EosCtx) Reset()                    { *m = EosCtx{} }
 func (m *EosCtx) String() string            { return proto.CompactTextString(m) }
 func (*EosCtx) ProtoMessage()               {}
-func (*EosCtx) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{10} }
+func (*EosCtx) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{11} }
 
 type isEosCtx_End interface {
 	isEosCtx_End()
@@ -553,10 +782,11 @@ type ReportResponse struct {
 func (m *ReportResponse) Reset()                    { *m = ReportResponse{} }
 func (m *ReportResponse) String() string            { return proto.CompactTextString(m) }
 func (*ReportResponse) ProtoMessage()               {}
-func (*ReportResponse) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{11} }
+func (*ReportResponse) Descriptor() ([]byte, []int) { return fileDescriptor0, []int{12} }
 
 func init() {
 	proto.RegisterType((*ReportRequest)(nil), "conduit.proxy.telemetry.ReportRequest")
+	proto.RegisterType((*Overflow)(nil), "conduit.proxy.telemetry.Overflow")
 	proto.RegisterType((*Process)(nil), "conduit.proxy.telemetry.Process")
 	proto.RegisterType((*ServerTransport)(nil), "conduit.proxy.telemetry.ServerTransport")
 	proto.RegisterType((*ClientTransport)(nil), "conduit.proxy.telemetry.ClientTransport")
@@ -646,59 +876,79 @@ var _Telemetry_serviceDesc = grpc.ServiceDesc{
 func init() { proto.RegisterFile("proxy/telemetry/telemetry.proto", fileDescriptor0) }
 
 var fileDescriptor0 = []byte{
-	// 850 bytes of a gzipped FileDescriptorProto
-	0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0xcc, 0x55, 0xdd, 0x6e, 0xe3, 0x44,
-	0x14, 0xae, 0x9b, 0xa4, 0x4d, 0x4e, 0x36, 0xdd, 0xec, 0x2c, 0xb0, 0xa6, 0x5a, 0xd4, 0x60, 0x60,
-	0x09, 0x08, 0x52, 0x51, 0xd8, 0x22, 0xc1, 0xd5, 0xa6, 0xbb, 0x68, 0x2b, 0xd8, 0xb6, 0x9a, 0xa4,
-	0xe2, 0x82, 0x0b, 0xcb, 0x1d, 0x1f, 0x35, 0x16, 0xf1, 0x8c, 0x99, 0x19, 0xa3, 0xe6, 0x02, 0xf1,
-	0x28, 0x88, 0x97, 0xe0, 0x99, 0xb8, 0x80, 0x77, 0x40, 0x9e, 0xf1, 0x38, 0x6e, 0x50, 0x1a, 0xe0,
-	0x8a, 0xab, 0x64, 0xce, 0xf7, 0x9d, 0x6f, 0xce, 0xef, 0x18, 0x0e, 0x32, 0x29, 0x6e, 0x16, 0x87,
-	0x1a, 0xe7, 0x98, 0xa2, 0x96, 0xb5, 0x7f, 0xa3, 0x4c, 0x0a, 0x2d, 0xc8, 0x23, 0x26, 0x78, 0x9c,
-	0x27, 0x7a, 0x64, 0x88, 0xa3, 0x0a, 0xde, 0x7f, 0xc8, 0x44, 0x9a, 0x0a, 0x7e, 0x68, 0x7f, 0x2c,
-	0x3b, 0xf8, 0xa3, 0x01, 0x3d, 0x8a, 0x99, 0x90, 0x9a, 0xe2, 0x0f, 0x39, 0x2a, 0x4d, 0xbe, 0x80,
-	0xdd, 0x4c, 0x0a, 0x86, 0x4a, 0xf9, 0xde, 0xc0, 0x1b, 0x76, 0x8f, 0x06, 0xa3, 0x35, 0x8a, 0xa3,
-	0x0b, 0xcb, 0xa3, 0xce, 0x81, 0x8c, 0xa1, 0x65, 0x38, 0xfe, 0xf6, 0xc0, 0x1b, 0xee, 0x1d, 0x7d,
-	0xb4, 0xd6, 0xf3, 0xd6, 0x95, 0x85, 0xce, 0xcd, 0x82, 0x5a, 0x57, 0x72, 0x09, 0x0f, 0x14, 0xca,
-	0x1f, 0x51, 0x86, 0x5a, 0x46, 0x5c, 0x15, 0x3c, 0xe5, 0x37, 0x06, 0x8d, 0x61, 0xf7, 0x68, 0xb8,
-	0x56, 0x6f, 0x62, 0x3c, 0xa6, 0xce, 0x81, 0xf6, 0xd5, 0x6d, 0x83, 0x2a, 0x64, 0xd9, 0x3c, 0x41,
-	0xae, 0xeb, 0xb2, 0xcd, 0x0d, 0xb2, 0x27, 0xc6, 0xa3, 0x26, 0xcb, 0x6e, 0x1b, 0x14, 0x79, 0x06,
-	0x6d, 0x69, 0xb3, 0x50, 0x7e, 0xcb, 0xa8, 0xbd, 0x77, 0x47, 0xd2, 0x86, 0x38, 0x61, 0x22, 0x43,
-	0x5a, 0xb9, 0x91, 0xaf, 0x60, 0x30, 0x4b, 0x94, 0x16, 0xd7, 0x32, 0x4a, 0xc3, 0xab, 0x9c, 0x7d,
-	0x8f, 0x3a, 0xbc, 0x12, 0x39, 0x8f, 0x55, 0xa8, 0x91, 0xeb, 0x59, 0x98, 0x2a, 0x7f, 0x67, 0xd0,
-	0x18, 0xf6, 0xe8, 0xe3, 0x8a, 0x37, 0x36, 0xb4, 0xb1, 0x61, 0x4d, 0x0b, 0xd2, 0x2b, 0x15, 0x04,
-	0xd0, 0x32, 0x85, 0x24, 0x5d, 0xd8, 0x3d, 0x3d, 0x1b, 0x9f, 0x5f, 0x9e, 0x3d, 0xef, 0x6f, 0x91,
-	0x7b, 0xd0, 0x3e, 0xbf, 0x9c, 0xda, 0x93, 0x17, 0xfc, 0x04, 0xbb, 0x65, 0xd3, 0x08, 0x81, 0x26,
-	0x17, 0x31, 0x9a, 0x26, 0x77, 0xa8, 0xf9, 0x4f, 0x3e, 0x06, 0xa2, 0xd8, 0x0c, 0xe3, 0x7c, 0x8e,
-	0x71, 0x98, 0x70, 0xa5, 0x23, 0xce, 0xd0, 0x34, 0xb3, 0x43, 0x1f, 0x54, 0xc8, 0x69, 0x09, 0x90,
-	0x43, 0x78, 0xb8, 0xa4, 0xf3, 0x28, 0x45, 0x95, 0x45, 0x0c, 0xfd, 0x86, 0xe1, 0x2f, 0x95, 0xce,
-	0x1c, 0x12, 0xfc, 0xee, 0xc1, 0xfd, 0x95, 0x56, 0x91, 0x63, 0xe8, 0x28, 0x91, 0x4b, 0x86, 0x61,
-	0x92, 0x95, 0x13, 0xf7, 0x66, 0x55, 0xc2, 0x72, 0x56, 0x4f, 0x2f, 0x9e, 0xc5, 0xb1, 0x2c, 0x46,
-	0xad, 0x6d, 0xb9, 0xa7, 0x19, 0xd9, 0x87, 0x36, 0x13, 0x9c, 0x23, 0xd3, 0xca, 0x44, 0xd8, 0xa3,
-	0xd5, 0x99, 0x7c, 0x0d, 0xdd, 0x38, 0x51, 0x15, 0x6c, 0xa7, 0xe7, 0x83, 0xb5, 0x8d, 0xa9, 0x82,
-	0x99, 0xe4, 0x69, 0x1a, 0xc9, 0x05, 0xad, 0x7b, 0x93, 0xcf, 0xa0, 0x6d, 0x76, 0x85, 0x89, 0xb9,
-	0xdf, 0x34, 0x73, 0xed, 0xaf, 0xc6, 0x77, 0x51, 0xe2, 0xb4, 0x62, 0x06, 0x7f, 0x7a, 0x70, 0x7f,
-	0x65, 0x7c, 0xc8, 0x97, 0xd0, 0xd5, 0x91, 0xbc, 0x46, 0x1d, 0x46, 0x71, 0x2c, 0xcb, 0x64, 0xf7,
-	0x57, 0xc5, 0xa6, 0x2c, 0x73, 0xd9, 0x82, 0xa5, 0x17, 0xc7, 0xff, 0x7b, 0xbe, 0x14, 0xfa, 0xab,
-	0xb2, 0xe4, 0x00, 0xba, 0x71, 0x2e, 0x23, 0x9d, 0x08, 0x5e, 0x0c, 0x71, 0x91, 0x6f, 0x93, 0x82,
-	0x33, 0xbd, 0x52, 0xe4, 0x2d, 0x80, 0xab, 0x85, 0x46, 0x15, 0x2a, 0xe4, 0xda, 0x64, 0xd5, 0xa4,
-	0x1d, 0x63, 0x99, 0x20, 0xd7, 0xc1, 0xaf, 0x1e, 0xdc, 0xab, 0x2f, 0x0d, 0x79, 0x0a, 0x0d, 0xa6,
-	0x6f, 0xca, 0xc2, 0xbd, 0xb3, 0x69, 0xd1, 0x4e, 0xf4, 0x0d, 0x2d, 0xf8, 0xe4, 0x35, 0x68, 0x31,
-	0x91, 0x97, 0x37, 0xf4, 0xa8, 0x3d, 0x90, 0xe7, 0xd0, 0x91, 0xa8, 0x32, 0xc1, 0x15, 0xba, 0x92,
-	0x3d, 0xb9, 0x43, 0xd2, 0x32, 0xed, 0xf2, 0x2e, 0x1d, 0x83, 0x5f, 0x3c, 0x80, 0xe5, 0x7d, 0xff,
-	0x79, 0x9a, 0x57, 0x46, 0x63, 0xfb, 0x5f, 0x8d, 0xc6, 0x63, 0xe8, 0x44, 0xb9, 0x9e, 0x09, 0x99,
-	0xe8, 0x45, 0xb9, 0x7d, 0x4b, 0x43, 0xf0, 0x9b, 0x57, 0x3c, 0xf1, 0xb5, 0xf0, 0xc9, 0x71, 0xbd,
-	0x8c, 0xef, 0x6e, 0xcc, 0xb9, 0xaa, 0xe3, 0x31, 0x3c, 0x72, 0x89, 0x87, 0xf3, 0x48, 0x23, 0x67,
-	0x8b, 0xd0, 0xd4, 0xb2, 0x98, 0xc8, 0xe2, 0x81, 0x7a, 0xdd, 0xc1, 0xdf, 0x58, 0xf4, 0xc4, 0x80,
-	0xe4, 0x29, 0x34, 0x91, 0xc7, 0xae, 0xc8, 0x6f, 0xaf, 0xbd, 0xf0, 0x85, 0x50, 0xb6, 0xbe, 0x86,
-	0x1e, 0x7c, 0x0e, 0xdd, 0x5a, 0x08, 0x64, 0x08, 0xfd, 0x99, 0xd6, 0x59, 0xa8, 0x74, 0xa4, 0x73,
-	0x15, 0x32, 0xf7, 0x78, 0xf5, 0xe8, 0x5e, 0x61, 0x9f, 0x18, 0xf3, 0x89, 0x88, 0x31, 0xf8, 0x16,
-	0xda, 0x4e, 0x8a, 0x7c, 0x52, 0xcf, 0xf5, 0xe0, 0xae, 0xab, 0xab, 0x34, 0x7d, 0xd8, 0x55, 0x5a,
-	0x62, 0x94, 0xba, 0x45, 0x73, 0xc7, 0xe0, 0x67, 0xd8, 0xb1, 0x44, 0xf2, 0x21, 0xf4, 0xaf, 0x65,
-	0xc6, 0xfe, 0x1e, 0xcc, 0xcb, 0x2d, 0xba, 0x57, 0x20, 0xcb, 0x70, 0x0a, 0xae, 0x44, 0x85, 0x3a,
-	0x44, 0x29, 0x85, 0xb4, 0xdc, 0x6d, 0xc7, 0x35, 0xc8, 0x8b, 0x02, 0x30, 0xdc, 0x37, 0xa0, 0x25,
-	0xf4, 0x0c, 0xa5, 0x69, 0x63, 0xfb, 0xe5, 0x16, 0xb5, 0xc7, 0x71, 0x0b, 0x1a, 0xc8, 0xe3, 0xa0,
-	0x0f, 0x7b, 0xee, 0xd3, 0x69, 0x0b, 0x73, 0x34, 0x83, 0xce, 0xd4, 0x65, 0x41, 0xbe, 0x83, 0x1d,
-	0x0b, 0x93, 0x27, 0xff, 0xec, 0xd3, 0xbb, 0xff, 0xfe, 0x46, 0x9e, 0xbd, 0x27, 0xd8, 0xba, 0xda,
-	0x31, 0xbb, 0xfe, 0xe9, 0x5f, 0x01, 0x00, 0x00, 0xff, 0xff, 0x2c, 0x8a, 0x8a, 0xb8, 0x82, 0x08,
-	0x00, 0x00,
+	// 1179 bytes of a gzipped FileDescriptorProto
+	0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0xff, 0xdd, 0x56, 0xcd, 0x72, 0x1b, 0x45,
+	0x10, 0xb6, 0xfe, 0x2c, 0xa9, 0x65, 0xd9, 0xf2, 0x38, 0xc4, 0x8b, 0x2a, 0x94, 0x9d, 0x4d, 0x62,
+	0x1c, 0x12, 0xe4, 0xc2, 0x90, 0x50, 0xc5, 0xcf, 0xc1, 0xb2, 0x9d, 0x8a, 0x8b, 0x60, 0xbb, 0xc6,
+	0x76, 0x71, 0xe0, 0xb0, 0x25, 0xef, 0x4e, 0xac, 0x2d, 0xb4, 0x3f, 0xcc, 0xcc, 0x06, 0x8b, 0x2a,
+	0x0a, 0xce, 0xdc, 0x78, 0x04, 0x9e, 0x8c, 0x27, 0xe0, 0x0d, 0x38, 0xd0, 0x33, 0xb3, 0xbb, 0x5a,
+	0x2b, 0x96, 0x0d, 0x1c, 0x39, 0x49, 0xdd, 0xfd, 0xcd, 0x37, 0x33, 0xdd, 0x5f, 0xf7, 0x2c, 0xac,
+	0xc5, 0x3c, 0xba, 0x1c, 0x6f, 0x49, 0x36, 0x62, 0x01, 0x93, 0xbc, 0xf0, 0xaf, 0x87, 0x11, 0x19,
+	0x91, 0x55, 0x37, 0x0a, 0xbd, 0xc4, 0x97, 0x3d, 0x0d, 0xec, 0xe5, 0xe1, 0xee, 0x8a, 0x1b, 0x05,
+	0x41, 0x14, 0x6e, 0x99, 0x1f, 0x83, 0xb6, 0xff, 0xa8, 0x41, 0x9b, 0xb2, 0x38, 0xe2, 0x92, 0xb2,
+	0xef, 0x13, 0x26, 0x24, 0xf9, 0x0c, 0xea, 0x18, 0x72, 0x99, 0x10, 0x56, 0x69, 0xbd, 0xb4, 0xd9,
+	0xda, 0x5e, 0xef, 0xcd, 0x60, 0xec, 0x1d, 0x1b, 0x1c, 0xcd, 0x16, 0x90, 0x3e, 0xd4, 0x34, 0xc6,
+	0x2a, 0xe3, 0xca, 0xc5, 0xed, 0xa7, 0x33, 0x57, 0x5e, 0xd9, 0x52, 0xf1, 0x5c, 0x8e, 0xa9, 0x59,
+	0x4a, 0xce, 0x60, 0x59, 0x30, 0xfe, 0x86, 0x71, 0x47, 0xf2, 0x41, 0x28, 0x14, 0x4e, 0x58, 0x95,
+	0xf5, 0x0a, 0x9e, 0x64, 0x73, 0x26, 0xdf, 0x89, 0x5e, 0x71, 0x9a, 0x2d, 0xa0, 0x1d, 0x71, 0xd5,
+	0x21, 0x14, 0xad, 0x3b, 0xf2, 0x59, 0x28, 0x8b, 0xb4, 0xd5, 0x5b, 0x68, 0x77, 0xf5, 0x8a, 0x02,
+	0xad, 0x7b, 0xd5, 0x21, 0xc8, 0x0e, 0x34, 0xb8, 0xb9, 0x85, 0xb0, 0x6a, 0x9a, 0xed, 0xd1, 0x0d,
+	0x97, 0xd6, 0xc0, 0x13, 0x37, 0x8a, 0x19, 0xcd, 0x97, 0x91, 0x17, 0xb0, 0x3e, 0xf4, 0x85, 0x8c,
+	0x2e, 0xf8, 0x20, 0x70, 0xce, 0x13, 0xf7, 0x3b, 0x26, 0x9d, 0xf3, 0x28, 0x09, 0x3d, 0xe1, 0x48,
+	0xdc, 0x6c, 0xe8, 0x04, 0xc2, 0x9a, 0x47, 0xea, 0x36, 0xbd, 0x97, 0xe3, 0xfa, 0x1a, 0xd6, 0xd7,
+	0xa8, 0x53, 0x05, 0xfa, 0x5a, 0x90, 0xcf, 0xa1, 0x3b, 0x8b, 0x27, 0x11, 0x16, 0x20, 0x43, 0x95,
+	0xae, 0x5e, 0xcb, 0x70, 0x26, 0xc8, 0x21, 0x3c, 0xc4, 0x63, 0x87, 0xcc, 0x95, 0x7e, 0x14, 0x3a,
+	0x5e, 0xc2, 0x07, 0xfa, 0xcf, 0x55, 0x1a, 0x3c, 0x48, 0x5d, 0xd3, 0xac, 0x4f, 0xb0, 0x7b, 0x29,
+	0xb4, 0xc8, 0x87, 0x87, 0xf9, 0x12, 0x1a, 0x11, 0x16, 0xe0, 0xf5, 0x28, 0xfa, 0xc1, 0x6a, 0x68,
+	0x19, 0xdd, 0x9f, 0x99, 0x97, 0xa3, 0x14, 0x48, 0xf3, 0x25, 0xe4, 0x11, 0x2c, 0x7a, 0x3c, 0x8a,
+	0x63, 0xe6, 0x39, 0xec, 0x0d, 0xde, 0x4f, 0x58, 0x4d, 0x24, 0xa9, 0xd2, 0x76, 0xea, 0xdd, 0xd7,
+	0x4e, 0xdb, 0x86, 0x9a, 0xd6, 0x0e, 0x69, 0x41, 0xfd, 0xe0, 0xb0, 0x7f, 0x74, 0x76, 0xb8, 0xd7,
+	0x99, 0x23, 0x0b, 0xd0, 0x38, 0x3a, 0x3b, 0x35, 0x56, 0xc9, 0xfe, 0xa5, 0x84, 0x66, 0xc6, 0xfb,
+	0xe4, 0x3a, 0x71, 0x29, 0x99, 0xb7, 0xaf, 0x91, 0xcc, 0x93, 0xeb, 0x24, 0x53, 0x36, 0xe0, 0xb7,
+	0x84, 0xd0, 0x2d, 0x08, 0xa1, 0xa2, 0x31, 0xb9, 0x6d, 0xff, 0x04, 0xf5, 0xb4, 0x55, 0x08, 0x81,
+	0x6a, 0x18, 0x79, 0x4c, 0xef, 0xd9, 0xa4, 0xfa, 0x3f, 0xf9, 0x10, 0x88, 0x70, 0x87, 0xcc, 0x4b,
+	0x46, 0x78, 0x5d, 0x3f, 0x14, 0x72, 0x10, 0xba, 0x4c, 0x6f, 0xd4, 0xa4, 0xcb, 0x79, 0xe4, 0x20,
+	0x0d, 0x90, 0x2d, 0x58, 0x99, 0xc0, 0xc3, 0x41, 0xc0, 0x44, 0x3c, 0x40, 0x7c, 0x45, 0xe3, 0x27,
+	0x4c, 0x87, 0x59, 0xc4, 0xfe, 0xad, 0x02, 0x4b, 0x53, 0x0d, 0x42, 0x9e, 0x43, 0x53, 0x44, 0x09,
+	0x77, 0x99, 0xe3, 0xc7, 0x69, 0x9f, 0xbf, 0x9b, 0x17, 0x28, 0x9d, 0x10, 0x07, 0xc7, 0x3b, 0x9e,
+	0xc7, 0x55, 0x83, 0x37, 0x0c, 0xf6, 0x20, 0x56, 0xd7, 0x4c, 0x6b, 0x9f, 0xa5, 0x22, 0xb7, 0xc9,
+	0x57, 0xd0, 0xf2, 0x7c, 0x91, 0x87, 0x4d, 0xcf, 0x3e, 0x9e, 0x59, 0xf6, 0xfc, 0x30, 0x27, 0x49,
+	0x10, 0x0c, 0xf8, 0x98, 0x16, 0x57, 0x93, 0x4f, 0xa0, 0xa1, 0x27, 0x94, 0x1b, 0x8d, 0xb0, 0x4d,
+	0xd5, 0x34, 0xb1, 0xa6, 0xcf, 0x77, 0x9c, 0xc6, 0x69, 0x8e, 0x24, 0x5f, 0x40, 0x77, 0x42, 0x32,
+	0x91, 0xb1, 0x8b, 0xb2, 0x4c, 0x1b, 0xb4, 0x4d, 0xad, 0x09, 0x22, 0x13, 0xef, 0xae, 0x8e, 0x93,
+	0xfb, 0xb0, 0x80, 0x36, 0x73, 0x46, 0x7e, 0xe0, 0x4b, 0xe6, 0x61, 0xd7, 0xa9, 0x0b, 0xb6, 0x94,
+	0xef, 0x95, 0x71, 0x91, 0x07, 0xd0, 0x1e, 0x24, 0x72, 0xf8, 0xa3, 0x33, 0x18, 0xa1, 0x9e, 0x10,
+	0x53, 0xd7, 0x98, 0x05, 0xed, 0xdc, 0x31, 0x3e, 0xc5, 0x63, 0x40, 0x1e, 0x0b, 0x7d, 0xc4, 0x34,
+	0x0c, 0x8f, 0xf6, 0xed, 0x69, 0x97, 0xfd, 0x67, 0x19, 0x96, 0xa6, 0xa6, 0x0b, 0x36, 0x70, 0x4b,
+	0x0e, 0xf8, 0x05, 0xf6, 0xdb, 0x00, 0xf3, 0x9e, 0x56, 0xa5, 0x3b, 0x7d, 0xeb, 0x53, 0x37, 0xce,
+	0xca, 0x02, 0x06, 0xae, 0xcc, 0xff, 0x77, 0x61, 0x36, 0x60, 0x49, 0x8e, 0x84, 0x93, 0xb6, 0xae,
+	0xd2, 0xbc, 0xae, 0x4d, 0x93, 0xb6, 0xd1, 0x6d, 0xa4, 0xad, 0xe4, 0x4e, 0x2c, 0xa8, 0x27, 0x31,
+	0x4e, 0x37, 0x6c, 0xb0, 0xba, 0x8e, 0x67, 0xa6, 0x4d, 0xa1, 0x33, 0x7d, 0x2d, 0xb2, 0x86, 0x69,
+	0xc9, 0x0e, 0x12, 0x98, 0x31, 0x50, 0xa5, 0x90, 0xb9, 0x70, 0x88, 0xbd, 0x07, 0x70, 0x3e, 0x96,
+	0x4c, 0x6d, 0x1c, 0x4a, 0x9d, 0xd5, 0x2a, 0x6d, 0x6a, 0xcf, 0x09, 0x3a, 0xec, 0xdf, 0x4b, 0xb0,
+	0x50, 0x9c, 0xe9, 0xe4, 0x19, 0x54, 0x5c, 0x79, 0x99, 0x16, 0xee, 0xc1, 0x6d, 0xef, 0xc0, 0xae,
+	0xbc, 0xa4, 0x0a, 0x4f, 0xee, 0x40, 0x4d, 0xe7, 0x21, 0xad, 0x9b, 0x31, 0xc8, 0x1e, 0x34, 0xb1,
+	0xc8, 0x71, 0x14, 0x0a, 0x96, 0x95, 0x6c, 0xe3, 0x06, 0x4a, 0x83, 0x34, 0x6f, 0xcb, 0x64, 0xa1,
+	0xfd, 0x57, 0x09, 0x60, 0xb2, 0xdf, 0x7f, 0x6e, 0xfb, 0x29, 0x69, 0x96, 0xff, 0x95, 0x34, 0xef,
+	0x41, 0x53, 0x49, 0x3f, 0xe2, 0xbe, 0x1c, 0xa7, 0x63, 0x6a, 0xe2, 0x50, 0xb7, 0xe7, 0x51, 0x22,
+	0x99, 0x16, 0x53, 0x93, 0x1a, 0x83, 0x3c, 0x86, 0x8e, 0x87, 0x67, 0xf6, 0x43, 0x53, 0x1e, 0x5d,
+	0xf2, 0x9a, 0x06, 0x2c, 0x15, 0xfc, 0xba, 0xe8, 0xa8, 0xfc, 0xc0, 0xe7, 0x3c, 0xe2, 0x69, 0xc7,
+	0x36, 0x68, 0x6e, 0xdb, 0xbf, 0x56, 0xd4, 0xe7, 0x4d, 0x21, 0x37, 0x98, 0x81, 0x42, 0x8d, 0x1e,
+	0xde, 0x9a, 0xd0, 0xbc, 0x48, 0xcf, 0x61, 0x35, 0xcb, 0xaa, 0x33, 0xc2, 0x81, 0x10, 0xba, 0xe3,
+	0x4c, 0xbd, 0x65, 0xad, 0xde, 0x77, 0xb2, 0xf0, 0x2b, 0x13, 0x4d, 0xa5, 0xfb, 0x0c, 0xaa, 0x0c,
+	0x9f, 0xc4, 0xb4, 0x82, 0xb3, 0x1f, 0xc1, 0xfd, 0x48, 0x98, 0xe2, 0x69, 0x38, 0x76, 0xd9, 0x5d,
+	0x21, 0x39, 0xc3, 0x97, 0x7c, 0xba, 0x57, 0xaa, 0x7a, 0xb7, 0x3b, 0x26, 0x3a, 0xd5, 0x27, 0x57,
+	0x05, 0x5b, 0x9b, 0x12, 0xac, 0x12, 0xfc, 0x6b, 0xae, 0x9e, 0x05, 0x13, 0x9f, 0x37, 0x82, 0x37,
+	0x2e, 0x0d, 0x78, 0x0a, 0x24, 0x7d, 0xb4, 0x9c, 0x02, 0x4f, 0x5d, 0xe3, 0x3a, 0x69, 0xa4, 0x9f,
+	0xd3, 0xf5, 0x60, 0x25, 0x43, 0x17, 0x69, 0x1b, 0x1a, 0xbe, 0x9c, 0x86, 0x5e, 0xe4, 0xec, 0xf6,
+	0xa7, 0xd0, 0x2a, 0xa4, 0x95, 0x6c, 0x42, 0x67, 0x28, 0x65, 0xec, 0xe0, 0xb3, 0x26, 0x13, 0x81,
+	0xd7, 0x4b, 0x9f, 0xc5, 0x36, 0x5d, 0x54, 0xfe, 0x13, 0xed, 0xde, 0x45, 0xaf, 0xfd, 0x0d, 0x34,
+	0xb2, 0xf4, 0x90, 0x8f, 0x8a, 0xf5, 0x5b, 0xbb, 0x29, 0x9d, 0x79, 0xe9, 0x70, 0x2a, 0x98, 0x6c,
+	0x65, 0x93, 0x31, 0x33, 0xed, 0x9f, 0x61, 0xde, 0x00, 0xc9, 0x07, 0xd0, 0xb9, 0xe0, 0xb1, 0xfb,
+	0xf6, 0x61, 0x5e, 0xce, 0xd1, 0x45, 0x15, 0x99, 0x1c, 0x47, 0x61, 0xb1, 0xd6, 0xd8, 0x0b, 0x4c,
+	0xa9, 0xcc, 0x60, 0xcb, 0x19, 0x56, 0x47, 0xf6, 0x55, 0x40, 0x63, 0xef, 0x42, 0x2d, 0x92, 0x43,
+	0xc6, 0xb5, 0xee, 0x1b, 0x08, 0x30, 0x66, 0xbf, 0x06, 0x15, 0xac, 0xb3, 0xdd, 0x81, 0xc5, 0xec,
+	0x53, 0xd8, 0x24, 0x66, 0x7b, 0x08, 0xcd, 0xd3, 0xec, 0x16, 0xe4, 0x5b, 0x98, 0x37, 0x61, 0xb2,
+	0xf1, 0xcf, 0x3e, 0xa5, 0xbb, 0xef, 0xdf, 0x8a, 0x33, 0xfb, 0xd8, 0x73, 0xe7, 0xf3, 0x7a, 0x38,
+	0x7f, 0xfc, 0x37, 0xfa, 0x1c, 0x2c, 0xc7, 0x52, 0x0c, 0x00, 0x00,
 }
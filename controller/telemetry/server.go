@@ -32,7 +32,7 @@ const (
 )
 
 var (
-	requestLabels = []string{"source_deployment", "target_deployment"}
+	requestLabels = []string{"source_deployment", "target_deployment", "route", "destination_name", "mirrored"}
 	requestsTotal = prometheus.NewCounterVec(
 		prometheus.CounterOpts{
 			Name: "requests_total",
@@ -75,6 +75,40 @@ var (
 		},
 		reportsLabels,
 	)
+
+	overflowLabels = []string{"pod", "scope"}
+	overflowTotal  = prometheus.NewCounterVec(
+		prometheus.CounterOpts{
+			Name: "overflow_total",
+			Help: "Total number of events a proxy aggregated without a scope because its scope limit was reached",
+		},
+		overflowLabels,
+	)
+
+	droppedEventsTotal = prometheus.NewCounterVec(
+		prometheus.CounterOpts{
+			Name: "dropped_events_total",
+			Help: "Total number of telemetry events a proxy dropped because its event buffer was full",
+		},
+		reportsLabels,
+	)
+
+	latencyBoundsMismatchesTotal = prometheus.NewCounterVec(
+		prometheus.CounterOpts{
+			Name: "latency_bounds_mismatches_total",
+			Help: "Total number of reports whose microsecond latency bucket bounds did not match their tenth-of-a-millisecond bounds",
+		},
+		reportsLabels,
+	)
+
+	rateLimitedLabels = []string{"pod", "source_deployment"}
+	rateLimitedTotal  = prometheus.NewCounterVec(
+		prometheus.CounterOpts{
+			Name: "rate_limited_total",
+			Help: "Total number of inbound requests a proxy refused because of a rate limit",
+		},
+		rateLimitedLabels,
+	)
 )
 
 func init() {
@@ -82,6 +116,10 @@ func init() {
 	prometheus.MustRegister(responsesTotal)
 	prometheus.MustRegister(responseLatency)
 	prometheus.MustRegister(reportsTotal)
+	prometheus.MustRegister(overflowTotal)
+	prometheus.MustRegister(droppedEventsTotal)
+	prometheus.MustRegister(latencyBoundsMismatchesTotal)
+	prometheus.MustRegister(rateLimitedTotal)
 }
 
 type (
@@ -300,6 +338,47 @@ func (s *server) Report(ctx context.Context, req *write.ReportRequest) (*write.R
 
 	reportsTotal.With(prometheus.Labels{"pod": id}).Inc()
 
+	if req.DroppedEvents > 0 {
+		logCtx.Warnf("Proxy dropped %d telemetry events since its last report", req.DroppedEvents)
+		droppedEventsTotal.With(prometheus.Labels{"pod": id}).Add(float64(req.DroppedEvents))
+	}
+
+	if overflow := req.GetOverflow(); overflow != nil {
+		counts := map[string]uint32{
+			"server_transports": overflow.ServerTransports,
+			"client_transports": overflow.ClientTransports,
+			"requests":          overflow.Requests,
+		}
+		for scope, count := range counts {
+			if count == 0 {
+				continue
+			}
+			logCtx.Warnf("Proxy reached its scope limit; %d %s events were not attributed", count, scope)
+			overflowTotal.With(prometheus.Labels{"pod": id, "scope": scope}).Add(float64(count))
+		}
+	}
+
+	for _, transport := range req.ServerTransports {
+		if transport.RateLimited == 0 {
+			continue
+		}
+		rateLimitedTotal.With(prometheus.Labels{
+			"pod":               id,
+			"source_deployment": s.getDeployment(transport.SourceIp),
+		}).Add(float64(transport.RateLimited))
+	}
+
+	// Prefer the microsecond bucket bounds, when the proxy reports them.
+	boundsUs := req.HistogramBucketBoundsUs
+	if len(boundsUs) > 0 && len(boundsUs) != len(req.HistogramBucketBoundsTenthMs) {
+		logCtx.WithFields(log.Fields{
+			"numBoundsUs":      len(boundsUs),
+			"numBoundsTenthMs": len(req.HistogramBucketBoundsTenthMs),
+		}).Warn("ignoring microsecond latency bucket bounds that do not match the tenth-of-a-millisecond bounds")
+		latencyBoundsMismatchesTotal.With(prometheus.Labels{"pod": id}).Inc()
+		boundsUs = nil
+	}
+
 	for _, requestScope := range req.Requests {
 		if requestScope.Ctx == nil {
 			return nil, errors.New("RequestCtx is required")
@@ -329,9 +408,12 @@ func (s *server) Report(ctx context.Context, req *write.ReportRequest) (*write.R
 
 			for bucketNum, count := range responseScope.ResponseLatencyCounts {
 				// Look up the bucket max value corresponding to this position
-				// in the report's latency histogram.
-				latencyTenthsMs := req.HistogramBucketBoundsTenthMs[bucketNum]
-				latencyMs := float64(latencyTenthsMs) / 10
+				// in the report's latency histogram, at the finest resolution
+				// the proxy reported.
+				latencyMs := float64(req.HistogramBucketBoundsTenthMs[bucketNum]) / 10
+				if boundsUs != nil {
+					latencyMs = float64(boundsUs[bucketNum]) / 1000
+				}
 				for i := uint32(0); i < count; i++ {
 					// Then, report that latency value to Prometheus a number
 					// of times equal to the count reported by the proxy.
@@ -443,6 +525,9 @@ func (s *server) requestLabelsFor(requestScope *write.RequestScope) prometheus.L
 	return prometheus.Labels{
 		"source_deployment": sourceDeployment,
 		"target_deployment": targetDeployment,
+		"route":             requestScope.Ctx.Route,
+		"destination_name":  requestScope.Ctx.DestinationName,
+		"mirrored":          strconv.FormatBool(requestScope.Ctx.Mirrored),
 	}
 }
 
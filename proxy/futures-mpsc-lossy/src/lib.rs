@@ -14,18 +14,24 @@ use futures::sync::mpsc;
 ///
 /// This allows producers to send events on this channel without obtaining a mutable
 /// reference to a sender.
+///
+/// Items that are rejected because the channel is full are counted for the channel
+/// as a whole, so that the count may be read from the receiver or any sender.
 pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
     let (tx, rx) = mpsc::unbounded();
     let capacity = Arc::new(AtomicUsize::new(capacity));
+    let rejected = Arc::new(AtomicUsize::new(0));
 
     let s = Sender {
         tx,
         capacity: capacity.clone(),
+        rejected: rejected.clone(),
     };
 
     let r = Receiver {
         rx,
         capacity,
+        rejected,
     };
 
     (s, r)
@@ -34,11 +40,15 @@ pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
 pub struct Receiver<T> {
     rx: mpsc::UnboundedReceiver<T>,
     capacity: Arc<AtomicUsize>,
+    rejected: Arc<AtomicUsize>,
 }
 
 pub struct Sender<T> {
     tx: mpsc::UnboundedSender<T>,
     capacity: Arc<AtomicUsize>,
+
+    /// Counts items rejected by all senders on this channel.
+    rejected: Arc<AtomicUsize>,
 }
 
 /// Indicates that channel was not able to send an item. Subsequents items, however, may
@@ -66,11 +76,20 @@ impl<T> Stream for Receiver<T> {
     }
 }
 
+impl<T> Receiver<T> {
+    /// Returns the number of items that have been rejected by all of this
+    /// channel's senders because the channel was full.
+    pub fn rejected(&self) -> usize {
+        self.rejected.load(Ordering::SeqCst)
+    }
+}
+
 // NB: `rx` does not have a `Debug` impl.
 impl<T> fmt::Debug for Receiver<T> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Receiver")
             .field("capacity", &self.capacity)
+            .field("rejected", &self.rejected)
             .finish()
     }
 }
@@ -82,6 +101,7 @@ impl<T> Sender<T> {
         loop {
             let cap = self.capacity.load(Ordering::SeqCst);
             if cap == 0 {
+                self.rejected.fetch_add(1, Ordering::SeqCst);
                 return Err(SendError::Rejected(v));
             }
 
@@ -96,6 +116,12 @@ impl<T> Sender<T> {
             .unbounded_send(v)
             .map_err(|se| SendError::NoReceiver(se.into_inner()))
     }
+
+    /// Returns the number of items that have been rejected by all of this
+    /// channel's senders because the channel was full.
+    pub fn rejected(&self) -> usize {
+        self.rejected.load(Ordering::SeqCst)
+    }
 }
 
 /// Drops events instead of exerting backpressure
@@ -118,6 +144,7 @@ impl<T> Clone for Sender<T> {
         Sender {
             tx: self.tx.clone(),
             capacity: self.capacity.clone(),
+            rejected: self.rejected.clone(),
         }
     }
 }
@@ -127,6 +154,7 @@ impl<T> fmt::Debug for Sender<T> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         fmt.debug_struct("Sender")
             .field("capacity", &self.capacity)
+            .field("rejected", &self.rejected)
             .finish()
     }
 }
@@ -140,3 +168,38 @@ impl<T> SendError<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::{Async, Stream};
+
+    use super::*;
+
+    #[test]
+    fn counts_rejected_items() {
+        let (tx0, mut rx) = channel(2);
+        let tx1 = tx0.clone();
+
+        assert_eq!(tx0.lossy_send(0), Ok(()));
+        assert_eq!(tx1.lossy_send(1), Ok(()));
+        assert_eq!(tx0.lossy_send(2), Err(SendError::Rejected(2)));
+        assert_eq!(tx1.lossy_send(3), Err(SendError::Rejected(3)));
+        assert_eq!(tx1.lossy_send(4), Err(SendError::Rejected(4)));
+
+        // Rejections are counted for the channel, regardless of the sender.
+        assert_eq!(tx0.rejected(), 3);
+        assert_eq!(tx1.rejected(), 3);
+        assert_eq!(rx.rejected(), 3);
+
+        // Clones share the count.
+        let tx2 = tx1.clone();
+        assert_eq!(tx2.lossy_send(5), Err(SendError::Rejected(5)));
+        assert_eq!(tx0.rejected(), 4);
+        assert_eq!(rx.rejected(), 4);
+
+        // Receiving an item frees capacity for another.
+        assert_eq!(rx.poll(), Ok(Async::Ready(Some(0))));
+        assert_eq!(tx0.lossy_send(6), Ok(()));
+        assert_eq!(rx.rejected(), 4);
+    }
+}
@@ -0,0 +1,691 @@
+//! Routes requests onto a consistent-hash ring of endpoints.
+//!
+//! Each endpoint is placed at `POINTS_PER_ENDPOINT` pseudo-random points on the
+//! ring, and a request is sent to the first endpoint at or after the hash of its
+//! key. When an endpoint is added or removed, only the keys between its points
+//! and their predecessors move.
+//!
+//! A request waits for its endpoint while the endpoint is busy. It only moves to
+//! the next endpoint on the ring if its endpoint fails or is removed, so that a
+//! key does not move between endpoints under load.
+//!
+//! Keys are hashed with FNV-1a, so that every proxy, whatever its version, maps
+//! a key to the same point on the ring.
+//!
+//! The ring also provides the sticky sessions of per-destination load
+//! balancing: a destination with a `HashKey` is balanced by the ring instead of
+//! by its `BalanceStrategy`.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::hash::Hasher;
+use std::marker::PhantomData;
+use std::net::{IpAddr, SocketAddr};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::{error, fmt};
+
+use futures::{Async, Future, Poll};
+use futures::task::{self, Task};
+use http;
+use rand::{self, Rng};
+use tower::Service;
+use tower_discover::{Change, Discover};
+
+use config::HashKey;
+use ctx;
+
+const POINTS_PER_ENDPOINT: u64 = 100;
+
+/// Sends each request to the endpoint that owns the hash of its key.
+///
+/// Requests without a key are sent to a random endpoint.
+pub struct HashRing<D: Discover> {
+    discover: D,
+    key: HashKey,
+    /// Shared with the responses that are waiting for their endpoint.
+    endpoints: Rc<RefCell<Endpoints<D::Service>>>,
+}
+
+struct Endpoints<S> {
+    by_addr: HashMap<SocketAddr, Endpoint<S>>,
+    ring: Ring,
+}
+
+struct Endpoint<S> {
+    service: S,
+    /// True if `service` has been ready since it was last called.
+    ready: bool,
+    /// True if `service` failed when it was last polled, until it is ready.
+    failed: bool,
+    /// Responses that are waiting for `service` to become ready.
+    waiters: Vec<Task>,
+}
+
+/// Points on the ring, sorted by hash.
+#[derive(Debug, Default)]
+struct Ring(Vec<(u64, SocketAddr)>);
+
+/// A response from the endpoint that owns a request's key.
+pub struct ResponseFuture<S: Service, D> {
+    state: State<S>,
+    _p: PhantomData<D>,
+}
+
+enum State<S: Service> {
+    /// The request's endpoint was busy when the request was made.
+    Waiting {
+        endpoints: Rc<RefCell<Endpoints<S>>>,
+        hash: u64,
+        request: Option<S::Request>,
+        /// The endpoint that was last waited on.
+        owner: Option<SocketAddr>,
+    },
+    Called(S::Future),
+    /// Every endpoint had failed when the request was made.
+    Failed,
+}
+
+#[derive(Debug)]
+pub enum Error<E, D> {
+    Inner(E),
+    Discover(D),
+    /// Every endpoint has failed.
+    NotReady,
+}
+
+/// A 64-bit FNV-1a hasher.
+///
+/// Unlike `DefaultHasher`, whose algorithm may change between Rust releases,
+/// its output is fixed.
+struct Fnv(u64);
+
+// ===== impl HashRing =====
+
+impl<D: Discover<Key = SocketAddr>> HashRing<D> {
+    pub fn new(discover: D, key: HashKey) -> Self {
+        HashRing {
+            discover,
+            key,
+            endpoints: Rc::new(RefCell::new(Endpoints {
+                by_addr: HashMap::new(),
+                ring: Ring::default(),
+            })),
+        }
+    }
+
+    fn update(&mut self) -> Result<(), D::DiscoverError> {
+        let mut endpoints = self.endpoints.borrow_mut();
+        while let Async::Ready(change) = self.discover.poll()? {
+            match change {
+                Change::Insert(addr, service) => endpoints.insert(addr, service),
+                Change::Remove(addr) => endpoints.remove(addr),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<D, B> Service for HashRing<D>
+where
+    D: Discover<Key = SocketAddr, Request = http::Request<B>>,
+{
+    type Request = http::Request<B>;
+    type Response = D::Response;
+    type Error = Error<D::Error, D::DiscoverError>;
+    type Future = ResponseFuture<D::Service, D::DiscoverError>;
+
+    /// Ready if any endpoint has not failed.
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.update().map_err(Error::Discover)?;
+
+        let mut endpoints = self.endpoints.borrow_mut();
+        let mut any_usable = false;
+        for (addr, endpoint) in &mut endpoints.by_addr {
+            // An endpoint with waiting responses is polled by those responses,
+            // so that they are notified when it becomes ready.
+            if endpoint.waiters.is_empty() {
+                if endpoint.poll_ready().is_err() {
+                    debug!("endpoint {} failed; skipping it", addr);
+                }
+            }
+            any_usable |= !endpoint.failed;
+        }
+
+        if any_usable {
+            Ok(Async::Ready(()))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+
+    fn call(&mut self, req: Self::Request) -> Self::Future {
+        let hash = hash_request(&self.key, &req).unwrap_or_else(|| rand::thread_rng().gen());
+
+        let state = {
+            let mut endpoints = self.endpoints.borrow_mut();
+            let owner = endpoints.owner(hash);
+            match owner {
+                Some(addr) => {
+                    let endpoint = endpoints.by_addr.get_mut(&addr)
+                        .expect("ring points must have endpoints");
+                    if endpoint.ready {
+                        State::Called(endpoint.call(req))
+                    } else {
+                        State::Waiting {
+                            endpoints: Rc::clone(&self.endpoints),
+                            hash,
+                            request: Some(req),
+                            owner: None,
+                        }
+                    }
+                }
+                None => State::Failed,
+            }
+        };
+
+        ResponseFuture {
+            state,
+            _p: PhantomData,
+        }
+    }
+}
+
+/// Hashes the part of `req` identified by `key`, if it is present.
+fn hash_request<B>(key: &HashKey, req: &http::Request<B>) -> Option<u64> {
+    match *key {
+        HashKey::Header(ref name) => {
+            req.headers().get(name).map(|v| hash(v.as_bytes()))
+        }
+        HashKey::Cookie(ref name) => {
+            req.headers()
+                .get_all(http::header::COOKIE)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(';'))
+                .filter_map(|pair| {
+                    let mut parts = pair.trim().splitn(2, '=');
+                    match (parts.next(), parts.next()) {
+                        (Some(n), Some(value)) if n == name.as_str() => Some(value),
+                        _ => None,
+                    }
+                })
+                .next()
+                .map(|value| hash(value.as_bytes()))
+        }
+        HashKey::PathSegment(index) => {
+            req.uri()
+                .path()
+                .split('/')
+                .filter(|s| !s.is_empty())
+                .nth(index)
+                .map(|segment| hash(segment.as_bytes()))
+        }
+        HashKey::SourceIp => {
+            req.extensions()
+                .get::<Arc<ctx::transport::Server>>()
+                .map(|ctx| match ctx.remote.ip() {
+                    IpAddr::V4(ip) => hash(&ip.octets()[..]),
+                    IpAddr::V6(ip) => hash(&ip.octets()[..]),
+                })
+        }
+    }
+}
+
+fn hash(bytes: &[u8]) -> u64 {
+    let mut hasher = Fnv::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+// ===== impl Endpoints =====
+
+impl<S> Endpoints<S> {
+    fn insert(&mut self, addr: SocketAddr, service: S) {
+        let endpoint = Endpoint {
+            service,
+            ready: false,
+            failed: false,
+            waiters: Vec::new(),
+        };
+        match self.by_addr.insert(addr, endpoint) {
+            // Responses waiting on the replaced service wait on the new one.
+            Some(mut old) => old.notify_waiters(),
+            None => self.ring.insert(addr),
+        }
+    }
+
+    fn remove(&mut self, addr: SocketAddr) {
+        if let Some(mut endpoint) = self.by_addr.remove(&addr) {
+            self.ring.remove(addr);
+            // Responses waiting on the endpoint move to the next one.
+            endpoint.notify_waiters();
+        }
+    }
+
+    /// Returns the endpoint that owns `hash`, skipping endpoints that have
+    /// failed.
+    fn owner(&self, hash: u64) -> Option<SocketAddr> {
+        let by_addr = &self.by_addr;
+        self.ring.owner(hash, |addr| !by_addr[addr].failed)
+    }
+}
+
+// ===== impl Endpoint =====
+
+impl<S> Endpoint<S> {
+    /// Registers the current task to be notified when the endpoint becomes
+    /// ready, fails, or is removed.
+    fn wait(&mut self) {
+        if !self.waiters.iter().any(|t| t.will_notify_current()) {
+            self.waiters.push(task::current());
+        }
+    }
+
+    fn notify_waiters(&mut self) {
+        for waiter in self.waiters.drain(..) {
+            waiter.notify();
+        }
+    }
+}
+
+impl<S: Service> Endpoint<S> {
+    fn poll_ready(&mut self) -> Poll<(), S::Error> {
+        if !self.ready {
+            match self.service.poll_ready() {
+                Ok(Async::Ready(())) => {
+                    self.ready = true;
+                    self.failed = false;
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(e) => {
+                    self.failed = true;
+                    self.notify_waiters();
+                    return Err(e);
+                }
+            }
+            // The service may only notify the last task that polled it, so
+            // every waiter must poll it again.
+            self.notify_waiters();
+        }
+        Ok(Async::Ready(()))
+    }
+
+    fn call(&mut self, req: S::Request) -> S::Future {
+        self.ready = false;
+        self.service.call(req)
+    }
+}
+
+// ===== impl ResponseFuture =====
+
+impl<S: Service, D> Future for ResponseFuture<S, D> {
+    type Item = S::Response;
+    type Error = Error<S::Error, D>;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let future = match self.state {
+                State::Called(ref mut f) => return f.poll().map_err(Error::Inner),
+                State::Failed => return Err(Error::NotReady),
+                State::Waiting { ref endpoints, hash, ref mut request, ref mut owner } => {
+                    let mut endpoints = endpoints.borrow_mut();
+                    let addr = match endpoints.owner(hash) {
+                        Some(addr) => addr,
+                        None => return Err(Error::NotReady),
+                    };
+                    *owner = Some(addr);
+
+                    let endpoint = endpoints.by_addr.get_mut(&addr)
+                        .expect("ring points must have endpoints");
+                    match endpoint.poll_ready() {
+                        Ok(Async::Ready(())) => {
+                            let req = request.take().expect("polled after ready");
+                            endpoint.call(req)
+                        }
+                        Ok(Async::NotReady) => {
+                            endpoint.wait();
+                            return Ok(Async::NotReady);
+                        }
+                        Err(_) => {
+                            debug!("endpoint {} failed; trying the next endpoint", addr);
+                            continue;
+                        }
+                    }
+                }
+            };
+            self.state = State::Called(future);
+        }
+    }
+}
+
+impl<S: Service, D> Drop for ResponseFuture<S, D> {
+    fn drop(&mut self) {
+        // This response may have been the waiter that its endpoint would have
+        // notified, so the other waiters must poll the endpoint in its place.
+        if let State::Waiting { ref endpoints, owner: Some(addr), .. } = self.state {
+            if let Ok(mut endpoints) = endpoints.try_borrow_mut() {
+                if let Some(endpoint) = endpoints.by_addr.get_mut(&addr) {
+                    endpoint.notify_waiters();
+                }
+            }
+        }
+    }
+}
+
+// ===== impl Ring =====
+
+impl Ring {
+    fn insert(&mut self, addr: SocketAddr) {
+        for i in 0..POINTS_PER_ENDPOINT {
+            let point = format!("{}-{}", addr, i);
+            self.0.push((hash(point.as_bytes()), addr));
+        }
+        self.0.sort_by_key(|&(h, _)| h);
+    }
+
+    fn remove(&mut self, addr: SocketAddr) {
+        self.0.retain(|&(_, a)| a != addr);
+    }
+
+    /// Returns the first endpoint at or after `hash` that is `usable`,
+    /// wrapping around the ring.
+    fn owner<F>(&self, hash: u64, usable: F) -> Option<SocketAddr>
+    where
+        F: Fn(&SocketAddr) -> bool,
+    {
+        let start = match self.0.binary_search_by(|&(h, _)| h.cmp(&hash)) {
+            Ok(i) | Err(i) => i,
+        };
+        self.0[start..]
+            .iter()
+            .chain(self.0[..start].iter())
+            .map(|&(_, addr)| addr)
+            .find(|addr| usable(addr))
+    }
+}
+
+// ===== impl Fnv =====
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+// ===== impl Error =====
+
+impl<E: fmt::Display, D: fmt::Display> fmt::Display for Error<E, D> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Inner(ref e) => fmt::Display::fmt(e, f),
+            Error::Discover(ref e) => write!(f, "discovery failed: {}", e),
+            Error::NotReady => f.pad("every endpoint has failed"),
+        }
+    }
+}
+
+impl<E: error::Error, D: error::Error> error::Error for Error<E, D> {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Inner(ref e) => e.description(),
+            Error::Discover(ref e) => e.description(),
+            Error::NotReady => "every endpoint has failed",
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            Error::Inner(ref e) => Some(e),
+            Error::Discover(ref e) => Some(e),
+            Error::NotReady => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::collections::VecDeque;
+    use std::net::SocketAddr;
+    use std::rc::Rc;
+
+    use futures::{future, Async, Future, Poll};
+    use http;
+    use tower::Service;
+    use tower_discover::{Change, Discover};
+
+    use config::HashKey;
+    use super::{hash, hash_request, HashRing, Ring};
+
+    fn addr(i: u8) -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, i], 8080))
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    enum Readiness {
+        Ready,
+        Busy,
+        Failed,
+    }
+
+    /// An endpoint whose readiness is set by the test.
+    struct Mock {
+        readiness: Rc<Cell<Readiness>>,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl Service for Mock {
+        type Request = http::Request<()>;
+        type Response = ();
+        type Error = ();
+        type Future = future::FutureResult<(), ()>;
+
+        fn poll_ready(&mut self) -> Poll<(), ()> {
+            match self.readiness.get() {
+                Readiness::Ready => Ok(Async::Ready(())),
+                Readiness::Busy => Ok(Async::NotReady),
+                Readiness::Failed => Err(()),
+            }
+        }
+
+        fn call(&mut self, _: Self::Request) -> Self::Future {
+            self.calls.set(self.calls.get() + 1);
+            future::ok(())
+        }
+    }
+
+    struct MockDiscover(Rc<RefCell<VecDeque<Change<SocketAddr, Mock>>>>);
+
+    impl Discover for MockDiscover {
+        type Key = SocketAddr;
+        type Request = http::Request<()>;
+        type Response = ();
+        type Error = ();
+        type Service = Mock;
+        type DiscoverError = ();
+
+        fn poll(&mut self) -> Poll<Change<SocketAddr, Mock>, ()> {
+            match self.0.borrow_mut().pop_front() {
+                Some(change) => Ok(Async::Ready(change)),
+                None => Ok(Async::NotReady),
+            }
+        }
+    }
+
+    struct Endpoints {
+        changes: Rc<RefCell<VecDeque<Change<SocketAddr, Mock>>>>,
+        readiness: Vec<Rc<Cell<Readiness>>>,
+        calls: Vec<Rc<Cell<usize>>>,
+    }
+
+    /// Builds a ring of `n` ready endpoints, keyed by the `x-user` header.
+    fn new_ring(n: u8) -> (HashRing<MockDiscover>, Endpoints) {
+        let changes = Rc::new(RefCell::new(VecDeque::new()));
+        let mut endpoints = Endpoints {
+            changes: changes.clone(),
+            readiness: Vec::new(),
+            calls: Vec::new(),
+        };
+        for i in 0..n {
+            let readiness = Rc::new(Cell::new(Readiness::Ready));
+            let calls = Rc::new(Cell::new(0));
+            changes.borrow_mut().push_back(Change::Insert(addr(i), Mock {
+                readiness: readiness.clone(),
+                calls: calls.clone(),
+            }));
+            endpoints.readiness.push(readiness);
+            endpoints.calls.push(calls);
+        }
+        let key = HashKey::Header("x-user".parse().unwrap());
+        (HashRing::new(MockDiscover(changes), key), endpoints)
+    }
+
+    fn request(user: &str) -> http::Request<()> {
+        http::Request::get("/").header("x-user", user).body(()).unwrap()
+    }
+
+    /// Calls `ring` for `user` and returns the index of the endpoint called.
+    fn owner_of(ring: &mut HashRing<MockDiscover>, endpoints: &Endpoints, user: &str) -> usize {
+        let before = endpoints.calls.iter().map(|c| c.get()).collect::<Vec<_>>();
+        assert!(ring.poll_ready().unwrap().is_ready());
+        ring.call(request(user)).wait().unwrap();
+        (0..before.len())
+            .find(|&i| endpoints.calls[i].get() > before[i])
+            .expect("an endpoint must be called")
+    }
+
+    #[test]
+    fn ring_remaps_only_keys_of_removed_endpoints() {
+        let mut ring = Ring::default();
+        for i in 0..4 {
+            ring.insert(addr(i));
+        }
+        let before = (0..1000u64)
+            .map(|k| ring.owner(hash(k.to_string().as_bytes()), |_| true).unwrap())
+            .collect::<Vec<_>>();
+
+        ring.remove(addr(3));
+        for (k, owner) in (0..1000u64).zip(before) {
+            let now = ring.owner(hash(k.to_string().as_bytes()), |_| true).unwrap();
+            if owner != addr(3) {
+                assert_eq!(now, owner);
+            }
+        }
+    }
+
+    #[test]
+    fn ring_skips_unusable_endpoints() {
+        let mut ring = Ring::default();
+        ring.insert(addr(1));
+        ring.insert(addr(2));
+        for k in 0..100u64 {
+            let owner = ring.owner(hash(k.to_string().as_bytes()), |a| *a != addr(1));
+            assert_eq!(owner, Some(addr(2)));
+        }
+        assert_eq!(ring.owner(0, |_| false), None);
+    }
+
+    #[test]
+    fn hashes_with_fnv1a() {
+        assert_eq!(hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn hashes_request_keys() {
+        let req = http::Request::get("/users/42/cart")
+            .header("x-user", "alice")
+            .header("cookie", "theme=dark; session=abc")
+            .body(())
+            .unwrap();
+
+        let header = HashKey::Header("x-user".parse().unwrap());
+        assert_eq!(hash_request(&header, &req), Some(hash(b"alice")));
+
+        let cookie = HashKey::Cookie("session".into());
+        assert_eq!(hash_request(&cookie, &req), Some(hash(b"abc")));
+
+        assert_eq!(hash_request(&HashKey::PathSegment(1), &req), Some(hash(b"42")));
+        assert_eq!(hash_request(&HashKey::PathSegment(3), &req), None);
+
+        let missing = HashKey::Cookie("user".into());
+        assert_eq!(hash_request(&missing, &req), None);
+    }
+
+    #[test]
+    fn requests_wait_for_a_busy_owner() {
+        let (mut ring, endpoints) = new_ring(2);
+        let owner = owner_of(&mut ring, &endpoints, "alice");
+        let other = 1 - owner;
+
+        endpoints.readiness[owner].set(Readiness::Busy);
+        future::lazy(|| {
+            assert!(ring.poll_ready().unwrap().is_ready());
+            let mut rsp = ring.call(request("alice"));
+            assert!(rsp.poll().unwrap().is_not_ready());
+            assert_eq!(endpoints.calls[owner].get(), 1);
+            assert_eq!(endpoints.calls[other].get(), 0);
+
+            endpoints.readiness[owner].set(Readiness::Ready);
+            assert!(rsp.poll().unwrap().is_ready());
+            assert_eq!(endpoints.calls[owner].get(), 2);
+            assert_eq!(endpoints.calls[other].get(), 0);
+            Ok::<_, ()>(())
+        }).wait().unwrap();
+    }
+
+    #[test]
+    fn requests_move_when_their_owner_fails() {
+        let (mut ring, endpoints) = new_ring(2);
+        let owner = owner_of(&mut ring, &endpoints, "alice");
+        let other = 1 - owner;
+
+        endpoints.readiness[owner].set(Readiness::Busy);
+        future::lazy(|| {
+            assert!(ring.poll_ready().unwrap().is_ready());
+            let mut rsp = ring.call(request("alice"));
+            assert!(rsp.poll().unwrap().is_not_ready());
+
+            endpoints.readiness[owner].set(Readiness::Failed);
+            assert!(rsp.poll().unwrap().is_ready());
+            assert_eq!(endpoints.calls[owner].get(), 1);
+            assert_eq!(endpoints.calls[other].get(), 1);
+            Ok::<_, ()>(())
+        }).wait().unwrap();
+    }
+
+    #[test]
+    fn requests_move_when_their_owner_is_removed() {
+        let (mut ring, endpoints) = new_ring(2);
+        let owner = owner_of(&mut ring, &endpoints, "alice");
+        let other = 1 - owner;
+
+        endpoints.readiness[owner].set(Readiness::Busy);
+        future::lazy(|| {
+            assert!(ring.poll_ready().unwrap().is_ready());
+            let mut rsp = ring.call(request("alice"));
+            assert!(rsp.poll().unwrap().is_not_ready());
+
+            endpoints.changes.borrow_mut().push_back(Change::Remove(addr(owner as u8)));
+            assert!(ring.poll_ready().unwrap().is_ready());
+            assert!(rsp.poll().unwrap().is_ready());
+            assert_eq!(endpoints.calls[other].get(), 1);
+            Ok::<_, ()>(())
+        }).wait().unwrap();
+    }
+}
@@ -0,0 +1,467 @@
+//! Load balancing across the endpoints of an outbound destination.
+//!
+//! Each destination chooses among its ready endpoints with a `BalanceStrategy`,
+//! set locally or by the controller. Sticky sessions, which hash a header, the
+//! source IP or another part of the request, are not a `BalanceStrategy`: they
+//! are configured as a destination's `HashKey`, which takes precedence, and are
+//! balanced by the consistent-hash ring in `hash`.
+
+use std::{error, fmt};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::{future, Async, Future, Poll};
+use http;
+use rand::Rng;
+use tower::Service;
+use tower_balance::Balance;
+use tower_balance::choose::{Choose, Replicas};
+use tower_balance::load::Load;
+use tower_discover::Discover;
+
+use config::BalanceStrategy;
+use control::discovery::{self, Metadata};
+
+mod hash;
+
+pub use self::hash::HashRing;
+
+/// Local endpoints with more pending requests than this are overloaded, so that
+/// requests may spill over to endpoints in other zones.
+const MAX_LOCAL_PENDING: usize = 100;
+
+/// The latency assumed for endpoints that have not yet responded.
+const DEFAULT_RTT_MS: f64 = 30.0;
+
+/// How quickly latency estimates decay towards newer observations.
+const RTT_DECAY_MS: f64 = 10_000.0;
+
+/// Balances requests either by choosing among ready endpoints or, for requests
+/// that should reach the same endpoint, by hashing onto a ring of endpoints.
+pub enum Balancer<D: Discover, C> {
+    Choose(Balance<D, C>),
+    Hash(HashRing<D>),
+}
+
+type ChooseError<D, C> = <Balance<D, C> as Service>::Error;
+type HashError<D> = hash::Error<<D as Discover>::Error, <D as Discover>::DiscoverError>;
+
+#[derive(Debug)]
+pub enum Error<C, H> {
+    Choose(C),
+    Hash(H),
+}
+
+/// A service to a single endpoint, tracking its pending requests, its latency,
+/// and whether it is in the proxy's zone.
+#[derive(Debug)]
+pub struct Endpoint<S> {
+    inner: S,
+    local: bool,
+    /// Each pending response holds a clone.
+    pending: Arc<()>,
+    rtt: Arc<Mutex<PeakEwma>>,
+}
+
+pub struct ResponseFuture<F> {
+    inner: F,
+    _pending: Arc<()>,
+    rtt: Arc<Mutex<PeakEwma>>,
+    start: Instant,
+}
+
+/// Estimates latency with an exponentially-weighted moving average that rises
+/// immediately to new peaks.
+#[derive(Debug)]
+struct PeakEwma {
+    estimate_ms: f64,
+    updated: Instant,
+}
+
+/// Binds `Endpoint`s, noting which are in the proxy's zone.
+#[derive(Clone, Debug)]
+pub struct BindEndpoint<B> {
+    bind: B,
+    zone: Option<String>,
+}
+
+/// A balancer's strategy, which may be changed while the balancer runs.
+#[derive(Clone, Debug)]
+pub struct SharedStrategy(Arc<Mutex<BalanceStrategy>>);
+
+/// Chooses among ready endpoints with a `SharedStrategy`, preferring those in
+/// the proxy's zone.
+///
+/// Endpoints in other zones are only considered when no local endpoint is
+/// ready or, unless endpoints are used in turn, when the chosen local endpoint
+/// would be overloaded.
+#[derive(Debug)]
+pub struct ChooseEndpoint<R> {
+    strategy: SharedStrategy,
+    rng: R,
+    /// The position of the next endpoint to use in turn.
+    next: usize,
+}
+
+// ===== impl Balancer =====
+
+impl<D, C, B> Service for Balancer<D, C>
+where
+    D: Discover<Key = SocketAddr, Request = http::Request<B>>,
+    Balance<D, C>: Service<Request = http::Request<B>, Response = D::Response>,
+{
+    type Request = http::Request<B>;
+    type Response = D::Response;
+    type Error = Error<ChooseError<D, C>, HashError<D>>;
+    type Future = future::Either<
+        future::MapErr<
+            <Balance<D, C> as Service>::Future,
+            fn(ChooseError<D, C>) -> Self::Error,
+        >,
+        future::MapErr<
+            <HashRing<D> as Service>::Future,
+            fn(HashError<D>) -> Self::Error,
+        >,
+    >;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        match *self {
+            Balancer::Choose(ref mut b) => b.poll_ready().map_err(Error::Choose),
+            Balancer::Hash(ref mut h) => h.poll_ready().map_err(Error::Hash),
+        }
+    }
+
+    fn call(&mut self, req: Self::Request) -> Self::Future {
+        match *self {
+            Balancer::Choose(ref mut b) => {
+                future::Either::A(b.call(req).map_err(Error::Choose as fn(_) -> _))
+            }
+            Balancer::Hash(ref mut h) => {
+                future::Either::B(h.call(req).map_err(Error::Hash as fn(_) -> _))
+            }
+        }
+    }
+}
+
+// ===== impl Error =====
+
+impl<C: fmt::Display, H: fmt::Display> fmt::Display for Error<C, H> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Choose(ref e) => fmt::Display::fmt(e, f),
+            Error::Hash(ref e) => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+impl<C: error::Error, H: error::Error> error::Error for Error<C, H> {
+    fn description(&self) -> &str {
+        match *self {
+            Error::Choose(ref e) => e.description(),
+            Error::Hash(ref e) => e.description(),
+        }
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            Error::Choose(ref e) => e.cause(),
+            Error::Hash(ref e) => e.cause(),
+        }
+    }
+}
+
+// ===== impl Endpoint =====
+
+impl<S> Endpoint<S> {
+    pub fn new(inner: S, local: bool) -> Self {
+        Endpoint {
+            inner,
+            local,
+            pending: Arc::new(()),
+            rtt: Arc::new(Mutex::new(PeakEwma::new())),
+        }
+    }
+
+    fn pending(&self) -> usize {
+        Arc::strong_count(&self.pending) - 1
+    }
+
+    /// Pending requests, weighted by latency.
+    fn cost(&self) -> f64 {
+        let rtt = self.rtt.lock().expect("rtt lock").estimate_ms;
+        rtt * (self.pending() + 1) as f64
+    }
+}
+
+impl<S: Service> Service for Endpoint<S> {
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, req: Self::Request) -> Self::Future {
+        ResponseFuture {
+            inner: self.inner.call(req),
+            _pending: self.pending.clone(),
+            rtt: self.rtt.clone(),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl<S> Load for Endpoint<S> {
+    type Metric = usize;
+
+    fn load(&self) -> usize {
+        self.pending()
+    }
+}
+
+impl<F: Future> Future for ResponseFuture<F> {
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<F::Item, F::Error> {
+        let poll = self.inner.poll();
+        if let Ok(Async::NotReady) = poll {
+            return poll;
+        }
+
+        self.rtt.lock().expect("rtt lock").observe(self.start.elapsed());
+        poll
+    }
+}
+
+// ===== impl PeakEwma =====
+
+impl PeakEwma {
+    fn new() -> Self {
+        PeakEwma {
+            estimate_ms: DEFAULT_RTT_MS,
+            updated: Instant::now(),
+        }
+    }
+
+    fn observe(&mut self, rtt: Duration) {
+        let now = Instant::now();
+        let rtt_ms = as_millis(rtt);
+        if rtt_ms > self.estimate_ms {
+            self.estimate_ms = rtt_ms;
+        } else {
+            let weight = (-as_millis(now - self.updated) / RTT_DECAY_MS).exp();
+            self.estimate_ms = self.estimate_ms * weight + rtt_ms * (1.0 - weight);
+        }
+        self.updated = now;
+    }
+}
+
+fn as_millis(d: Duration) -> f64 {
+    d.as_secs() as f64 * 1_000.0 + f64::from(d.subsec_nanos()) / 1_000_000.0
+}
+
+// ===== impl BindEndpoint =====
+
+impl<B> BindEndpoint<B> {
+    /// Binds endpoints with `bind`. Endpoints whose metadata places them in
+    /// `zone` are local; if `zone` is unknown, no endpoint is.
+    pub fn new(bind: B, zone: Option<String>) -> Self {
+        BindEndpoint { bind, zone }
+    }
+}
+
+impl<B: discovery::Bind> discovery::Bind for BindEndpoint<B> {
+    type Request = B::Request;
+    type Response = B::Response;
+    type Error = B::Error;
+    type BindError = B::BindError;
+    type Service = Endpoint<B::Service>;
+
+    fn bind(&self, addr: &SocketAddr, metadata: &Metadata) -> Result<Self::Service, Self::BindError> {
+        let local = match (self.zone.as_ref(), metadata.zone()) {
+            (Some(zone), Some(endpoint_zone)) => zone == endpoint_zone,
+            _ => false,
+        };
+        let svc = self.bind.bind(addr, metadata)?;
+        Ok(Endpoint::new(svc, local))
+    }
+}
+
+// ===== impl SharedStrategy =====
+
+impl SharedStrategy {
+    pub fn new(strategy: BalanceStrategy) -> Self {
+        SharedStrategy(Arc::new(Mutex::new(strategy)))
+    }
+
+    pub fn get(&self) -> BalanceStrategy {
+        *self.0.lock().expect("strategy lock")
+    }
+
+    pub fn set(&self, strategy: BalanceStrategy) {
+        *self.0.lock().expect("strategy lock") = strategy;
+    }
+}
+
+// ===== impl ChooseEndpoint =====
+
+impl<R: Rng> ChooseEndpoint<R> {
+    pub fn new(strategy: SharedStrategy, rng: R) -> Self {
+        ChooseEndpoint {
+            strategy,
+            rng,
+            next: 0,
+        }
+    }
+}
+
+impl<K, S, R: Rng> Choose<K, Endpoint<S>> for ChooseEndpoint<R> {
+    fn choose(&mut self, replicas: Replicas<K, Endpoint<S>>) -> usize {
+        let n = replicas.len();
+        let is_local = |i: usize| replicas[i].local;
+        let pending = |i: usize| replicas[i].pending();
+        match self.strategy.get() {
+            BalanceStrategy::RoundRobin => round_robin(&mut self.next, n, &is_local),
+            BalanceStrategy::LeastRequests => {
+                choose_local(&mut self.rng, n, &is_local, &pending, |i| pending(i) as f64)
+            }
+            BalanceStrategy::PeakEwma => {
+                choose_local(&mut self.rng, n, &is_local, &pending, |i| replicas[i].cost())
+            }
+        }
+    }
+}
+
+/// Chooses the next of `n` endpoints in turn, skipping those in other zones
+/// unless none are local.
+fn round_robin<L>(next: &mut usize, n: usize, is_local: L) -> usize
+where
+    L: Fn(usize) -> bool,
+{
+    let local = (0..n).filter(|&i| is_local(i)).collect::<Vec<_>>();
+    let i = *next;
+    *next = next.wrapping_add(1);
+    if local.is_empty() {
+        i % n
+    } else {
+        local[i % local.len()]
+    }
+}
+
+/// Chooses one of `n` endpoints with the power of two choices, preferring local
+/// endpoints unless they are overloaded.
+fn choose_local<R, L, P, C>(rng: &mut R, n: usize, is_local: L, pending: P, cost: C) -> usize
+where
+    R: Rng,
+    L: Fn(usize) -> bool,
+    P: Fn(usize) -> usize,
+    C: Fn(usize) -> f64,
+{
+    let local = (0..n).filter(|&i| is_local(i)).collect::<Vec<_>>();
+    if local.is_empty() {
+        return power_of_two_choices(rng, n, &cost);
+    }
+
+    let l = local[power_of_two_choices(rng, local.len(), |i| cost(local[i]))];
+    if pending(l) <= MAX_LOCAL_PENDING {
+        return l;
+    }
+
+    let any = power_of_two_choices(rng, n, &cost);
+    if cost(any) < cost(l) {
+        trace!("local endpoint overloaded; spilling over to another zone");
+        any
+    } else {
+        l
+    }
+}
+
+/// Of two distinct endpoints selected at random from `n`, returns the one with
+/// the lower cost.
+fn power_of_two_choices<R, C>(rng: &mut R, n: usize, cost: C) -> usize
+where
+    R: Rng,
+    C: Fn(usize) -> f64,
+{
+    debug_assert!(n > 0);
+    if n == 1 {
+        return 0;
+    }
+
+    let a = rng.gen_range(0, n);
+    let mut b = rng.gen_range(0, n - 1);
+    if b >= a {
+        b += 1;
+    }
+
+    if cost(b) < cost(a) { b } else { a }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand;
+
+    use std::time::Duration;
+
+    use super::{choose_local, round_robin, PeakEwma, MAX_LOCAL_PENDING};
+
+    fn least_requests(n: usize, is_local: &Fn(usize) -> bool, pending: &Fn(usize) -> usize) -> usize {
+        choose_local(&mut rand::thread_rng(), n, is_local, pending, |i| pending(i) as f64)
+    }
+
+    #[test]
+    fn prefers_local_endpoints() {
+        let local = |i: usize| i % 3 == 0;
+        for _ in 0..100 {
+            let i = least_requests(9, &local, &|_| 0);
+            assert!(local(i), "chose remote endpoint {}", i);
+        }
+    }
+
+    #[test]
+    fn spills_over_when_local_endpoints_are_overloaded() {
+        let pending = |i: usize| if i == 0 { MAX_LOCAL_PENDING + 1 } else { 0 };
+        let mut spilled = false;
+        for _ in 0..100 {
+            spilled |= least_requests(3, &|i| i == 0, &pending) != 0;
+        }
+        assert!(spilled);
+    }
+
+    #[test]
+    fn uses_all_endpoints_without_local_ones() {
+        let mut chosen = [false; 3];
+        for _ in 0..100 {
+            chosen[least_requests(3, &|_| false, &|_| 0)] = true;
+        }
+        assert_eq!(chosen, [true; 3]);
+    }
+
+    #[test]
+    fn round_robin_uses_local_endpoints_in_turn() {
+        let mut next = 0;
+        let chosen = (0..4)
+            .map(|_| round_robin(&mut next, 5, |i| i != 2))
+            .collect::<Vec<_>>();
+        assert_eq!(chosen, vec![0, 1, 3, 4]);
+
+        let chosen = (0..3)
+            .map(|_| round_robin(&mut next, 3, |_| false))
+            .collect::<Vec<_>>();
+        assert_eq!(chosen, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn peak_ewma_rises_to_peaks_and_decays() {
+        let mut rtt = PeakEwma::new();
+        rtt.observe(Duration::from_millis(500));
+        assert_eq!(rtt.estimate_ms, 500.0);
+
+        rtt.observe(Duration::from_millis(10));
+        assert!(rtt.estimate_ms <= 500.0 && rtt.estimate_ms > 10.0);
+    }
+}
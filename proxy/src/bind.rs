@@ -1,23 +1,31 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::default::Default;
 use std::marker::PhantomData;
 use std::net::SocketAddr;
+use std::rc::{Rc, Weak};
 use std::sync::Arc;
 use std::sync::atomic::AtomicUsize;
 
-use futures::{Future, Poll};
+use futures::{Async, Future, Poll};
 use futures::future::Map;
+use h2;
 use http::{self, uri};
 use tokio_core::reactor::Handle;
 use tower;
+use tower_buffer::{self, Buffer};
 use tower_h2;
-use tower_reconnect::Reconnect;
+use tower_reconnect::{Error as ReconnectError, Reconnect};
 
 use conduit_proxy_controller_grpc;
 use conduit_proxy_router::Reuse;
 use control;
+use control::discovery::Metadata;
 use ctx;
+use fully_qualified_authority::FullyQualifiedAuthority;
+use orig_proto;
 use telemetry::{self, sensor};
 use transparency::{self, HttpBody, h1};
 use transport;
@@ -29,25 +37,35 @@ use transport;
 /// # TODO
 ///
 /// Buffering is not bounded and no timeouts are applied.
-pub struct Bind<C, B> {
+pub struct Bind<C, B>
+where
+    B: tower_h2::Body + 'static,
+{
     ctx: C,
     sensors: telemetry::Sensors,
     executor: Handle,
     req_ids: Arc<AtomicUsize>,
+    shared_http2: Rc<RefCell<HashMap<SocketAddr, Weak<SharedHttp2<B>>>>>,
     _p: PhantomData<B>,
 }
 
 /// Binds a `Service` from a `SocketAddr` for a pre-determined protocol.
-pub struct BindProtocol<C, B> {
+pub struct BindProtocol<C, B>
+where
+    B: tower_h2::Body + 'static,
+{
     bind: Bind<C, B>,
     protocol: Protocol,
+    destination_name: Option<FullyQualifiedAuthority>,
 }
 
 /// Protocol portion of the `Recognize` key for a request.
 ///
 /// This marks whether to use HTTP/2 or HTTP/1.x for a request. In
 /// the case of HTTP/1.x requests, it also stores a "host" key to ensure
-/// that each host receives its own connection.
+/// that each host receives its own connection. HTTP/1.x requests to endpoints
+/// that run a proxy are sent over HTTP/2, and all hosts share one connection
+/// to each such endpoint.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Protocol {
     Http1(Host),
@@ -75,15 +93,60 @@ pub struct NormalizeUri<S> {
 
 pub type Service<B> = Reconnect<NormalizeUri<NewHttp<B>>>;
 
-pub type NewHttp<B> = sensor::NewHttp<Client<B>, B, HttpBody>;
+pub type NewHttp<B> = sensor::NewHttp<orig_proto::Upgrade<Endpoint<B>>, B, HttpBody>;
 
 pub type HttpResponse = http::Response<sensor::http::ResponseBody<HttpBody>>;
 
 pub type Client<B> = transparency::Client<
     sensor::Connect<transport::Connect>,
-    B,
+    sensor::http::RequestBody<B>,
 >;
 
+/// The HTTP/2 client to an endpoint that runs a proxy.
+///
+/// It is shared by every binding that upgrades requests to the endpoint, so
+/// that each endpoint gets a single connection, whatever the `Host` of the
+/// requests sent over it.
+pub type SharedHttp2<B> = Buffer<Reconnect<Client<B>>>;
+
+/// Binds a client to a single endpoint.
+pub enum Endpoint<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    /// Connects for this binding alone.
+    Dedicated(Client<B>),
+    /// Uses the HTTP/2 client shared by all bindings to the endpoint.
+    Shared(Rc<SharedHttp2<B>>),
+}
+
+/// Future returned by `Endpoint`'s `NewService`.
+pub enum NewEndpoint<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    Dedicated(<Client<B> as tower::NewService>::Future),
+    Shared(Option<SharedHttp2<B>>),
+}
+
+/// The `Service` yielded by `Endpoint`'s `NewService`.
+pub enum EndpointService<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    Dedicated(<Client<B> as tower::NewService>::Service),
+    Shared(SharedHttp2<B>),
+}
+
+/// Future returned by `EndpointService`.
+pub enum EndpointResponse<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    Dedicated(<<Client<B> as tower::NewService>::Service as tower::Service>::Future),
+    Shared(<SharedHttp2<B> as tower::Service>::Future),
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum BufferSpawnError {
     Inbound,
@@ -110,13 +173,17 @@ impl Error for BufferSpawnError {
     fn cause(&self) -> Option<&Error> { None }
 }
 
-impl<B> Bind<(), B> {
+impl<B> Bind<(), B>
+where
+    B: tower_h2::Body + 'static,
+{
     pub fn new(executor: Handle) -> Self {
         Self {
             executor,
             ctx: (),
             sensors: telemetry::Sensors::null(),
             req_ids: Default::default(),
+            shared_http2: Default::default(),
             _p: PhantomData,
         }
     }
@@ -134,25 +201,33 @@ impl<B> Bind<(), B> {
             sensors: self.sensors,
             executor: self.executor,
             req_ids: self.req_ids,
+            shared_http2: self.shared_http2,
             _p: PhantomData,
         }
     }
 }
 
-impl<C: Clone, B> Clone for Bind<C, B> {
+impl<C: Clone, B> Clone for Bind<C, B>
+where
+    B: tower_h2::Body + 'static,
+{
     fn clone(&self) -> Self {
         Self {
             ctx: self.ctx.clone(),
             sensors: self.sensors.clone(),
             executor: self.executor.clone(),
             req_ids: self.req_ids.clone(),
+            shared_http2: self.shared_http2.clone(),
             _p: PhantomData,
         }
     }
 }
 
 
-impl<C, B> Bind<C, B> {
+impl<C, B> Bind<C, B>
+where
+    B: tower_h2::Body + 'static,
+{
 
     // pub fn ctx(&self) -> &C {
     //     &self.ctx
@@ -176,25 +251,48 @@ impl<B> Bind<Arc<ctx::Proxy>, B>
 where
     B: tower_h2::Body + 'static,
 {
-    pub fn bind_service(&self, addr: &SocketAddr, protocol: &Protocol) -> Service<B> {
-        trace!("bind_service addr={}, protocol={:?}", addr, protocol);
+    pub fn bind_service(
+        &self,
+        addr: &SocketAddr,
+        metadata: &Metadata,
+        destination_name: &Option<FullyQualifiedAuthority>,
+        protocol: &Protocol,
+    ) -> Service<B> {
+        trace!(
+            "bind_service addr={}, metadata={:?}, destination_name={:?}, protocol={:?}",
+            addr,
+            metadata,
+            destination_name,
+            protocol
+        );
         let client_ctx = ctx::transport::Client::new(
             &self.ctx,
             addr,
+            metadata,
+            destination_name,
+            &None,
+            &None,
             conduit_proxy_controller_grpc::common::Protocol::Http,
         );
 
-        // Map a socket address to a connection.
-        let connect = self.sensors.connect(
-            transport::Connect::new(*addr, &self.executor),
-            &client_ctx
-        );
-
-        let client = transparency::Client::new(
-            protocol,
-            connect,
-            self.executor.clone(),
-        );
+        // HTTP/1 requests to another proxy are multiplexed over HTTP/2, and
+        // that proxy restores their original version.
+        let upgrade = metadata.is_meshed() && protocol.can_upgrade();
+        let endpoint = if upgrade {
+            self.shared_http2(addr, metadata)
+        } else {
+            // Map a socket address to a connection.
+            let connect = self.sensors.connect(
+                transport::Connect::new(*addr, &self.executor),
+                &client_ctx
+            );
+            Endpoint::Dedicated(transparency::Client::new(
+                protocol,
+                connect,
+                self.executor.clone(),
+            ))
+        };
+        let client = orig_proto::Upgrade::new(endpoint, upgrade);
 
         let sensors = self.sensors.http(
             self.req_ids.clone(),
@@ -211,16 +309,86 @@ where
         // TODO: Add some sort of backoff logic.
         Reconnect::new(proxy)
     }
+
+    /// Returns the HTTP/2 client shared by the bindings that upgrade requests
+    /// to `addr`, connecting to it if none of them is bound.
+    ///
+    /// The shared connection carries requests for every name and `Host` that
+    /// is bound to the endpoint, so its transport is reported with a context
+    /// of the endpoint alone, without a destination name. Its requests are
+    /// still reported with the context of their own binding.
+    fn shared_http2(&self, addr: &SocketAddr, metadata: &Metadata) -> Endpoint<B> {
+        let mut clients = self.shared_http2.borrow_mut();
+        if let Some(client) = clients.get(addr).and_then(Weak::upgrade) {
+            return Endpoint::Shared(client);
+        }
+
+        // Forget the clients of endpoints that are no longer bound.
+        clients.retain(|_, client| client.upgrade().is_some());
+
+        let endpoint_ctx = ctx::transport::Client::new(
+            &self.ctx,
+            addr,
+            metadata,
+            &None,
+            &None,
+            &None,
+            conduit_proxy_controller_grpc::common::Protocol::Http,
+        );
+        let connect = self.sensors.connect(
+            transport::Connect::new(*addr, &self.executor),
+            &endpoint_ctx
+        );
+
+        let client = transparency::Client::new(
+            &Protocol::Http2,
+            connect.clone(),
+            self.executor.clone(),
+        );
+        match Buffer::new(Reconnect::new(client), &self.executor) {
+            Ok(client) => {
+                let client = Rc::new(client);
+                clients.insert(*addr, Rc::downgrade(&client));
+                Endpoint::Shared(client)
+            },
+            Err(_) => {
+                warn!("error spawning shared http/2 client for {}", addr);
+                Endpoint::Dedicated(transparency::Client::new(
+                    &Protocol::Http2,
+                    connect,
+                    self.executor.clone(),
+                ))
+            },
+        }
+    }
 }
 
 // ===== impl BindProtocol =====
 
 
-impl<C, B> Bind<C, B> {
+impl<C, B> Bind<C, B>
+where
+    B: tower_h2::Body + 'static,
+{
     pub fn with_protocol(self, protocol: Protocol) -> BindProtocol<C, B> {
         BindProtocol {
             bind: self,
             protocol,
+            destination_name: None,
+        }
+    }
+}
+
+impl<C, B> BindProtocol<C, B>
+where
+    B: tower_h2::Body + 'static,
+{
+    /// Records that the endpoints bound by this are resolved from `name`, so
+    /// that telemetry may identify the destination that served each request.
+    pub fn with_destination_name(self, name: FullyQualifiedAuthority) -> Self {
+        BindProtocol {
+            destination_name: Some(name),
+            ..self
         }
     }
 }
@@ -235,12 +403,120 @@ where
     type Service = Service<B>;
     type BindError = ();
 
-    fn bind(&self, addr: &SocketAddr) -> Result<Self::Service, Self::BindError> {
-        Ok(self.bind.bind_service(addr, &self.protocol))
+    fn bind(&self, addr: &SocketAddr, metadata: &Metadata) -> Result<Self::Service, Self::BindError> {
+        Ok(self.bind.bind_service(addr, metadata, &self.destination_name, &self.protocol))
     }
 }
 
 
+// ===== impl Endpoint =====
+
+
+impl<B> tower::NewService for Endpoint<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    type Request = http::Request<sensor::http::RequestBody<B>>;
+    type Response = http::Response<HttpBody>;
+    type Error = tower_h2::client::Error;
+    type Service = EndpointService<B>;
+    type InitError = <Client<B> as tower::NewService>::InitError;
+    type Future = NewEndpoint<B>;
+
+    fn new_service(&self) -> Self::Future {
+        match *self {
+            Endpoint::Dedicated(ref client) =>
+                NewEndpoint::Dedicated(client.new_service()),
+            Endpoint::Shared(ref client) =>
+                NewEndpoint::Shared(Some((**client).clone())),
+        }
+    }
+}
+
+impl<B> Future for NewEndpoint<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    type Item = EndpointService<B>;
+    type Error = <Client<B> as tower::NewService>::InitError;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match *self {
+            NewEndpoint::Dedicated(ref mut f) => {
+                let service = try_ready!(f.poll());
+                Ok(Async::Ready(EndpointService::Dedicated(service)))
+            },
+            NewEndpoint::Shared(ref mut client) => {
+                let client = client.take().expect("polled after ready");
+                Ok(Async::Ready(EndpointService::Shared(client)))
+            },
+        }
+    }
+}
+
+impl<B> tower::Service for EndpointService<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    type Request = http::Request<sensor::http::RequestBody<B>>;
+    type Response = http::Response<HttpBody>;
+    type Error = tower_h2::client::Error;
+    type Future = EndpointResponse<B>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        match *self {
+            EndpointService::Dedicated(ref mut svc) => svc.poll_ready(),
+            EndpointService::Shared(ref mut svc) =>
+                svc.poll_ready().map_err(shared_http2_error),
+        }
+    }
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        match *self {
+            EndpointService::Dedicated(ref mut svc) =>
+                EndpointResponse::Dedicated(svc.call(request)),
+            EndpointService::Shared(ref mut svc) =>
+                EndpointResponse::Shared(svc.call(request)),
+        }
+    }
+}
+
+impl<B> Future for EndpointResponse<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    type Item = http::Response<HttpBody>;
+    type Error = tower_h2::client::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        match *self {
+            EndpointResponse::Dedicated(ref mut f) => f.poll(),
+            EndpointResponse::Shared(ref mut f) =>
+                f.poll().map_err(shared_http2_error),
+        }
+    }
+}
+
+/// Recovers the HTTP/2 error from a failure of the shared client.
+///
+/// Failures to connect, or of the client's buffer, are reported as internal
+/// errors, like failures of an HTTP/1 client.
+fn shared_http2_error<E: fmt::Display>(
+    error: tower_buffer::Error<ReconnectError<tower_h2::client::Error, E>>,
+) -> tower_h2::client::Error {
+    match error {
+        tower_buffer::Error::Inner(ReconnectError::Inner(e)) => e,
+        tower_buffer::Error::Inner(ReconnectError::Connect(e)) => {
+            debug!("shared http/2 client connect error: {}", e);
+            h2::Reason::INTERNAL_ERROR.into()
+        },
+        _ => {
+            debug!("shared http/2 client failed");
+            h2::Reason::INTERNAL_ERROR.into()
+        },
+    }
+}
+
 // ===== impl NormalizeUri =====
 
 
@@ -330,6 +606,17 @@ impl Protocol {
         }
     }
 
+    /// Returns true if requests may be sent to another proxy over HTTP/2.
+    ///
+    /// Only HTTP/1 requests with an authority are upgraded, since HTTP/2
+    /// requests must have one.
+    pub fn can_upgrade(&self) -> bool {
+        match *self {
+            Protocol::Http1(Host::Authority(_)) => true,
+            _ => false,
+        }
+    }
+
     pub fn into_key<T>(self, key: T) -> Reuse<(T, Protocol)> {
         if self.is_cachable() {
             Reuse::Reusable((key, self))
@@ -1,12 +1,19 @@
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::time::Duration;
 
 use http;
 
+use faults::Faults;
+use fully_qualified_authority::FullyQualifiedAuthority;
+use policy::Policy;
+use rate_limit::RateLimits;
+use route::Routes;
+use rules::Rules;
 use transport::{Host, HostAndPort, HostAndPortError};
 use convert::TryFrom;
 
@@ -35,11 +42,64 @@ pub struct Config {
     /// The maximum amount of time to wait for a connection to the private peer.
     pub private_connect_timeout: Duration,
 
+    /// Original destination ports of inbound connections that are proxied as
+    /// TCP, without detecting their protocol.
+    pub inbound_opaque_ports: Vec<u16>,
+
+    /// Original destination ports of outbound connections that are proxied as
+    /// TCP, without detecting their protocol.
+    pub outbound_opaque_ports: Vec<u16>,
+
+    /// Targets to which outbound `CONNECT` requests may open tunnels, as
+    /// `(suffix, port)` pairs. A target is allowed if its host matches a
+    /// suffix and, if one is given, its port matches, too. When unset,
+    /// tunnels may be opened to any target.
+    pub outbound_connect_allowlist: Option<Vec<(String, Option<u16>)>>,
+
+    /// The maximum amount of time to wait for the first bytes of a connection
+    /// to detect its protocol. Connections that send nothing in time are
+    /// proxied as TCP.
+    pub sniff_timeout: Duration,
+
     /// The path to "/etc/resolv.conf"
     pub resolv_conf_path: PathBuf,
 
+    /// Rules that choose the scheme of Destination API requests, as `(suffix,
+    /// scheme)` pairs. Names that match a rule are resolved with the controller
+    /// even if they are not cluster-local.
+    pub destination_schemes: Vec<(String, String)>,
+
+    /// Rules that override the load-balancing strategy of destinations, as
+    /// `(suffix, strategy)` pairs. Destinations that match no rule use the
+    /// strategy chosen by the controller.
+    pub balance_strategies: Vec<(String, BalanceStrategy)>,
+
+    /// Rules that route requests for destinations by consistent hashing, as
+    /// `(suffix, key)` pairs. These take precedence over `balance_strategies`.
+    pub hash_keys: Vec<(String, HashKey)>,
+
+    /// Destinations whose traffic is split between backing destinations, as
+    /// `(name, [(backend, weight)])` pairs. Names are fully qualified in the
+    /// pod's namespace, and each request for `name` is sent to one of its
+    /// backends, chosen at random by weight.
+    pub traffic_splits: Vec<(String, Vec<(String, u32)>)>,
+
     /// Where to talk to the control plane.
-    pub control_host_and_port: HostAndPort,
+    ///
+    /// When there is no controller, the proxy runs standalone: reports are written
+    /// to `report_sink` and destinations are resolved from `static_destinations`.
+    pub control_host_and_port: Option<HostAndPort>,
+
+    /// Where to write telemetry reports when there is no controller.
+    pub report_sink: Option<ReportSink>,
+
+    /// The addresses of destinations, used in place of the controller's
+    /// Destination API when running standalone.
+    pub static_destinations: Vec<(http::uri::Authority, Vec<SocketAddr>)>,
+
+    /// The path to a file of destinations, which is watched for changes, used in
+    /// place of `static_destinations` when running standalone.
+    pub destinations_path: Option<PathBuf>,
 
     /// Event queue capacity.
     pub event_buffer_capacity: usize,
@@ -47,15 +107,53 @@ pub struct Config {
     /// Interval after which to flush metrics.
     pub metrics_flush_interval: Duration,
 
+    /// The layout of response latency histograms.
+    pub latency_buckets: LatencyBuckets,
+
     /// Timeout after which to cancel telemetry reports.
     pub report_timeout: Duration,
 
     /// Timeout after which to cancel binding a request.
     pub bind_timeout: Duration,
 
+    /// Route definitions, loaded from a file, used to name requests in telemetry.
+    pub routes: Routes,
+
+    /// Rules, loaded from a file, that route or rewrite outbound requests
+    /// before they are routed by authority.
+    pub routing_rules: Rules,
+
+    /// The largest request body that is buffered to mirror a request. Requests
+    /// with larger bodies are not mirrored.
+    pub mirror_max_body_bytes: usize,
+
+    /// Faults, loaded from a file, to inject into inbound requests.
+    pub inbound_faults: Faults,
+
+    /// Faults, loaded from a file, to inject into outbound requests.
+    pub outbound_faults: Faults,
+
+    /// Rate limits, loaded from a file, for inbound requests.
+    pub inbound_rate_limits: RateLimits,
+
+    /// Allow and deny rules, loaded from a file, for inbound requests and
+    /// connections.
+    pub inbound_policy: Policy,
+
+    /// Where to send tracing spans, if anywhere.
+    ///
+    /// When set, the proxy starts a trace for requests that do not already carry
+    /// trace context.
+    pub trace_collector: Option<http::Uri>,
+
     pub pod_name: Option<String>,
     pub pod_namespace: String,
     pub node_name: Option<String>,
+
+    /// The zone in which the proxy runs, if known.
+    ///
+    /// Outbound requests prefer endpoints in the same zone.
+    pub zone: Option<String>,
 }
 
 /// Configuration settings for binding a listener.
@@ -67,6 +165,77 @@ pub struct Listener {
     pub addr: Addr,
 }
 
+/// Where a standalone proxy writes telemetry reports, as newline-delimited JSON.
+///
+/// Configured as a file path, or as `-` for stdout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReportSink {
+    Stdout,
+    File(PathBuf),
+}
+
+/// How requests are distributed across a destination's endpoints.
+///
+/// Every strategy prefers endpoints in the proxy's zone, if it is known.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BalanceStrategy {
+    /// Endpoints are used in turn. Configured as `round-robin`.
+    RoundRobin,
+
+    /// The endpoint with fewer pending requests, of two chosen at random, is used.
+    /// Configured as `least-requests`.
+    LeastRequests,
+
+    /// Like `LeastRequests`, but pending requests are weighted by a moving
+    /// average of each endpoint's latency that favors recent peaks. Configured
+    /// as `peak-ewma`.
+    PeakEwma,
+}
+
+/// The part of a request that is hashed to choose an endpoint, so that
+/// requests with the same key reach the same endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HashKey {
+    /// Configured as `header:<name>`.
+    Header(http::header::HeaderName),
+
+    /// Configured as `cookie:<name>`.
+    Cookie(String),
+
+    /// The path segment at the given index, starting from 0. Configured as
+    /// `path:<index>`.
+    PathSegment(usize),
+
+    /// The address of the client. Configured as `source-ip`.
+    SourceIp,
+}
+
+/// The layout of response latency histograms.
+///
+/// Configured as either a comma-separated list of bucket bounds, in milliseconds
+/// (e.g. `0.5,1,5,10,100`), or as `log-linear:<min ms>,<max ms>,<buckets per
+/// decade>`. Everything above the largest bound is counted in an additional,
+/// unbounded bucket.
+///
+/// The layout is only configured locally; it is not negotiated with the
+/// controller. Each report carries its bounds, so the controller can decode any
+/// layout, but its own Prometheus buckets are fixed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LatencyBuckets {
+    /// Matches the buckets used by the controller's Prometheus histograms.
+    Default,
+
+    /// Buckets with the given inclusive upper bounds, in increasing order.
+    Bounds(Vec<Duration>),
+
+    /// `per_decade` linear buckets for each power of ten from `min` to `max`.
+    LogLinear {
+        min: Duration,
+        max: Duration,
+        per_decade: u32,
+    },
+}
+
 /// A logical address. This abstracts over the various strategies for cross
 /// process communication.
 #[derive(Clone, Copy, Debug)]
@@ -75,7 +244,10 @@ pub struct Addr(SocketAddr);
 /// Errors produced when loading a `Config` struct.
 #[derive(Clone, Debug)]
 pub enum Error {
-    InvalidEnvVar
+    InvalidEnvVar,
+    /// A file named by an environment variable could not be read or is not
+    /// valid.
+    InvalidFile(PathBuf),
 }
 
 #[derive(Clone, Debug)]
@@ -85,6 +257,25 @@ pub enum ParseError {
     HostIsNotAnIpAddress,
     NotUnicode,
     UrlError(UrlError),
+    /// Latency bucket bounds must be positive and strictly increasing, and there
+    /// may be at most `MAX_LATENCY_BUCKET_BOUNDS` of them.
+    InvalidLatencyBuckets,
+    /// Static destinations must be given as `authority=addr[,addr...]`, separated
+    /// by semicolons.
+    InvalidStaticDestinations,
+    /// Destination schemes must be given as `suffix=scheme`, separated by commas.
+    InvalidDestinationSchemes,
+    /// Balance strategies must be given as `suffix=strategy`, separated by commas.
+    InvalidBalanceStrategies,
+    /// Hash keys must be given as `suffix=key`, separated by commas.
+    InvalidHashKeys,
+    /// Traffic splits must be given as `name=backend:weight[,backend:weight...]`,
+    /// separated by semicolons, and at least one backend of each must have a
+    /// positive weight.
+    InvalidTrafficSplits,
+    /// `CONNECT` allowlists must be given as `suffix[:port]`, separated by
+    /// commas.
+    InvalidConnectAllowlist,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -123,6 +314,7 @@ pub struct TestEnv {
 // Environment variables to look at when loading the configuration
 const ENV_EVENT_BUFFER_CAPACITY: &str = "CONDUIT_PROXY_EVENT_BUFFER_CAPACITY";
 pub const ENV_METRICS_FLUSH_INTERVAL_SECS: &str = "CONDUIT_PROXY_METRICS_FLUSH_INTERVAL_SECS";
+pub const ENV_LATENCY_BUCKETS: &str = "CONDUIT_PROXY_LATENCY_BUCKETS";
 const ENV_REPORT_TIMEOUT_SECS: &str = "CONDUIT_PROXY_REPORT_TIMEOUT_SECS";
 pub const ENV_PRIVATE_LISTENER: &str = "CONDUIT_PROXY_PRIVATE_LISTENER";
 pub const ENV_PRIVATE_FORWARD: &str = "CONDUIT_PROXY_PRIVATE_FORWARD";
@@ -131,12 +323,32 @@ pub const ENV_CONTROL_LISTENER: &str = "CONDUIT_PROXY_CONTROL_LISTENER";
 const ENV_PRIVATE_CONNECT_TIMEOUT: &str = "CONDUIT_PROXY_PRIVATE_CONNECT_TIMEOUT";
 const ENV_PUBLIC_CONNECT_TIMEOUT: &str = "CONDUIT_PROXY_PUBLIC_CONNECT_TIMEOUT";
 pub const ENV_BIND_TIMEOUT: &str = "CONDUIT_PROXY_BIND_TIMEOUT";
+pub const ENV_INBOUND_OPAQUE_PORTS: &str = "CONDUIT_PROXY_INBOUND_OPAQUE_PORTS";
+pub const ENV_OUTBOUND_OPAQUE_PORTS: &str = "CONDUIT_PROXY_OUTBOUND_OPAQUE_PORTS";
+pub const ENV_SNIFF_TIMEOUT: &str = "CONDUIT_PROXY_SNIFF_TIMEOUT";
+pub const ENV_OUTBOUND_CONNECT_ALLOWLIST: &str = "CONDUIT_PROXY_OUTBOUND_CONNECT_ALLOWLIST";
 
 const ENV_NODE_NAME: &str = "CONDUIT_PROXY_NODE_NAME";
+pub const ENV_ZONE: &str = "CONDUIT_PROXY_ZONE";
 const ENV_POD_NAME: &str = "CONDUIT_PROXY_POD_NAME";
 pub const ENV_POD_NAMESPACE: &str = "CONDUIT_PROXY_POD_NAMESPACE";
 
 pub const ENV_CONTROL_URL: &str = "CONDUIT_PROXY_CONTROL_URL";
+pub const ENV_DESTINATION_SCHEMES: &str = "CONDUIT_PROXY_DESTINATION_SCHEMES";
+pub const ENV_BALANCE_STRATEGIES: &str = "CONDUIT_PROXY_BALANCE_STRATEGIES";
+pub const ENV_HASH_KEYS: &str = "CONDUIT_PROXY_HASH_KEYS";
+pub const ENV_TRAFFIC_SPLITS: &str = "CONDUIT_PROXY_TRAFFIC_SPLITS";
+pub const ENV_REPORT_PATH: &str = "CONDUIT_PROXY_REPORT_PATH";
+pub const ENV_STATIC_DESTINATIONS: &str = "CONDUIT_PROXY_STATIC_DESTINATIONS";
+pub const ENV_DESTINATIONS_PATH: &str = "CONDUIT_PROXY_DESTINATIONS_PATH";
+pub const ENV_ROUTES_PATH: &str = "CONDUIT_PROXY_ROUTES_PATH";
+pub const ENV_ROUTING_RULES_PATH: &str = "CONDUIT_PROXY_ROUTING_RULES_PATH";
+const ENV_MIRROR_MAX_BODY_BYTES: &str = "CONDUIT_PROXY_MIRROR_MAX_BODY_BYTES";
+pub const ENV_INBOUND_FAULTS_PATH: &str = "CONDUIT_PROXY_INBOUND_FAULTS_PATH";
+pub const ENV_OUTBOUND_FAULTS_PATH: &str = "CONDUIT_PROXY_OUTBOUND_FAULTS_PATH";
+pub const ENV_INBOUND_RATE_LIMITS_PATH: &str = "CONDUIT_PROXY_INBOUND_RATE_LIMITS_PATH";
+pub const ENV_INBOUND_POLICY_PATH: &str = "CONDUIT_PROXY_INBOUND_POLICY_PATH";
+pub const ENV_TRACE_COLLECTOR_URL: &str = "CONDUIT_PROXY_TRACE_COLLECTOR_URL";
 const ENV_RESOLV_CONF: &str = "CONDUIT_RESOLV_CONF";
 
 // Default values for various configuration fields
@@ -149,7 +361,13 @@ const DEFAULT_CONTROL_LISTENER: &str = "tcp://0.0.0.0:4190";
 const DEFAULT_PRIVATE_CONNECT_TIMEOUT_MS: u64 = 20;
 const DEFAULT_PUBLIC_CONNECT_TIMEOUT_MS: u64 = 300;
 const DEFAULT_BIND_TIMEOUT_MS: u64 = 10_000; // ten seconds, as in Linkerd.
+const DEFAULT_SNIFF_TIMEOUT_MS: u64 = 10_000;
 const DEFAULT_RESOLV_CONF: &str = "/etc/resolv.conf";
+const DEFAULT_MIRROR_MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// The maximum number of configured latency bucket bounds. With the unbounded
+/// bucket, histograms have at most 256 buckets.
+const MAX_LATENCY_BUCKET_BOUNDS: usize = 255;
 
 // ===== impl Config =====
 
@@ -167,10 +385,16 @@ impl<'a> TryFrom<&'a Strings> for Config {
         let public_connect_timeout = parse(strings, ENV_PUBLIC_CONNECT_TIMEOUT, parse_number);
         let private_connect_timeout = parse(strings, ENV_PRIVATE_CONNECT_TIMEOUT, parse_number);
         let bind_timeout = parse(strings, ENV_BIND_TIMEOUT, parse_number);
+        let inbound_opaque_ports = parse(strings, ENV_INBOUND_OPAQUE_PORTS, parse_ports);
+        let outbound_opaque_ports = parse(strings, ENV_OUTBOUND_OPAQUE_PORTS, parse_ports);
+        let sniff_timeout = parse(strings, ENV_SNIFF_TIMEOUT, parse_number);
+        let outbound_connect_allowlist =
+            parse(strings, ENV_OUTBOUND_CONNECT_ALLOWLIST, parse_connect_allowlist);
         let resolv_conf_path = strings.get(ENV_RESOLV_CONF);
         let event_buffer_capacity = parse(strings, ENV_EVENT_BUFFER_CAPACITY, parse_number);
         let metrics_flush_interval_secs =
             parse(strings, ENV_METRICS_FLUSH_INTERVAL_SECS, parse_number);
+        let latency_buckets = parse(strings, ENV_LATENCY_BUCKETS, parse_latency_buckets);
         let report_timeout = parse(strings, ENV_REPORT_TIMEOUT_SECS, parse_number);
         let pod_name = strings.get(ENV_POD_NAME);
         let pod_namespace = strings.get(ENV_POD_NAMESPACE).and_then(|maybe_value| {
@@ -181,16 +405,61 @@ impl<'a> TryFrom<&'a Strings> for Config {
             })
         });
         let node_name = strings.get(ENV_NODE_NAME);
+        let zone = strings.get(ENV_ZONE);
+        let routes = load(strings, ENV_ROUTES_PATH, Routes::load);
+        let routing_rules = load(strings, ENV_ROUTING_RULES_PATH, Rules::load);
+        let mirror_max_body_bytes = parse(strings, ENV_MIRROR_MAX_BODY_BYTES, parse_number);
+        let inbound_faults = load(strings, ENV_INBOUND_FAULTS_PATH, Faults::load);
+        let outbound_faults = load(strings, ENV_OUTBOUND_FAULTS_PATH, Faults::load);
+        let inbound_rate_limits =
+            load(strings, ENV_INBOUND_RATE_LIMITS_PATH, RateLimits::load);
+        let inbound_policy = load(strings, ENV_INBOUND_POLICY_PATH, Policy::load);
+        let trace_collector = parse(strings, ENV_TRACE_COLLECTOR_URL, parse_http_url);
+
+        let destination_schemes =
+            parse(strings, ENV_DESTINATION_SCHEMES, parse_destination_schemes);
+        let balance_strategies =
+            parse(strings, ENV_BALANCE_STRATEGIES, parse_balance_strategies);
+        let hash_keys = parse(strings, ENV_HASH_KEYS, parse_hash_keys);
+        let traffic_splits = parse(strings, ENV_TRAFFIC_SPLITS, parse_traffic_splits);
+        // Splits are looked up by the normalized name of a request's destination,
+        // so short names are qualified in the pod's namespace.
+        let traffic_splits = match (traffic_splits, &pod_namespace) {
+            (Ok(Some(splits)), &Ok(ref namespace)) =>
+                Ok(Some(normalize_traffic_splits(splits, namespace))),
+            (r, _) => r,
+        };
+        let report_sink = parse(strings, ENV_REPORT_PATH, parse_report_sink);
+        let destinations_path = strings.get(ENV_DESTINATIONS_PATH);
+        let static_destinations = match (
+            parse(strings, ENV_STATIC_DESTINATIONS, parse_static_destinations),
+            &destinations_path,
+        ) {
+            (Ok(Some(_)), &Ok(Some(_))) => {
+                error!(
+                    "{} and {} are mutually exclusive",
+                    ENV_STATIC_DESTINATIONS,
+                    ENV_DESTINATIONS_PATH
+                );
+                Err(Error::InvalidEnvVar)
+            },
+            (r, _) => r,
+        };
 
         // There is no default controller URL because a default would make it
         // too easy to connect to the wrong controller, which would be dangerous.
-        let control_host_and_port = match parse(strings, ENV_CONTROL_URL, parse_url) {
-            Ok(Some(x)) => Ok(x),
-            Ok(None) => {
+        // Running without a controller must be requested explicitly, by
+        // configuring where reports are written instead.
+        let control_host_and_port = match (parse(strings, ENV_CONTROL_URL, parse_url), &report_sink) {
+            (Ok(None), &Ok(None)) => {
                 error!("{} is not set", ENV_CONTROL_URL);
                 Err(Error::InvalidEnvVar)
             },
-            Err(e) => Err(e),
+            (Ok(Some(_)), &Ok(Some(_))) => {
+                error!("{} and {} are mutually exclusive", ENV_CONTROL_URL, ENV_REPORT_PATH);
+                Err(Error::InvalidEnvVar)
+            },
+            (r, _) => r,
         };
 
         Ok(Config {
@@ -214,22 +483,45 @@ impl<'a> TryFrom<&'a Strings> for Config {
             private_connect_timeout:
                 Duration::from_millis(private_connect_timeout?
                                           .unwrap_or(DEFAULT_PRIVATE_CONNECT_TIMEOUT_MS)),
+            inbound_opaque_ports: inbound_opaque_ports?.unwrap_or_default(),
+            outbound_opaque_ports: outbound_opaque_ports?.unwrap_or_default(),
+            outbound_connect_allowlist: outbound_connect_allowlist?,
+            sniff_timeout:
+                Duration::from_millis(sniff_timeout?.unwrap_or(DEFAULT_SNIFF_TIMEOUT_MS)),
             resolv_conf_path: resolv_conf_path?
                 .unwrap_or(DEFAULT_RESOLV_CONF.into())
                 .into(),
+            destination_schemes: destination_schemes?.unwrap_or_default(),
+            balance_strategies: balance_strategies?.unwrap_or_default(),
+            hash_keys: hash_keys?.unwrap_or_default(),
+            traffic_splits: traffic_splits?.unwrap_or_default(),
             control_host_and_port: control_host_and_port?,
+            report_sink: report_sink?,
+            static_destinations: static_destinations?.unwrap_or_default(),
+            destinations_path: destinations_path?.map(PathBuf::from),
 
             event_buffer_capacity: event_buffer_capacity?.unwrap_or(DEFAULT_EVENT_BUFFER_CAPACITY),
             metrics_flush_interval:
                 Duration::from_secs(metrics_flush_interval_secs?
                                         .unwrap_or(DEFAULT_METRICS_FLUSH_INTERVAL_SECS)),
+            latency_buckets: latency_buckets?.unwrap_or_default(),
             report_timeout:
                 Duration::from_secs(report_timeout?.unwrap_or(DEFAULT_REPORT_TIMEOUT_SECS)),
             bind_timeout:
                 Duration::from_millis(bind_timeout?.unwrap_or(DEFAULT_BIND_TIMEOUT_MS)),
+            routes: routes?.unwrap_or_default(),
+            routing_rules: routing_rules?.unwrap_or_default(),
+            mirror_max_body_bytes:
+                mirror_max_body_bytes?.unwrap_or(DEFAULT_MIRROR_MAX_BODY_BYTES),
+            inbound_faults: inbound_faults?.unwrap_or_default(),
+            outbound_faults: outbound_faults?.unwrap_or_default(),
+            inbound_rate_limits: inbound_rate_limits?.unwrap_or_default(),
+            inbound_policy: inbound_policy?.unwrap_or_default(),
+            trace_collector: trace_collector?,
             pod_name: pod_name?,
             pod_namespace: pod_namespace?,
             node_name: node_name?,
+            zone: zone?,
         })
     }
 }
@@ -240,6 +532,14 @@ impl Config {
     }
 }
 
+// ===== impl LatencyBuckets =====
+
+impl Default for LatencyBuckets {
+    fn default() -> Self {
+        LatencyBuckets::Default
+    }
+}
+
 // ===== impl Addr =====
 
 impl FromStr for Addr {
@@ -320,6 +620,279 @@ fn parse_url(s: &str) -> Result<HostAndPort, ParseError> {
         .map_err(|e| ParseError::UrlError(UrlError::AuthorityError(e)))
 }
 
+/// Parses an `http://` URL, such as a collector's endpoint. Unlike `parse_url`, the
+/// path is retained.
+fn parse_http_url(s: &str) -> Result<http::Uri, ParseError> {
+    let url = s.parse::<http::Uri>().map_err(|_| ParseError::UrlError(UrlError::SyntaxError))?;
+    if url.scheme_part().map(|s| s.as_str()) != Some("http") {
+        return Err(ParseError::UrlError(UrlError::UnsupportedScheme));
+    }
+    {
+        let authority = url.authority_part()
+            .ok_or_else(|| ParseError::UrlError(UrlError::MissingAuthority))?;
+        HostAndPort::try_from(authority)
+            .map_err(|e| ParseError::UrlError(UrlError::AuthorityError(e)))?;
+    }
+    Ok(url)
+}
+
+/// Parses destination scheme rules of the form `suffix=scheme`, separated by
+/// commas, e.g. `consul=consul,example.com=dns`.
+fn parse_destination_schemes(s: &str) -> Result<Vec<(String, String)>, ParseError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|rule| {
+            let mut parts = rule.splitn(2, '=');
+            let suffix = parts.next().unwrap_or("").trim().trim_matches('.');
+            let scheme = parts.next().unwrap_or("").trim();
+            if suffix.is_empty() || scheme.is_empty() {
+                return Err(ParseError::InvalidDestinationSchemes);
+            }
+            Ok((suffix.to_ascii_lowercase(), scheme.to_owned()))
+        })
+        .collect()
+}
+
+fn parse_balance_strategies(s: &str) -> Result<Vec<(String, BalanceStrategy)>, ParseError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|rule| {
+            let mut parts = rule.splitn(2, '=');
+            let suffix = parts.next().unwrap_or("").trim().trim_matches('.');
+            let strategy = match parts.next().map(str::trim) {
+                Some("round-robin") => BalanceStrategy::RoundRobin,
+                Some("least-requests") => BalanceStrategy::LeastRequests,
+                Some("peak-ewma") => BalanceStrategy::PeakEwma,
+                _ => return Err(ParseError::InvalidBalanceStrategies),
+            };
+            if suffix.is_empty() {
+                return Err(ParseError::InvalidBalanceStrategies);
+            }
+            Ok((suffix.to_ascii_lowercase(), strategy))
+        })
+        .collect()
+}
+
+fn parse_hash_keys(s: &str) -> Result<Vec<(String, HashKey)>, ParseError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|rule| {
+            let mut parts = rule.splitn(2, '=');
+            let suffix = parts.next().unwrap_or("").trim().trim_matches('.');
+            let key = parts.next().unwrap_or("").trim();
+            let mut key_parts = key.splitn(2, ':');
+            let key = match (key_parts.next(), key_parts.next()) {
+                (Some("header"), Some(name)) => {
+                    http::header::HeaderName::from_bytes(name.as_bytes())
+                        .map(HashKey::Header)
+                        .map_err(|_| ParseError::InvalidHashKeys)?
+                }
+                (Some("cookie"), Some(name)) if !name.is_empty() => {
+                    HashKey::Cookie(name.to_owned())
+                }
+                (Some("path"), Some(index)) => {
+                    index.parse()
+                        .map(HashKey::PathSegment)
+                        .map_err(|_| ParseError::InvalidHashKeys)?
+                }
+                (Some("source-ip"), None) => HashKey::SourceIp,
+                _ => return Err(ParseError::InvalidHashKeys),
+            };
+            if suffix.is_empty() {
+                return Err(ParseError::InvalidHashKeys);
+            }
+            Ok((suffix.to_ascii_lowercase(), key))
+        })
+        .collect()
+}
+
+/// Parses traffic splits of the form `name=backend:weight[,backend:weight...]`,
+/// separated by semicolons, e.g. `web.default.svc.cluster.local=web-v1:9,web-v2:1`.
+///
+/// Backends are qualified like the authorities of requests, so `web-v1` is
+/// resolved in the proxy's namespace, and use the port of the split request.
+fn parse_traffic_splits(
+    s: &str,
+) -> Result<Vec<(String, Vec<(String, u32)>)>, ParseError> {
+    s.split(';')
+        .map(str::trim)
+        .filter(|split| !split.is_empty())
+        .map(|split| {
+            let mut parts = split.splitn(2, '=');
+            let name = parts.next().unwrap_or("").trim().trim_right_matches('.');
+            if name.is_empty() {
+                return Err(ParseError::InvalidTrafficSplits);
+            }
+            let backends = parts.next()
+                .ok_or(ParseError::InvalidTrafficSplits)?
+                .split(',')
+                .map(|backend| {
+                    let mut parts = backend.trim().splitn(2, ':');
+                    let host = parts.next().unwrap_or("");
+                    let weight = parts.next()
+                        .and_then(|w| w.parse::<u32>().ok())
+                        .ok_or(ParseError::InvalidTrafficSplits)?;
+                    match host.parse::<http::uri::Authority>() {
+                        Ok(ref a) if a.port().is_none() => Ok((host.to_owned(), weight)),
+                        _ => Err(ParseError::InvalidTrafficSplits),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if backends.iter().all(|&(_, weight)| weight == 0) {
+                return Err(ParseError::InvalidTrafficSplits);
+            }
+            Ok((name.to_ascii_lowercase(), backends))
+        })
+        .collect()
+}
+
+/// Qualifies the names of traffic splits like the authorities of requests, so
+/// that a split named `web` applies to `web.<namespace>.svc.cluster.local`.
+fn normalize_traffic_splits(
+    splits: Vec<(String, Vec<(String, u32)>)>,
+    namespace: &str,
+) -> Vec<(String, Vec<(String, u32)>)> {
+    splits.into_iter()
+        .map(|(name, backends)| {
+            let name = match name.parse::<http::uri::Authority>() {
+                Ok(authority) => FullyQualifiedAuthority::normalize(&authority, namespace)
+                    .name
+                    .without_trailing_dot()
+                    .host()
+                    .to_owned(),
+                Err(_) => name,
+            };
+            (name, backends)
+        })
+        .collect()
+}
+
+/// Parses a comma-separated list of ports.
+fn parse_ports(s: &str) -> Result<Vec<u16>, ParseError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(parse_number)
+        .collect()
+}
+
+/// Parses `CONNECT` targets of the form `suffix[:port]`, separated by commas,
+/// e.g. `googleapis.com:443,api.example.com`.
+fn parse_connect_allowlist(s: &str) -> Result<Vec<(String, Option<u16>)>, ParseError> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|target| {
+            // IPv6 addresses are given in brackets, e.g. `[::1]:443`.
+            let (host, port) = match target.rfind(':') {
+                Some(i) if !target.ends_with(']') => {
+                    let port = target[i + 1..]
+                        .parse::<u16>()
+                        .map_err(|_| ParseError::InvalidConnectAllowlist)?;
+                    (&target[..i], Some(port))
+                }
+                _ => (target, None),
+            };
+            let host = host
+                .trim_matches(|c| c == '[' || c == ']')
+                .trim_matches('.');
+            if host.is_empty() {
+                return Err(ParseError::InvalidConnectAllowlist);
+            }
+            Ok((host.to_ascii_lowercase(), port))
+        })
+        .collect()
+}
+
+fn parse_report_sink(s: &str) -> Result<ReportSink, ParseError> {
+    match s {
+        "-" => Ok(ReportSink::Stdout),
+        path => Ok(ReportSink::File(PathBuf::from(path))),
+    }
+}
+
+/// Parses destinations of the form `authority=addr[,addr...]`, separated by
+/// semicolons, e.g. `web.default:8080=10.1.1.1:8080,10.1.1.2:8080;db:5432=10.1.2.1:5432`.
+fn parse_static_destinations(
+    s: &str,
+) -> Result<Vec<(http::uri::Authority, Vec<SocketAddr>)>, ParseError> {
+    s.split(';')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|dst| {
+            let mut parts = dst.splitn(2, '=');
+            let authority = parts.next()
+                .map(str::trim)
+                .and_then(|a| if a.is_empty() { None } else { a.parse::<http::uri::Authority>().ok() })
+                .ok_or(ParseError::InvalidStaticDestinations)?;
+            let addrs = parts.next()
+                .ok_or(ParseError::InvalidStaticDestinations)?
+                .split(',')
+                .map(|a| a.trim().parse::<SocketAddr>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| ParseError::InvalidStaticDestinations)?;
+            Ok((authority, addrs))
+        })
+        .collect()
+}
+
+fn parse_latency_buckets(s: &str) -> Result<LatencyBuckets, ParseError> {
+    if s.starts_with("log-linear:") {
+        let params = s["log-linear:".len()..].split(',').collect::<Vec<_>>();
+        if params.len() != 3 {
+            return Err(ParseError::InvalidLatencyBuckets);
+        }
+        let min = parse_millis(params[0])?;
+        let max = parse_millis(params[1])?;
+        let per_decade = parse_number(params[2].trim())?;
+        if min >= max || per_decade == 0 {
+            return Err(ParseError::InvalidLatencyBuckets);
+        }
+        if log_linear_bounds(min, max, per_decade) > MAX_LATENCY_BUCKET_BOUNDS as u64 {
+            return Err(ParseError::InvalidLatencyBuckets);
+        }
+        return Ok(LatencyBuckets::LogLinear { min, max, per_decade });
+    }
+
+    let bounds = s.split(',')
+        .map(parse_millis)
+        .collect::<Result<Vec<_>, _>>()?;
+    if bounds.len() > MAX_LATENCY_BUCKET_BOUNDS || bounds.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(ParseError::InvalidLatencyBuckets);
+    }
+    Ok(LatencyBuckets::Bounds(bounds))
+}
+
+/// Returns the most bounds that a log-linear layout may have: `min`, `max`, and
+/// `per_decade` bounds for each power of ten in between.
+fn log_linear_bounds(min: Duration, max: Duration, per_decade: u32) -> u64 {
+    let micros = |d: Duration| d.as_secs() * 1_000_000 + u64::from(d.subsec_nanos() / 1_000);
+    let (min, max) = (micros(min), micros(max));
+
+    let mut decades = 0u64;
+    let mut decade = min;
+    while decade < max {
+        decades += 1;
+        decade = decade.saturating_mul(10);
+    }
+    decades.saturating_mul(u64::from(per_decade)).saturating_add(2)
+}
+
+/// Parses a positive, possibly fractional, number of milliseconds with microsecond
+/// precision.
+fn parse_millis(s: &str) -> Result<Duration, ParseError> {
+    let ms: f64 = parse_number(s.trim())?;
+    let us = (ms * 1_000.0).round();
+    if !(us >= 1.0 && us < 1e15) {
+        return Err(ParseError::InvalidLatencyBuckets);
+    }
+    let us = us as u64;
+    Ok(Duration::new(us / 1_000_000, (us % 1_000_000) as u32 * 1_000))
+}
+
 fn parse<T, Parse>(strings: &Strings, name: &str, parse: Parse) -> Result<Option<T>, Error>
     where Parse: FnOnce(&str) -> Result<T, ParseError> {
     match strings.get(name)? {
@@ -333,3 +906,253 @@ fn parse<T, Parse>(strings: &Strings, name: &str, parse: Parse) -> Result<Option
         None => Ok(None),
     }
 }
+
+/// Loads the file named by the environment variable `name`, if it is set.
+fn load<T, E, Load>(strings: &Strings, name: &str, load: Load) -> Result<Option<T>, Error>
+    where Load: FnOnce(&Path) -> Result<T, E>, E: fmt::Debug {
+    match strings.get(name)? {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            let r = load(&path).map_err(|load_error| {
+                error!("{}={} could not be loaded: {:?}", name, path.display(), load_error);
+                Error::InvalidFile(path.clone())
+            })?;
+            Ok(Some(r))
+        },
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use convert::TryFrom;
+
+    use super::{
+        normalize_traffic_splits,
+        parse_balance_strategies,
+        parse_destination_schemes,
+        parse_hash_keys,
+        parse_latency_buckets,
+        parse_connect_allowlist,
+        parse_ports,
+        parse_static_destinations,
+        parse_traffic_splits,
+        BalanceStrategy,
+        Config,
+        Error,
+        HashKey,
+        LatencyBuckets,
+        Strings,
+        TestEnv,
+        ENV_CONTROL_URL,
+        ENV_INBOUND_FAULTS_PATH,
+        ENV_INBOUND_POLICY_PATH,
+        ENV_INBOUND_RATE_LIMITS_PATH,
+        ENV_OUTBOUND_FAULTS_PATH,
+        ENV_POD_NAMESPACE,
+        ENV_ROUTES_PATH,
+        ENV_ROUTING_RULES_PATH,
+    };
+
+    /// Loads a configuration in which `name` refers to a file that does not
+    /// exist.
+    fn load_missing_file(name: &'static str) -> Result<Config, Error> {
+        let mut env = TestEnv::new();
+        env.put(ENV_CONTROL_URL, "tcp://127.0.0.1:8086".to_owned());
+        env.put(ENV_POD_NAMESPACE, "ns".to_owned());
+        env.put(name, "/nonexistent/conduit-proxy-config".to_owned());
+        let strings: &Strings = &env;
+        Config::try_from(strings)
+    }
+
+    #[test]
+    fn missing_files_are_invalid() {
+        let names = [
+            ENV_ROUTES_PATH,
+            ENV_ROUTING_RULES_PATH,
+            ENV_INBOUND_FAULTS_PATH,
+            ENV_OUTBOUND_FAULTS_PATH,
+            ENV_INBOUND_RATE_LIMITS_PATH,
+            ENV_INBOUND_POLICY_PATH,
+        ];
+        for name in &names {
+            match load_missing_file(*name) {
+                Err(Error::InvalidFile(ref path))
+                    if path == Path::new("/nonexistent/conduit-proxy-config") => {}
+                r => panic!("unexpected for {}: {:?}", name, r),
+            }
+        }
+    }
+
+    #[test]
+    fn parses_latency_bucket_bounds() {
+        assert_eq!(
+            parse_latency_buckets("0.05, 1,2.5,1000").unwrap(),
+            LatencyBuckets::Bounds(vec![
+                Duration::new(0, 50_000),
+                Duration::from_millis(1),
+                Duration::new(0, 2_500_000),
+                Duration::from_secs(1),
+            ])
+        );
+
+        assert!(parse_latency_buckets("2,1").is_err());
+        assert!(parse_latency_buckets("1,1").is_err());
+        assert!(parse_latency_buckets("0,1").is_err());
+        assert!(parse_latency_buckets("-1").is_err());
+        assert!(parse_latency_buckets("1,,2").is_err());
+    }
+
+    #[test]
+    fn parses_log_linear_latency_buckets() {
+        assert_eq!(
+            parse_latency_buckets("log-linear:0.1,60000,9").unwrap(),
+            LatencyBuckets::LogLinear {
+                min: Duration::new(0, 100_000),
+                max: Duration::from_secs(60),
+                per_decade: 9,
+            }
+        );
+
+        assert!(parse_latency_buckets("log-linear:1,1,9").is_err());
+        assert!(parse_latency_buckets("log-linear:1,10,0").is_err());
+        assert!(parse_latency_buckets("log-linear:1,10").is_err());
+        // Five decades of 200 buckets each is more than a histogram may have.
+        assert!(parse_latency_buckets("log-linear:1,100000,200").is_err());
+        assert!(parse_latency_buckets("log-linear:1,100000,50").is_ok());
+    }
+
+    #[test]
+    fn parses_static_destinations() {
+        let dsts = parse_static_destinations(
+            "web.default:8080=10.1.1.1:8080, 10.1.1.2:8080; db:5432=[::1]:5432;"
+        ).unwrap();
+        assert_eq!(dsts.len(), 2);
+        assert_eq!(dsts[0].0, "web.default:8080");
+        assert_eq!(dsts[0].1, vec![
+            "10.1.1.1:8080".parse().unwrap(),
+            "10.1.1.2:8080".parse().unwrap(),
+        ]);
+        assert_eq!(dsts[1].0, "db:5432");
+        assert_eq!(dsts[1].1, vec!["[::1]:5432".parse().unwrap()]);
+
+        assert!(parse_static_destinations("web:8080").is_err());
+        assert!(parse_static_destinations("web:8080=web:8080").is_err());
+        assert!(parse_static_destinations("=10.1.1.1:8080").is_err());
+    }
+
+    #[test]
+    fn parses_destination_schemes() {
+        assert_eq!(
+            parse_destination_schemes(" .Consul=consul, example.com.=dns").unwrap(),
+            vec![
+                ("consul".to_owned(), "consul".to_owned()),
+                ("example.com".to_owned(), "dns".to_owned()),
+            ]
+        );
+
+        assert!(parse_destination_schemes("consul").is_err());
+        assert!(parse_destination_schemes("consul=").is_err());
+        assert!(parse_destination_schemes("=dns").is_err());
+    }
+
+    #[test]
+    fn parses_balance_strategies() {
+        assert_eq!(
+            parse_balance_strategies("web.prod=round-robin, Cache.=peak-ewma").unwrap(),
+            vec![
+                ("web.prod".to_owned(), BalanceStrategy::RoundRobin),
+                ("cache".to_owned(), BalanceStrategy::PeakEwma),
+            ]
+        );
+
+        assert!(parse_balance_strategies("web").is_err());
+        assert!(parse_balance_strategies("web=random").is_err());
+        assert!(parse_balance_strategies("=least-requests").is_err());
+    }
+
+    #[test]
+    fn parses_hash_keys() {
+        assert_eq!(
+            parse_hash_keys("a=header:X-User, b=cookie:session,c=path:1,d=source-ip").unwrap(),
+            vec![
+                ("a".to_owned(), HashKey::Header("x-user".parse().unwrap())),
+                ("b".to_owned(), HashKey::Cookie("session".to_owned())),
+                ("c".to_owned(), HashKey::PathSegment(1)),
+                ("d".to_owned(), HashKey::SourceIp),
+            ]
+        );
+
+        assert!(parse_hash_keys("a").is_err());
+        assert!(parse_hash_keys("a=header:").is_err());
+        assert!(parse_hash_keys("a=cookie:").is_err());
+        assert!(parse_hash_keys("a=path:first").is_err());
+        assert!(parse_hash_keys("a=source-ip:1").is_err());
+        assert!(parse_hash_keys("=source-ip").is_err());
+    }
+
+    #[test]
+    fn parses_traffic_splits() {
+        assert_eq!(
+            parse_traffic_splits("Web.default.svc.cluster.local.=web-v1:9, web-v2:1;api=api-v2:1").unwrap(),
+            vec![
+                (
+                    "web.default.svc.cluster.local".to_owned(),
+                    vec![("web-v1".to_owned(), 9), ("web-v2".to_owned(), 1)],
+                ),
+                ("api".to_owned(), vec![("api-v2".to_owned(), 1)]),
+            ]
+        );
+        assert_eq!(parse_traffic_splits("").unwrap(), vec![]);
+        assert_eq!(
+            normalize_traffic_splits(
+                parse_traffic_splits("api=api-v2:1;web.default=web-v1:1").unwrap(),
+                "ns",
+            ),
+            vec![
+                ("api.ns.svc.cluster.local".to_owned(), vec![("api-v2".to_owned(), 1)]),
+                ("web.default.svc.cluster.local".to_owned(), vec![("web-v1".to_owned(), 1)]),
+            ]
+        );
+
+        assert!(parse_traffic_splits("web").is_err());
+        assert!(parse_traffic_splits("=web-v1:1").is_err());
+        assert!(parse_traffic_splits("web=web-v1").is_err());
+        assert!(parse_traffic_splits("web=web-v1:-1").is_err());
+        assert!(parse_traffic_splits("web=web-v1:8080:1").is_err());
+        assert!(parse_traffic_splits("web=:1").is_err());
+        assert!(parse_traffic_splits("web=web-v1:0,web-v2:0").is_err());
+    }
+
+    #[test]
+    fn parses_ports() {
+        assert_eq!(parse_ports("3306, 25,5432").unwrap(), vec![3306, 25, 5432]);
+        assert_eq!(parse_ports("").unwrap(), Vec::<u16>::new());
+
+        assert!(parse_ports("mysql").is_err());
+        assert!(parse_ports("65536").is_err());
+    }
+
+    #[test]
+    fn parses_connect_allowlist() {
+        assert_eq!(
+            parse_connect_allowlist(".GoogleApis.com:443, api.example.com,10.1.1.1:8443").unwrap(),
+            vec![
+                ("googleapis.com".to_owned(), Some(443)),
+                ("api.example.com".to_owned(), None),
+                ("10.1.1.1".to_owned(), Some(8443)),
+            ]
+        );
+        assert_eq!(parse_connect_allowlist("").unwrap(), vec![]);
+
+        assert!(parse_connect_allowlist("api.example.com:https").is_err());
+        assert!(parse_connect_allowlist(":443").is_err());
+        assert_eq!(
+            parse_connect_allowlist("[::1]:443").unwrap(),
+            vec![("::1".to_owned(), Some(443))]
+        );
+    }
+}
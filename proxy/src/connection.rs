@@ -1,10 +1,10 @@
-use bytes::Buf;
+use bytes::{Buf, Bytes, BytesMut};
 use futures::*;
 use std;
 use std::io;
 use std::net::SocketAddr;
 use tokio_core::net::{TcpListener, TcpStreamNew, TcpStream};
-use tokio_core::reactor::Handle;
+use tokio_core::reactor::{Handle, Timeout as ReactorTimeout};
 use tokio_io::{AsyncRead, AsyncWrite};
 
 use config::Addr;
@@ -34,6 +34,9 @@ pub struct Connecting(TcpStreamNew);
 #[derive(Debug)]
 pub enum Connection {
     Plain(PlaintextSocket),
+    /// A plaintext socket from which bytes were read while peeking. They are
+    /// read again before anything more is read from the socket.
+    Peeked(Bytes, PlaintextSocket),
 }
 
 // ===== impl BoundPort =====
@@ -107,7 +110,24 @@ impl Connection {
 
     pub fn peek_future<T: AsMut<[u8]>>(self, buf: T) -> Peek<T> {
         Peek {
-            inner: Some((self, buf))
+            inner: Some((self, buf)),
+            len: 0,
+            is_complete: None,
+            timeout: None,
+        }
+    }
+
+    /// Puts back bytes that were read from the connection, so that they are
+    /// read again.
+    fn unread(self, bytes: &[u8]) -> Connection {
+        match self {
+            Connection::Plain(socket) => Connection::Peeked(Bytes::from(bytes), socket),
+            Connection::Peeked(rest, socket) => {
+                let mut peeked = BytesMut::with_capacity(bytes.len() + rest.len());
+                peeked.extend_from_slice(bytes);
+                peeked.extend_from_slice(&rest);
+                Connection::Peeked(peeked.freeze(), socket)
+            }
         }
     }
 
@@ -119,7 +139,15 @@ impl Connection {
     // as is done above.
     fn socket(&self) -> &PlaintextSocket {
         match self {
-            &Connection::Plain(ref socket) => socket
+            &Connection::Plain(ref socket) => socket,
+            &Connection::Peeked(_, ref socket) => socket,
+        }
+    }
+
+    fn socket_mut(&mut self) -> &mut PlaintextSocket {
+        match self {
+            &mut Connection::Plain(ref mut socket) => socket,
+            &mut Connection::Peeked(_, ref mut socket) => socket,
         }
     }
 }
@@ -130,70 +158,87 @@ impl io::Read for Connection {
 
         match *self {
             Plain(ref mut t) => t.read(buf),
+            Peeked(ref mut peeked, ref mut t) => {
+                if peeked.is_empty() {
+                    return t.read(buf);
+                }
+                let n = ::std::cmp::min(peeked.len(), buf.len());
+                buf[..n].copy_from_slice(&peeked.split_to(n));
+                Ok(n)
+            }
         }
     }
 }
 
 impl AsyncRead for Connection {
     unsafe fn prepare_uninitialized_buffer(&self, buf: &mut [u8]) -> bool {
-        use self::Connection::*;
-
-        match *self {
-            Plain(ref t) => t.prepare_uninitialized_buffer(buf),
-        }
+        self.socket().prepare_uninitialized_buffer(buf)
     }
 }
 
 impl io::Write for Connection {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        use self::Connection::*;
-
-        match *self {
-            Plain(ref mut t) => t.write(buf),
-        }
+        self.socket_mut().write(buf)
     }
 
     fn flush(&mut self) -> io::Result<()> {
-        use self::Connection::*;
-
-        match *self {
-            Plain(ref mut t) => t.flush(),
-        }
+        self.socket_mut().flush()
     }
 }
 
 impl AsyncWrite for Connection {
     fn shutdown(&mut self) -> Poll<(), io::Error> {
         use std::net::Shutdown;
-        use self::Connection::*;
 
-        match *self {
-            Plain(ref mut t) => {
-                try_ready!(AsyncWrite::shutdown(t));
-                // TCP shutdown the write side.
-                //
-                // If we're shutting down, then we definitely won't write
-                // anymore. So, we should tell the remote about this. This
-                // is relied upon in our TCP proxy, to start shutting down
-                // the pipe if one side closes.
-                TcpStream::shutdown(t, Shutdown::Write).map(Async::Ready)
-            },
-        }
+        let t = self.socket_mut();
+        try_ready!(AsyncWrite::shutdown(t));
+        // TCP shutdown the write side.
+        //
+        // If we're shutting down, then we definitely won't write
+        // anymore. So, we should tell the remote about this. This
+        // is relied upon in our TCP proxy, to start shutting down
+        // the pipe if one side closes.
+        TcpStream::shutdown(t, Shutdown::Write).map(Async::Ready)
     }
 
     fn write_buf<B: Buf>(&mut self, buf: &mut B) -> Poll<usize, io::Error> {
-        use self::Connection::*;
-
-        match *self {
-            Plain(ref mut t) => t.write_buf(buf),
-        }
+        self.socket_mut().write_buf(buf)
     }
 }
 
 // impl Peek
 
+/// Reads bytes from a connection, without consuming them.
+///
+/// The bytes that are read are put back into the connection when the peek
+/// completes.
 pub struct Peek<T> {
     inner: Option<(Connection, T)>,
+    /// How many bytes have been read into the buffer.
+    len: usize,
+    is_complete: Option<fn(&[u8]) -> bool>,
+    timeout: Option<ReactorTimeout>,
+}
+
+impl<T> Peek<T> {
+    /// Keeps reading until `is_complete` is true of the bytes read so far, or
+    /// the buffer is full, rather than completing as soon as any bytes are
+    /// read.
+    pub fn until(self, is_complete: fn(&[u8]) -> bool) -> Self {
+        Peek {
+            is_complete: Some(is_complete),
+            ..self
+        }
+    }
+
+    /// Completes with the bytes peeked so far, which may be none, if
+    /// `timeout` fires first.
+    pub fn with_timeout(self, timeout: ReactorTimeout) -> Self {
+        Peek {
+            timeout: Some(timeout),
+            ..self
+        }
+    }
 }
 
 impl<T: AsMut<[u8]>> Future for Peek<T> {
@@ -201,17 +246,45 @@ impl<T: AsMut<[u8]>> Future for Peek<T> {
     type Error = std::io::Error;
 
     fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
-        let (conn, mut buf) = self.inner.take().expect("polled after completed");
-        match conn.socket().peek(buf.as_mut()) {
-            Ok(n) => Ok(Async::Ready((conn, buf, n))),
-            Err(e) => match e.kind() {
-                std::io::ErrorKind::WouldBlock => {
-                    self.inner = Some((conn, buf));
-                    Ok(Async::NotReady)
+        let (mut conn, mut buf) = self.inner.take().expect("polled after completed");
+        loop {
+            let read = {
+                let buf = buf.as_mut();
+                let is_complete = self.len > 0 && match self.is_complete {
+                    Some(is_complete) => is_complete(&buf[..self.len]),
+                    None => true,
+                };
+                if is_complete || self.len == buf.len() {
+                    break;
+                }
+                // Reading, rather than peeking the socket, waits for more
+                // bytes to be received when some already have been.
+                io::Read::read(&mut conn, &mut buf[self.len..])
+            };
+            match read {
+                Ok(0) => break,
+                Ok(n) => self.len += n,
+                Err(e) => match e.kind() {
+                    std::io::ErrorKind::WouldBlock => {
+                        let expired = match self.timeout {
+                            Some(ref mut timeout) => timeout.poll()?.is_ready(),
+                            None => false,
+                        };
+                        if expired {
+                            debug!("peek timed out after {} bytes", self.len);
+                            break;
+                        }
+                        self.inner = Some((conn, buf));
+                        return Ok(Async::NotReady);
+                    },
+                    _ => return Err(e),
                 },
-                _ => Err(e)
-            },
+            }
         }
+
+        let n = self.len;
+        let conn = conn.unread(&buf.as_mut()[..n]);
+        Ok(Async::Ready((conn, buf, n)))
     }
 }
 
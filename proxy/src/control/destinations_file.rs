@@ -0,0 +1,206 @@
+//! Resolves destinations from a file that is watched for changes.
+//!
+//! The file has one destination per line: an authority followed by its
+//! addresses:
+//!
+//! ```text
+//! # authority         addresses
+//! web.default:8080    10.1.1.1:8080 10.1.1.2:8080
+//! db:5432             10.1.2.1:5432
+//! ```
+//!
+//! Authorities are qualified relative to the proxy's namespace, as the
+//! authorities of requests are. Addresses may not be given weights, since the
+//! load balancer does not use them; a weighted address is invalid rather than
+//! silently treated as unweighted.
+//!
+//! The file is re-read every second. When it is missing or invalid,
+//! the last good set of destinations is kept.
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use futures::{Async, Poll, Stream};
+use http;
+use tokio_core::reactor::{Handle, Interval};
+
+use super::discovery::{local_name, Destinations, Resolve};
+
+/// How often the file is checked for changes.
+const POLL_INTERVAL_SECS: u64 = 1;
+
+/// A `Resolve` backed by a file of destinations.
+// NOTE: `Interval` does not impl `Debug`.
+pub struct DestinationsFile {
+    path: PathBuf,
+    default_namespace: String,
+    interval: Interval,
+    /// Whether the file should be read without waiting for the interval.
+    read_now: bool,
+    /// The contents of the file when it was last read, so that destinations are
+    /// only reported when the file changes.
+    contents: Option<String>,
+}
+
+#[derive(Debug)]
+enum Error {
+    /// The line (1-indexed) does not have an authority and at least one address.
+    InvalidLine(usize),
+    InvalidAuthority(usize),
+    InvalidAddress(usize),
+}
+
+// ===== impl DestinationsFile =====
+
+impl DestinationsFile {
+    pub fn new(path: PathBuf, default_namespace: String, handle: &Handle) -> io::Result<Self> {
+        let interval = Interval::new(Duration::from_secs(POLL_INTERVAL_SECS), handle)?;
+        Ok(DestinationsFile {
+            path,
+            default_namespace,
+            interval,
+            read_now: true,
+            contents: None,
+        })
+    }
+
+    fn read(&self) -> io::Result<String> {
+        let mut s = String::new();
+        File::open(&self.path).and_then(|mut f| f.read_to_string(&mut s))?;
+        Ok(s)
+    }
+}
+
+impl Resolve for DestinationsFile {
+    fn poll_destinations(&mut self) -> Poll<Destinations, ()> {
+        loop {
+            if !self.read_now {
+                match self.interval.poll() {
+                    Ok(Async::Ready(Some(()))) => {}
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Ok(Async::Ready(None)) => return Err(()),
+                    Err(e) => {
+                        error!("destinations file interval failed: {}", e);
+                        return Err(());
+                    }
+                }
+            }
+            self.read_now = false;
+
+            let contents = match self.read() {
+                Ok(contents) => contents,
+                Err(e) => {
+                    warn!("failed to read {}: {}", self.path.display(), e);
+                    continue;
+                }
+            };
+            if self.contents.as_ref() == Some(&contents) {
+                continue;
+            }
+
+            let parsed = parse(&contents, &self.default_namespace);
+            self.contents = Some(contents);
+            match parsed {
+                Ok(destinations) => {
+                    debug!("read {} destinations from {}", destinations.len(), self.path.display());
+                    return Ok(Async::Ready(destinations));
+                }
+                Err(e) => {
+                    warn!("{} is not valid: {:?}", self.path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+fn parse(s: &str, default_namespace: &str) -> Result<Destinations, Error> {
+    let mut destinations = Destinations::new();
+
+    for (i, line) in s.lines().enumerate() {
+        let lineno = i + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let authority = fields.next()
+            .ok_or(Error::InvalidLine(lineno))?
+            .parse::<http::uri::Authority>()
+            .map_err(|_| Error::InvalidAuthority(lineno))?;
+
+        let mut addrs = HashSet::new();
+        for field in fields {
+            let addr = field.parse::<SocketAddr>()
+                .map_err(|_| Error::InvalidAddress(lineno))?;
+            addrs.insert(addr);
+        }
+        if addrs.is_empty() {
+            return Err(Error::InvalidLine(lineno));
+        }
+
+        if let Some(name) = local_name(&authority, default_namespace) {
+            destinations.entry(name).or_insert_with(HashSet::new).extend(addrs);
+        }
+    }
+
+    Ok(destinations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_destinations() {
+        let destinations = parse("
+            # Comments and blank lines are ignored.
+            web:8080          10.1.1.1:8080 10.1.1.2:8080
+            db.other:5432     [::1]:5432
+            web.test:8080     10.1.1.3:8080
+        ", "test").unwrap();
+        assert_eq!(destinations.len(), 2);
+
+        let addrs = |name: &str| {
+            let (_, addrs) = destinations.iter()
+                .find(|&(n, _)| n.without_trailing_dot().as_str() == name)
+                .expect(name);
+            let mut addrs = addrs.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+            addrs.sort();
+            addrs
+        };
+        assert_eq!(
+            addrs("web.test.svc.cluster.local:8080"),
+            vec!["10.1.1.1:8080", "10.1.1.2:8080", "10.1.1.3:8080"]
+        );
+        assert_eq!(addrs("db.other.svc.cluster.local:5432"), vec!["[::1]:5432"]);
+    }
+
+    #[test]
+    fn rejects_invalid_destinations() {
+        match parse("web:8080", "test") {
+            Err(Error::InvalidLine(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match parse("\nweb:8080 web:8080", "test") {
+            Err(Error::InvalidAddress(2)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match parse("web:8080 10.1.1.1:8080=heavy", "test") {
+            Err(Error::InvalidAddress(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match parse("web:8080 10.1.1.1:8080 10.1.1.2:8080=2", "test") {
+            Err(Error::InvalidAddress(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match parse("wéb:8080 10.1.1.1:8080", "test") {
+            Err(Error::InvalidAuthority(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+    }
+}
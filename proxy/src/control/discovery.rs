@@ -1,19 +1,23 @@
-use std::collections::{HashSet, VecDeque};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::collections::hash_map::{Entry, HashMap};
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::fmt;
+use std::sync::Arc;
 
 use futures::{Async, Future, Poll, Stream};
 use futures::sync::mpsc;
+use http;
 use tower::Service;
 use tower_h2::{HttpService, BoxBody, RecvBody};
 use tower_discover::{Change, Discover};
 use tower_grpc as grpc;
 
-use fully_qualified_authority::FullyQualifiedAuthority;
+use config::BalanceStrategy;
+use fully_qualified_authority::{FullyQualifiedAuthority, NamedAddress};
 
 use conduit_proxy_controller_grpc::common::{Destination, TcpAddress};
 use conduit_proxy_controller_grpc::destination::Update as PbUpdate;
+use conduit_proxy_controller_grpc::destination::balancer::Strategy as PbStrategy;
 use conduit_proxy_controller_grpc::destination::update::Update as PbUpdate2;
 use conduit_proxy_controller_grpc::destination::client::{Destination as DestinationSvc};
 
@@ -21,19 +25,32 @@ use conduit_proxy_controller_grpc::destination::client::{Destination as Destinat
 #[derive(Clone, Debug)]
 pub struct Discovery {
     tx: mpsc::UnboundedSender<(FullyQualifiedAuthority, mpsc::UnboundedSender<Update>)>,
+    schemes: Schemes,
 }
 
+/// Chooses the scheme of each Destination API request from rules that match the
+/// suffix of the destination's host.
+///
+/// Names that match no rule are only resolved if they are cluster-local, with the
+/// `k8s` scheme.
+#[derive(Clone, Debug, Default)]
+pub struct Schemes(Arc<Vec<(String, String)>>);
+
 /// A `tower_discover::Discover`, given to a `tower_balance::Balance`.
 #[derive(Debug)]
 pub struct Watch<B> {
     rx: mpsc::UnboundedReceiver<Update>,
     bind: B,
+    /// The most recent strategy chosen by the controller, if it has not been
+    /// taken yet.
+    strategy: Option<BalanceStrategy>,
 }
 
 /// A background handle to eventually bind on the controller thread.
 #[derive(Debug)]
 pub struct Background {
     rx: mpsc::UnboundedReceiver<(FullyQualifiedAuthority, mpsc::UnboundedSender<Update>)>,
+    schemes: Schemes,
 }
 
 /// A future returned from `Background::work()`, doing the work of talking to
@@ -51,10 +68,12 @@ pub struct DiscoveryWork<T: HttpService<ResponseBody = RecvBody>> {
     rpc_ready: bool,
     /// A receiver of new watch requests.
     rx: mpsc::UnboundedReceiver<(FullyQualifiedAuthority, mpsc::UnboundedSender<Update>)>,
+    schemes: Schemes,
 }
 
 struct DestinationSet<T: HttpService<ResponseBody = RecvBody>> {
-    addrs: HashSet<SocketAddr>,
+    addrs: HashMap<SocketAddr, Metadata>,
+    strategy: Option<BalanceStrategy>,
     needs_reconnect: bool,
     rx: UpdateRx<T>,
     txs: Vec<mpsc::UnboundedSender<Update>>,
@@ -79,6 +98,34 @@ enum UpdateRx<T: HttpService<ResponseBody = RecvBody>> {
 type UpdateRsp<F> =
     grpc::client::server_streaming::ResponseFuture<PbUpdate, F>;
 
+/// The addresses of each destination known to a `Resolve`.
+pub type Destinations = HashMap<FullyQualifiedAuthority, HashSet<SocketAddr>>;
+
+/// A source of destination addresses, used in place of the controller's
+/// Destination API.
+pub trait Resolve {
+    /// Polls for changes to the set of destinations.
+    ///
+    /// Returns `Ready` with every known destination whenever any of them
+    /// changes, and `NotReady` otherwise.
+    fn poll_destinations(&mut self) -> Poll<Destinations, ()>;
+}
+
+/// A future returned from `Background::work_local()`, serving watches from a
+/// `Resolve` when there is no controller.
+#[derive(Debug)]
+pub struct LocalWork<R> {
+    resolve: R,
+    destinations: Destinations,
+    watches: HashMap<FullyQualifiedAuthority, Vec<mpsc::UnboundedSender<Update>>>,
+    /// A receiver of new watch requests.
+    rx: mpsc::UnboundedReceiver<(FullyQualifiedAuthority, mpsc::UnboundedSender<Update>)>,
+}
+
+/// Resolves destinations from a fixed set of addresses.
+#[derive(Debug)]
+pub struct StaticDestinations(Option<Destinations>);
+
 /// Wraps the error types returned by `UpdateRx` polls.
 ///
 /// An `UpdateRx` error is either the error type of the `Future` in the
@@ -91,12 +138,19 @@ enum RxError<T> {
     Stream(grpc::Error),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 enum Update {
-    Insert(SocketAddr),
+    Insert(SocketAddr, Metadata),
     Remove(SocketAddr),
+    Strategy(BalanceStrategy),
 }
 
+/// Describes an endpoint, as reported by the Destination API.
+///
+/// Endpoints that are not resolved by the controller have no metadata.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Metadata(Arc<BTreeMap<String, String>>);
+
 /// Bind a `SocketAddr` with a protocol.
 pub trait Bind {
     /// Requests handled by the discovered services
@@ -113,22 +167,24 @@ pub trait Bind {
     /// The discovered `Service` instance.
     type Service: Service<Request = Self::Request, Response = Self::Response, Error = Self::Error>;
 
-    /// Bind a socket address with a service.
-    fn bind(&self, addr: &SocketAddr) -> Result<Self::Service, Self::BindError>;
+    /// Bind a socket address, described by `metadata`, with a service.
+    fn bind(&self, addr: &SocketAddr, metadata: &Metadata) -> Result<Self::Service, Self::BindError>;
 }
 
 /// Creates a "channel" of `Discovery` to `Background` handles.
 ///
 /// The `Discovery` is used by a listener, the `Background` is consumed
 /// on the controller thread.
-pub fn new() -> (Discovery, Background) {
+pub fn new(schemes: Schemes) -> (Discovery, Background) {
     let (tx, rx) = mpsc::unbounded();
     (
         Discovery {
             tx,
+            schemes: schemes.clone(),
         },
         Background {
             rx,
+            schemes,
         },
     )
 }
@@ -147,12 +203,123 @@ impl Discovery {
         Watch {
             rx,
             bind,
+            strategy: None,
         }
     }
+
+    /// Returns the name with which `authority` is resolved, or `None` if it is
+    /// not resolved with the Destination API.
+    ///
+    /// Names with a configured scheme are resolved as they are; otherwise,
+    /// only names that qualify as cluster-local are resolved.
+    pub fn resolvable_name(
+        &self,
+        authority: &http::uri::Authority,
+        default_namespace: &str,
+    ) -> Option<FullyQualifiedAuthority> {
+        if self.schemes.configured(authority.host()).is_some() {
+            return Some(FullyQualifiedAuthority::verbatim(authority));
+        }
+
+        match FullyQualifiedAuthority::normalize(authority, default_namespace) {
+            NamedAddress { name, use_destination_service: true } => Some(name),
+            NamedAddress { .. } => None,
+        }
+    }
+}
+
+// ==== impl Schemes =====
+
+/// The scheme of cluster-local names that match no rule.
+const DEFAULT_SCHEME: &str = "k8s";
+
+impl Schemes {
+    /// Builds `Schemes` from `(suffix, scheme)` rules, which are tried in order.
+    pub fn new(rules: Vec<(String, String)>) -> Self {
+        Schemes(Arc::new(rules))
+    }
+
+    /// Returns the scheme of the first rule whose suffix matches `host`, if any.
+    fn configured(&self, host: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|&&(ref suffix, _)| host_has_suffix(host, suffix))
+            .map(|&(_, ref scheme)| scheme.as_str())
+    }
+
+    fn scheme(&self, auth: &FullyQualifiedAuthority) -> &str {
+        self.configured(auth.without_trailing_dot().host())
+            .unwrap_or(DEFAULT_SCHEME)
+    }
+}
+
+/// Returns true if `host` ends with `suffix`.
+///
+/// Suffixes match whole labels, so `consul` matches `web.service.consul` but
+/// not `webconsul`. IP addresses never match.
+pub fn host_has_suffix(host: &str, suffix: &str) -> bool {
+    let host = host.trim_right_matches('.');
+    if host.parse::<IpAddr>().is_ok() {
+        return false;
+    }
+
+    host.len() >= suffix.len()
+        && host[host.len() - suffix.len()..].eq_ignore_ascii_case(suffix)
+        && (host.len() == suffix.len()
+            || host.as_bytes()[host.len() - suffix.len() - 1] == b'.')
+}
+
+// ==== impl Metadata =====
+
+impl Metadata {
+    /// Builds metadata from an endpoint's labels.
+    pub fn new<I>(labels: I) -> Self
+    where
+        I: IntoIterator<Item = (String, String)>,
+    {
+        Metadata(Arc::new(labels.into_iter().collect()))
+    }
+
+    /// Returns all of the endpoint's labels, including the well-known ones.
+    pub fn labels(&self) -> &BTreeMap<String, String> {
+        &self.0
+    }
+
+    /// The name of the endpoint's pod, if known.
+    pub fn pod(&self) -> Option<&str> {
+        self.label("pod")
+    }
+
+    /// The zone in which the endpoint runs, if known.
+    pub fn zone(&self) -> Option<&str> {
+        self.label("zone")
+    }
+
+    /// Returns true if the endpoint is known to run a proxy.
+    pub fn is_meshed(&self) -> bool {
+        self.label("meshed") == Some("true")
+    }
+
+    /// Returns true if the endpoint is known to accept HTTP/2.
+    pub fn is_h2(&self) -> bool {
+        self.label("h2") == Some("true")
+    }
+
+    fn label(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
 }
 
 // ==== impl Watch =====
 
+impl<B> Watch<B> {
+    /// Returns the load-balancing strategy most recently chosen by the
+    /// controller, if it has changed since this was last called.
+    pub fn take_strategy(&mut self) -> Option<BalanceStrategy> {
+        self.strategy.take()
+    }
+}
+
 impl<B> Discover for Watch<B>
 where
     B: Bind,
@@ -165,22 +332,27 @@ where
     type DiscoverError = ();
 
     fn poll(&mut self) -> Poll<Change<Self::Key, Self::Service>, Self::DiscoverError> {
-        let up = self.rx.poll();
-        trace!("watch: {:?}", up);
-        let update = match up {
-            Ok(Async::Ready(Some(update))) => update,
-            Ok(Async::Ready(None)) => unreachable!(),
-            Ok(Async::NotReady) => return Ok(Async::NotReady),
-            Err(_) => return Err(()),
-        };
+        loop {
+            let up = self.rx.poll();
+            trace!("watch: {:?}", up);
+            let update = match up {
+                Ok(Async::Ready(Some(update))) => update,
+                Ok(Async::Ready(None)) => unreachable!(),
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(_) => return Err(()),
+            };
 
-        match update {
-            Update::Insert(addr) => {
-                let service = self.bind.bind(&addr).map_err(|_| ())?;
+            match update {
+                Update::Insert(addr, metadata) => {
+                    let service = self.bind.bind(&addr, &metadata).map_err(|_| ())?;
 
-                Ok(Async::Ready(Change::Insert(addr, service)))
+                    return Ok(Async::Ready(Change::Insert(addr, service)));
+                }
+                Update::Remove(addr) => return Ok(Async::Ready(Change::Remove(addr))),
+                Update::Strategy(strategy) => {
+                    self.strategy = Some(strategy);
+                }
             }
-            Update::Remove(addr) => Ok(Async::Ready(Change::Remove(addr))),
         }
     }
 }
@@ -198,6 +370,17 @@ impl Background {
             reconnects: VecDeque::new(),
             rpc_ready: false,
             rx: self.rx,
+            schemes: self.schemes,
+        }
+    }
+
+    /// Bind this handle to resolve destinations without the controller.
+    pub fn work_local<R: Resolve>(self, resolve: R) -> LocalWork<R> {
+        LocalWork {
+            resolve,
+            destinations: HashMap::new(),
+            watches: HashMap::new(),
+            rx: self.rx,
         }
     }
 }
@@ -255,14 +438,17 @@ where
                             let set = occ.get_mut();
                             // we may already know of some addresses here, so push
                             // them onto the new watch first
-                            for &addr in &set.addrs {
-                                let _ = tx.unbounded_send(Update::Insert(addr));
+                            for (&addr, metadata) in &set.addrs {
+                                let _ = tx.unbounded_send(Update::Insert(addr, metadata.clone()));
+                            }
+                            if let Some(strategy) = set.strategy {
+                                let _ = tx.unbounded_send(Update::Strategy(strategy));
                             }
                             set.txs.push(tx);
                         }
                         Entry::Vacant(vac) => {
                             let req = Destination {
-                                scheme: "k8s".into(),
+                                scheme: self.schemes.scheme(vac.key()).into(),
                                 path: vac.key().without_trailing_dot()
                                         .as_str().into(),
                             };
@@ -272,7 +458,8 @@ where
                             let stream = UpdateRx::Waiting(response);
 
                             vac.insert(DestinationSet {
-                                addrs: HashSet::new(),
+                                addrs: HashMap::new(),
+                                strategy: None,
                                 needs_reconnect: false,
                                 rx: stream,
                                 txs: vec![tx],
@@ -298,7 +485,7 @@ where
             if let Some(set) = self.destinations.get_mut(&auth) {
                 trace!("Destination.Get reconnect {:?}", auth);
                 let req = Destination {
-                    scheme: "k8s".into(),
+                    scheme: self.schemes.scheme(&auth).into(),
                     path: auth.without_trailing_dot().as_str().into(),
                 };
                 let mut svc = DestinationSvc::new(client.lift_ref());
@@ -322,20 +509,31 @@ where
 
                 match set.rx.poll() {
                     Ok(Async::Ready(Some(update))) => match update.update {
-                        Some(PbUpdate2::Add(a_set)) => for addr in a_set.addrs {
-                            if let Some(addr) = addr.addr.and_then(pb_to_sock_addr) {
-                                if set.addrs.insert(addr) {
-                                    trace!("update {:?} for {:?}", addr, auth);
-                                    // retain is used to drop any senders that are dead
-                                    set.txs.retain(|tx| {
-                                        tx.unbounded_send(Update::Insert(addr)).is_ok()
-                                    });
+                        Some(PbUpdate2::Add(a_set)) => for w_addr in a_set.addrs {
+                            if let Some(addr) = w_addr.addr.and_then(pb_to_sock_addr) {
+                                let metadata = Metadata::new(w_addr.metadata);
+                                match set.addrs.insert(addr, metadata.clone()) {
+                                    Some(ref old) if *old == metadata => continue,
+                                    Some(_) => {
+                                        trace!("update metadata of {:?} for {:?}", addr, auth);
+                                        // the endpoint is removed first so that it is
+                                        // rebound with its new metadata.
+                                        set.txs.retain(|tx| {
+                                            tx.unbounded_send(Update::Remove(addr)).is_ok()
+                                        });
+                                    }
+                                    None => trace!("update {:?} for {:?}", addr, auth),
                                 }
+                                // retain is used to drop any senders that are dead
+                                set.txs.retain(|tx| {
+                                    let update = Update::Insert(addr, metadata.clone());
+                                    tx.unbounded_send(update).is_ok()
+                                });
                             }
                         },
                         Some(PbUpdate2::Remove(r_set)) => for addr in r_set.addrs {
                             if let Some(addr) = pb_to_sock_addr(addr) {
-                                if set.addrs.remove(&addr) {
+                                if set.addrs.remove(&addr).is_some() {
                                     trace!("remove {:?} for {:?}", addr, auth);
                                     // retain is used to drop any senders that are dead
                                     set.txs.retain(|tx| {
@@ -344,6 +542,16 @@ where
                                 }
                             }
                         },
+                        Some(PbUpdate2::Balancer(balancer)) => {
+                            let strategy = pb_to_strategy(balancer.strategy);
+                            if set.strategy != Some(strategy) {
+                                trace!("balance {:?} with {:?}", auth, strategy);
+                                set.strategy = Some(strategy);
+                                set.txs.retain(|tx| {
+                                    tx.unbounded_send(Update::Strategy(strategy)).is_ok()
+                                });
+                            }
+                        },
                         None => (),
                     },
                     Ok(Async::Ready(None)) => {
@@ -369,11 +577,102 @@ where
     }
 }
 
+// ==== impl LocalWork =====
+
+impl<R: Resolve> LocalWork<R> {
+    /// Notifies watches of the differences between the current destinations and
+    /// `destinations`.
+    fn update(&mut self, mut destinations: Destinations) {
+        let empty = HashSet::new();
+        for (auth, txs) in &mut self.watches {
+            let old = self.destinations.get(auth).unwrap_or(&empty);
+            let new = destinations.get(auth).unwrap_or(&empty);
+
+            let updates = new.difference(old)
+                .map(|&addr| Update::Insert(addr, Metadata::default()))
+                .chain(old.difference(new).map(|&addr| Update::Remove(addr)));
+            for update in updates {
+                trace!("update {:?} for {:?}", update, auth);
+                // retain is used to drop any senders that are dead
+                txs.retain(|tx| tx.unbounded_send(update.clone()).is_ok());
+            }
+        }
+
+        ::std::mem::swap(&mut self.destinations, &mut destinations);
+    }
+}
+
+impl<R: Resolve> Future for LocalWork<R> {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        while let Ok(Async::Ready(destinations)) = self.resolve.poll_destinations() {
+            self.update(destinations);
+        }
+
+        loop {
+            match self.rx.poll() {
+                Ok(Async::Ready(Some((auth, tx)))) => {
+                    match self.destinations.get(&auth) {
+                        Some(addrs) => for &addr in addrs {
+                            let _ = tx.unbounded_send(Update::Insert(addr, Metadata::default()));
+                        },
+                        None => debug!("no addresses known for {:?}", auth),
+                    }
+                    self.watches.entry(auth).or_insert_with(Vec::new).push(tx);
+                }
+                Ok(Async::Ready(None)) => {
+                    trace!("Discover tx is dropped, shutdown?");
+                    return Ok(Async::Ready(()));
+                }
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Err(_) => unreachable!("unbounded receiver doesn't error"),
+            }
+        }
+    }
+}
+
+/// Qualifies `authority` as the name of a destination, relative to
+/// `default_namespace`.
+///
+/// Returns `None`, with a warning, for names that are not resolved through
+/// discovery: requests for them are routed to their original destination.
+pub fn local_name(
+    authority: &http::uri::Authority,
+    default_namespace: &str,
+) -> Option<FullyQualifiedAuthority> {
+    match FullyQualifiedAuthority::normalize(authority, default_namespace) {
+        NamedAddress { name, use_destination_service: true } => Some(name),
+        NamedAddress { name, .. } => {
+            warn!("ignoring destination {:?}: not a local service", name);
+            None
+        }
+    }
+}
+
+// ==== impl StaticDestinations =====
+
+impl StaticDestinations {
+    pub fn new(destinations: Destinations) -> Self {
+        StaticDestinations(Some(destinations))
+    }
+}
+
+impl Resolve for StaticDestinations {
+    fn poll_destinations(&mut self) -> Poll<Destinations, ()> {
+        match self.0.take() {
+            Some(destinations) => Ok(Async::Ready(destinations)),
+            None => Ok(Async::NotReady),
+        }
+    }
+}
+
 // ===== impl Bind =====
 
 impl<F, S, E> Bind for F
 where
-    F: Fn(&SocketAddr) -> Result<S, E>,
+    F: Fn(&SocketAddr, &Metadata) -> Result<S, E>,
     S: Service,
 {
     type Request = S::Request;
@@ -382,8 +681,8 @@ where
     type Service = S;
     type BindError = E;
 
-    fn bind(&self, addr: &SocketAddr) -> Result<Self::Service, Self::BindError> {
-        (*self)(addr)
+    fn bind(&self, addr: &SocketAddr, metadata: &Metadata) -> Result<Self::Service, Self::BindError> {
+        (*self)(addr, metadata)
     }
 }
 
@@ -414,6 +713,17 @@ where T: HttpService<RequestBody = BoxBody, ResponseBody = RecvBody>,
 
 // ===== impl RxError =====
 
+fn pb_to_strategy(pb: i32) -> BalanceStrategy {
+    if pb == PbStrategy::RoundRobin as i32 {
+        BalanceStrategy::RoundRobin
+    } else if pb == PbStrategy::PeakEwma as i32 {
+        BalanceStrategy::PeakEwma
+    } else {
+        // Unknown strategies are left to the proxy, too.
+        BalanceStrategy::LeastRequests
+    }
+}
+
 fn pb_to_sock_addr(pb: TcpAddress) -> Option<SocketAddr> {
     use conduit_proxy_controller_grpc::common::ip_address::Ip;
     use std::net::{Ipv4Addr, Ipv6Addr};
@@ -465,3 +775,54 @@ fn pb_to_sock_addr(pb: TcpAddress) -> Option<SocketAddr> {
         None => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use http::uri::Authority;
+    use super::{Metadata, Schemes};
+
+    fn scheme(schemes: &Schemes, authority: &str) -> Option<String> {
+        let authority: Authority = authority.parse().unwrap();
+        schemes.configured(authority.host()).map(String::from)
+    }
+
+    #[test]
+    fn schemes_match_host_suffixes() {
+        let schemes = Schemes::new(vec![
+            ("service.consul".into(), "consul".into()),
+            ("consul".into(), "other".into()),
+            ("example.com".into(), "dns".into()),
+        ]);
+
+        assert_eq!(scheme(&schemes, "web.service.consul:8080"), Some("consul".into()));
+        assert_eq!(scheme(&schemes, "web.node.CONSUL"), Some("other".into()));
+        assert_eq!(scheme(&schemes, "example.com."), Some("dns".into()));
+        assert_eq!(scheme(&schemes, "api.example.com"), Some("dns".into()));
+
+        assert_eq!(scheme(&schemes, "webconsul"), None);
+        assert_eq!(scheme(&schemes, "badexample.com"), None);
+        assert_eq!(scheme(&schemes, "web.ns.svc.cluster.local"), None);
+        assert_eq!(scheme(&schemes, "10.1.1.1:80"), None);
+    }
+
+    #[test]
+    fn metadata_reads_well_known_labels() {
+        let metadata = Metadata::new(vec![
+            ("pod".into(), "web-1".into()),
+            ("zone".into(), "us-west-1a".into()),
+            ("meshed".into(), "true".into()),
+            ("h2".into(), "false".into()),
+            ("team".into(), "a".into()),
+        ]);
+        assert_eq!(metadata.pod(), Some("web-1"));
+        assert_eq!(metadata.zone(), Some("us-west-1a"));
+        assert!(metadata.is_meshed());
+        assert!(!metadata.is_h2());
+        assert_eq!(metadata.labels().get("team").map(String::as_str), Some("a"));
+
+        let unknown = Metadata::default();
+        assert_eq!(unknown.pod(), None);
+        assert!(!unknown.is_meshed());
+        assert!(!unknown.is_h2());
+    }
+}
@@ -1,5 +1,7 @@
 use std::fmt;
 use std::io;
+use std::net::SocketAddr;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
 use bytes::Bytes;
@@ -16,22 +18,28 @@ use tower::Service;
 use tower_h2;
 use tower_reconnect::{Error as ReconnectError, Reconnect};
 
+use config::ReportSink;
 use dns;
 use fully_qualified_authority::FullyQualifiedAuthority;
 use transport::{HostAndPort, LookupAddressAndConnect};
 use timeout::{Timeout, TimeoutError};
 
+mod destinations_file;
 pub mod discovery;
 mod observe;
 pub mod pb;
+mod report_writer;
 mod telemetry;
 
-use self::discovery::{Background as DiscoBg, Discovery, Watch};
-pub use self::discovery::Bind;
+use self::destinations_file::DestinationsFile;
+use self::discovery::{Background as DiscoBg, Destinations, Discovery, StaticDestinations, Watch};
+pub use self::discovery::{Bind, Schemes};
 pub use self::observe::Observe;
 use conduit_proxy_controller_grpc::telemetry::ReportRequest;
+use self::report_writer::ReportWriter;
 use self::telemetry::Telemetry;
 
+#[derive(Clone)]
 pub struct Control {
     disco: Discovery,
 }
@@ -40,8 +48,8 @@ pub struct Background {
     disco: DiscoBg,
 }
 
-pub fn new() -> (Control, Background) {
-    let (tx, rx) = self::discovery::new();
+pub fn new(schemes: Schemes) -> (Control, Background) {
+    let (tx, rx) = self::discovery::new(schemes);
 
     let c = Control {
         disco: tx,
@@ -60,6 +68,16 @@ impl Control {
     pub fn resolve<B>(&self, auth: &FullyQualifiedAuthority, bind: B) -> Watch<B> {
         self.disco.resolve(auth, bind)
     }
+
+    /// Returns the name with which `authority` is resolved, or `None` if it
+    /// should be routed to its original destination.
+    pub fn resolvable_name(
+        &self,
+        authority: &http::uri::Authority,
+        default_namespace: &str,
+    ) -> Option<FullyQualifiedAuthority> {
+        self.disco.resolvable_name(authority, default_namespace)
+    }
 }
 
 // ===== impl Background =====
@@ -114,6 +132,50 @@ impl Background {
 
         Box::new(fut)
     }
+
+    /// Runs without a controller.
+    ///
+    /// Reports are written to `sink` rather than sent to the controller.
+    /// Destinations are resolved from the file at `destinations_path`, if one is
+    /// configured, and from `static_destinations` otherwise. Both are qualified
+    /// relative to `default_namespace`.
+    pub fn standalone<S>(
+        self,
+        events: S,
+        sink: &ReportSink,
+        destinations_path: Option<&Path>,
+        static_destinations: &[(http::uri::Authority, Vec<SocketAddr>)],
+        default_namespace: &str,
+        executor: &Handle,
+    ) -> io::Result<Box<Future<Item = (), Error = ()>>>
+    where
+        S: Stream<Item = ReportRequest, Error = ()> + 'static,
+    {
+        let reports = ReportWriter::new(events, sink)?;
+
+        let disco: Box<Future<Item = (), Error = ()>> = match destinations_path {
+            Some(path) => {
+                let file = DestinationsFile::new(
+                    path.to_owned(),
+                    default_namespace.to_owned(),
+                    executor,
+                )?;
+                Box::new(self.disco.work_local(file))
+            }
+            None => {
+                let mut destinations = Destinations::new();
+                for &(ref authority, ref addrs) in static_destinations {
+                    if let Some(name) = discovery::local_name(authority, default_namespace) {
+                        destinations.insert(name, addrs.iter().cloned().collect());
+                    }
+                }
+                Box::new(self.disco.work_local(StaticDestinations::new(destinations)))
+            }
+        };
+
+        let fut = reports.join(disco).map(|_| {});
+        Ok(Box::new(fut))
+    }
 }
 
 // ===== Backoff =====
@@ -126,6 +126,11 @@ impl<'a> TryFrom<&'a Event> for common::TapEvent {
                         .unwrap_or_default()
                         .into(),
                     path: ctx.uri.path().into(),
+                    request_id: ctx.request_id.clone().unwrap_or_default(),
+                    route: ctx.route
+                        .as_ref()
+                        .map(|r| r.to_string())
+                        .unwrap_or_default(),
                 };
 
                 common::TapEvent {
@@ -0,0 +1,414 @@
+//! Writes telemetry reports as newline-delimited JSON, for proxies that run
+//! without a controller.
+//!
+//! Each report is written as a single line, with the fields of `ReportRequest`
+//! named as in `telemetry.proto`. Addresses are written as strings and enums by
+//! name.
+
+use std::fmt::Write as FmtWrite;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use futures::{Async, Future, Poll, Stream};
+
+use conduit_proxy_controller_grpc::common::{ip_address, IpAddress, Protocol, TcpAddress};
+use conduit_proxy_controller_grpc::telemetry::{
+    eos_ctx,
+    report_request,
+    EosScope,
+    ReportRequest,
+    RequestScope,
+    ResponseScope,
+};
+use config::ReportSink;
+use json::{encode_nums, encode_str};
+
+/// Drains a stream of reports, writing each to `out`.
+pub struct ReportWriter<T> {
+    reports: T,
+    out: Box<Write>,
+}
+
+impl<T> ReportWriter<T>
+where
+    T: Stream<Item = ReportRequest>,
+    T::Error: ::std::fmt::Debug,
+{
+    pub fn new(reports: T, sink: &ReportSink) -> io::Result<Self> {
+        let out: Box<Write> = match *sink {
+            ReportSink::Stdout => Box::new(io::stdout()),
+            ReportSink::File(ref path) => {
+                Box::new(OpenOptions::new().create(true).append(true).open(path)?)
+            }
+        };
+
+        Ok(ReportWriter {
+            reports,
+            out,
+        })
+    }
+}
+
+impl<T> Future for ReportWriter<T>
+where
+    T: Stream<Item = ReportRequest>,
+    T::Error: ::std::fmt::Debug,
+{
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        loop {
+            match self.reports.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(None)) => {
+                    debug!("report stream complete");
+                    return Ok(Async::Ready(()));
+                }
+                Err(err) => {
+                    warn!("report stream error: {:?}", err);
+                }
+                Ok(Async::Ready(Some(report))) => {
+                    let mut line = encode(&report);
+                    line.push('\n');
+                    let written = self.out
+                        .write_all(line.as_bytes())
+                        .and_then(|_| self.out.flush());
+                    match written {
+                        Ok(()) => trace!(
+                            "report written; requests={} accepts={} connects={}",
+                            report.requests.len(),
+                            report.server_transports.len(),
+                            report.client_transports.len(),
+                        ),
+                        Err(e) => warn!("failed to write report: {}", e),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Encodes a report as a single line of JSON.
+fn encode(report: &ReportRequest) -> String {
+    let mut out = String::with_capacity(1024);
+
+    out.push_str("{\"process\":");
+    match report.process {
+        Some(ref p) => {
+            out.push_str("{\"node\":");
+            encode_str(&mut out, &p.node);
+            out.push_str(",\"scheduled_instance\":");
+            encode_str(&mut out, &p.scheduled_instance);
+            out.push_str(",\"scheduled_namespace\":");
+            encode_str(&mut out, &p.scheduled_namespace);
+            out.push('}');
+        }
+        None => out.push_str("null"),
+    }
+
+    let proxy = if report.proxy == report_request::Proxy::Inbound as i32 {
+        "INBOUND"
+    } else if report.proxy == report_request::Proxy::Outbound as i32 {
+        "OUTBOUND"
+    } else {
+        "UNKNOWN"
+    };
+    let _ = write!(out, ",\"proxy\":\"{}\"", proxy);
+
+    out.push_str(",\"server_transports\":[");
+    for (i, t) in report.server_transports.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"source_ip\":");
+        encode_ip(&mut out, t.source_ip.as_ref());
+        let _ = write!(
+            out,
+            ",\"connects\":{},\"protocol\":\"{}\",\"disconnect_duration_counts\":",
+            t.connects,
+            protocol(t.protocol),
+        );
+        encode_nums(&mut out, &t.disconnect_duration_counts);
+        let _ = write!(
+            out,
+            ",\"rate_limited\":{},\"authz_allowed\":{},\"authz_denied\":{}",
+            t.rate_limited,
+            t.authz_allowed,
+            t.authz_denied,
+        );
+        out.push('}');
+    }
+
+    out.push_str("],\"client_transports\":[");
+    for (i, t) in report.client_transports.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        out.push_str("{\"target_addr\":");
+        encode_addr(&mut out, t.target_addr.as_ref());
+        let _ = write!(
+            out,
+            ",\"connects\":{},\"protocol\":\"{}\",\"disconnect_duration_counts\":",
+            t.connects,
+            protocol(t.protocol),
+        );
+        encode_nums(&mut out, &t.disconnect_duration_counts);
+        out.push_str(",\"tls_server_name\":");
+        encode_str(&mut out, &t.tls_server_name);
+        out.push_str(",\"upgrade\":");
+        encode_str(&mut out, &t.upgrade);
+        out.push('}');
+    }
+
+    out.push_str("],\"requests\":[");
+    for (i, r) in report.requests.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        encode_request(&mut out, r);
+    }
+    out.push(']');
+
+    out.push_str(",\"histogram_bucket_bounds_tenth_ms\":");
+    encode_nums(&mut out, &report.histogram_bucket_bounds_tenth_ms);
+    out.push_str(",\"histogram_bucket_bounds_us\":");
+    encode_nums(&mut out, &report.histogram_bucket_bounds_us);
+    out.push_str(",\"connection_duration_bucket_bounds_ms\":");
+    encode_nums(&mut out, &report.connection_duration_bucket_bounds_ms);
+
+    out.push_str(",\"overflow\":");
+    match report.overflow {
+        Some(ref o) => {
+            let _ = write!(
+                out,
+                "{{\"server_transports\":{},\"client_transports\":{},\"requests\":{}}}",
+                o.server_transports,
+                o.client_transports,
+                o.requests,
+            );
+        }
+        None => out.push_str("null"),
+    }
+
+    let _ = write!(out, ",\"dropped_events\":{}}}", report.dropped_events);
+    out
+}
+
+fn encode_request(out: &mut String, r: &RequestScope) {
+    out.push_str("{\"ctx\":");
+    match r.ctx {
+        Some(ref ctx) => {
+            out.push_str("{\"source_ip\":");
+            encode_ip(out, ctx.source_ip.as_ref());
+            out.push_str(",\"target_addr\":");
+            encode_addr(out, ctx.target_addr.as_ref());
+            out.push_str(",\"authority\":");
+            encode_str(out, &ctx.authority);
+            out.push_str(",\"route\":");
+            encode_str(out, &ctx.route);
+            out.push_str(",\"destination_name\":");
+            encode_str(out, &ctx.destination_name);
+            let _ = write!(out, ",\"mirrored\":{}}}", ctx.mirrored);
+        }
+        None => out.push_str("null"),
+    }
+
+    let _ = write!(out, ",\"count\":{},\"responses\":[", r.count);
+    for (i, res) in r.responses.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        encode_response(out, res);
+    }
+    out.push_str("]}");
+}
+
+fn encode_response(out: &mut String, res: &ResponseScope) {
+    out.push_str("{\"ctx\":");
+    match res.ctx {
+        Some(ref ctx) => {
+            let _ = write!(out, "{{\"http_status_code\":{}}}", ctx.http_status_code);
+        }
+        None => out.push_str("null"),
+    }
+
+    out.push_str(",\"response_latency_counts\":");
+    encode_nums(out, &res.response_latency_counts);
+    out.push_str(",\"stream_duration_counts\":");
+    encode_nums(out, &res.stream_duration_counts);
+    let _ = write!(
+        out,
+        ",\"bytes_sent\":{},\"frames_sent\":{},\
+         \"request_bytes_sent\":{},\"request_frames_sent\":{},\"ends\":[",
+        res.bytes_sent,
+        res.frames_sent,
+        res.request_bytes_sent,
+        res.request_frames_sent,
+    );
+    for (i, end) in res.ends.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        encode_end(out, end);
+    }
+    out.push_str("]}");
+}
+
+fn encode_end(out: &mut String, end: &EosScope) {
+    out.push_str("{\"ctx\":");
+    let _ = match end.ctx.as_ref().and_then(|ctx| ctx.end.as_ref()) {
+        Some(&eos_ctx::End::GrpcStatusCode(code)) => {
+            write!(out, "{{\"grpc_status_code\":{}}}", code)
+        }
+        Some(&eos_ctx::End::ResetErrorCode(code)) => {
+            write!(out, "{{\"reset_error_code\":{}}}", code)
+        }
+        Some(&eos_ctx::End::Other(other)) => write!(out, "{{\"other\":{}}}", other),
+        None => write!(out, "null"),
+    };
+    let _ = write!(out, ",\"streams\":{}}}", end.streams);
+}
+
+fn encode_ip(out: &mut String, ip: Option<&IpAddress>) {
+    match ip.and_then(to_ip_addr) {
+        Some(ip) => {
+            let _ = write!(out, "\"{}\"", ip);
+        }
+        None => out.push_str("null"),
+    }
+}
+
+fn encode_addr(out: &mut String, addr: Option<&TcpAddress>) {
+    let addr = addr.and_then(|a| {
+        a.ip.as_ref()
+            .and_then(to_ip_addr)
+            .map(|ip| SocketAddr::new(ip, a.port as u16))
+    });
+    match addr {
+        Some(addr) => {
+            let _ = write!(out, "\"{}\"", addr);
+        }
+        None => out.push_str("null"),
+    }
+}
+
+fn to_ip_addr(ip: &IpAddress) -> Option<IpAddr> {
+    match ip.ip {
+        Some(ip_address::Ip::Ipv4(octets)) => Some(IpAddr::V4(Ipv4Addr::from(octets))),
+        Some(ip_address::Ip::Ipv6(ref v6)) => Some(IpAddr::V6(Ipv6Addr::from(v6))),
+        None => None,
+    }
+}
+
+fn protocol(p: i32) -> &'static str {
+    if p == Protocol::Http as i32 {
+        "HTTP"
+    } else if p == Protocol::Tcp as i32 {
+        "TCP"
+    } else {
+        "UNKNOWN"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use conduit_proxy_controller_grpc::common::TcpAddress;
+    use conduit_proxy_controller_grpc::telemetry::*;
+
+    use super::encode;
+
+    #[test]
+    fn encodes_reports_as_a_json_line() {
+        let addr: SocketAddr = ([10, 1, 1, 1], 8080).into();
+        let report = ReportRequest {
+            process: Some(Process {
+                node: "node".into(),
+                scheduled_instance: "web-1".into(),
+                scheduled_namespace: "default".into(),
+            }),
+            proxy: 0,
+            server_transports: vec![ServerTransport {
+                source_ip: Some([10, 1, 1, 2].into()),
+                connects: 1,
+                disconnects: vec![],
+                protocol: 0,
+                disconnect_duration_counts: vec![1, 0],
+                rate_limited: 3,
+                authz_allowed: 4,
+                authz_denied: 5,
+            }],
+            client_transports: vec![ClientTransport {
+                target_addr: Some(TcpAddress::from(&addr)),
+                connects: 2,
+                disconnects: vec![],
+                protocol: 1,
+                disconnect_duration_counts: vec![0, 2],
+                tls_server_name: "web.example.com".into(),
+                upgrade: "websocket".into(),
+            }],
+            requests: vec![RequestScope {
+                ctx: Some(RequestCtx {
+                    source_ip: Some([10, 1, 1, 2].into()),
+                    target_addr: Some(TcpAddress::from(&addr)),
+                    authority: "web".into(),
+                    route: "get-\"user\"".into(),
+                    destination_name: "web-v2.default.svc.cluster.local".into(),
+                    mirrored: false,
+                }),
+                count: 1,
+                responses: vec![ResponseScope {
+                    ctx: Some(ResponseCtx { http_status_code: 200 }),
+                    response_latency_counts: vec![1, 0],
+                    ends: vec![EosScope {
+                        ctx: Some(EosCtx {
+                            end: Some(eos_ctx::End::GrpcStatusCode(0)),
+                        }),
+                        streams: 1,
+                    }],
+                    stream_duration_counts: vec![0, 1],
+                    bytes_sent: 5,
+                    frames_sent: 1,
+                    request_bytes_sent: 3,
+                    request_frames_sent: 1,
+                }],
+            }],
+            histogram_bucket_bounds_tenth_ms: vec![10, u32::max_value()],
+            histogram_bucket_bounds_us: vec![1000, u64::max_value()],
+            connection_duration_bucket_bounds_ms: vec![10],
+            overflow: None,
+            dropped_events: 2,
+        };
+
+        let json = encode(&report);
+        assert!(!json.contains('\n'));
+        assert_eq!(
+            json,
+            "{\"process\":{\"node\":\"node\",\"scheduled_instance\":\"web-1\",\
+             \"scheduled_namespace\":\"default\"},\"proxy\":\"INBOUND\",\
+             \"server_transports\":[{\"source_ip\":\"10.1.1.2\",\"connects\":1,\
+             \"protocol\":\"HTTP\",\"disconnect_duration_counts\":[1,0],\
+             \"rate_limited\":3,\"authz_allowed\":4,\"authz_denied\":5}],\
+             \"client_transports\":[{\"target_addr\":\"10.1.1.1:8080\",\"connects\":2,\
+             \"protocol\":\"TCP\",\"disconnect_duration_counts\":[0,2],\
+             \"tls_server_name\":\"web.example.com\",\"upgrade\":\"websocket\"}],\
+             \"requests\":[{\"ctx\":{\"source_ip\":\"10.1.1.2\",\
+             \"target_addr\":\"10.1.1.1:8080\",\"authority\":\"web\",\
+             \"route\":\"get-\\\"user\\\"\",\
+             \"destination_name\":\"web-v2.default.svc.cluster.local\",\
+             \"mirrored\":false},\"count\":1,\
+             \"responses\":[{\"ctx\":{\"http_status_code\":200},\
+             \"response_latency_counts\":[1,0],\"stream_duration_counts\":[0,1],\
+             \"bytes_sent\":5,\"frames_sent\":1,\
+             \"request_bytes_sent\":3,\"request_frames_sent\":1,\
+             \"ends\":[{\"ctx\":{\"grpc_status_code\":0},\"streams\":1}]}]}],\
+             \"histogram_bucket_bounds_tenth_ms\":[10,4294967295],\
+             \"histogram_bucket_bounds_us\":[1000,18446744073709551615],\
+             \"connection_duration_bucket_bounds_ms\":[10],\
+             \"overflow\":null,\"dropped_events\":2}"
+        );
+    }
+}
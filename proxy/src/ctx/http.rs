@@ -2,6 +2,9 @@ use http;
 use std::sync::Arc;
 
 use ctx;
+use mirror;
+use request_id;
+use telemetry::tracing::SpanContext;
 
 /// Describes a stream's request headers.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -17,6 +20,18 @@ pub struct Request {
 
     /// Identifies the proxy client that dispatched the request.
     pub client: Arc<ctx::transport::Client>,
+
+    /// Identifies the proxy's span for this request, if it is part of a trace.
+    pub trace: Option<SpanContext>,
+
+    /// The request's `x-request-id`, if it has one.
+    pub request_id: Option<String>,
+
+    /// The name of the route that the request matched, if any.
+    pub route: Option<Arc<str>>,
+
+    /// Whether the request is a mirrored copy, whose response is ignored.
+    pub mirrored: bool,
 }
 
 /// Describes a stream's response headers.
@@ -40,6 +55,8 @@ impl Request {
         server: &Arc<ctx::transport::Server>,
         client: &Arc<ctx::transport::Client>,
         id: usize,
+        trace: Option<SpanContext>,
+        route: Option<Arc<str>>,
     ) -> Arc<Self> {
         let r = Self {
             id,
@@ -47,6 +64,12 @@ impl Request {
             method: request.method().clone(),
             server: Arc::clone(server),
             client: Arc::clone(client),
+            trace,
+            request_id: request_id::get(request)
+                .and_then(|id| id.to_str().ok())
+                .map(String::from),
+            route,
+            mirrored: mirror::is_mirrored(request),
         };
 
         Arc::new(r)
@@ -3,7 +3,9 @@ use std::sync::Arc;
 
 use conduit_proxy_controller_grpc::common::Protocol;
 
+use control::discovery::Metadata;
 use ctx;
+use fully_qualified_authority::FullyQualifiedAuthority;
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Ctx {
@@ -26,6 +28,16 @@ pub struct Server {
 pub struct Client {
     pub proxy: Arc<ctx::Proxy>,
     pub remote: SocketAddr,
+    /// Describes the remote endpoint, if it was resolved by the controller.
+    pub metadata: Metadata,
+    /// The name that the remote endpoint was resolved from, if any.
+    pub destination_name: Option<FullyQualifiedAuthority>,
+    /// The server name indicated by the TLS client, if the connection was
+    /// proxied as TLS.
+    pub tls_server_name: Option<String>,
+    /// The protocol that the connection was upgraded to from HTTP/1, if it
+    /// was proxied after an upgrade.
+    pub upgrade: Option<String>,
     pub protocol: Protocol,
 }
 
@@ -85,11 +97,19 @@ impl Client {
     pub fn new(
         proxy: &Arc<ctx::Proxy>,
         remote: &SocketAddr,
+        metadata: &Metadata,
+        destination_name: &Option<FullyQualifiedAuthority>,
+        tls_server_name: &Option<String>,
+        upgrade: &Option<String>,
         protocol: Protocol,
     ) -> Arc<Client> {
         let c = Client {
             proxy: Arc::clone(proxy),
             remote: *remote,
+            metadata: metadata.clone(),
+            destination_name: destination_name.clone(),
+            tls_server_name: tls_server_name.clone(),
+            upgrade: upgrade.clone(),
             protocol: protocol,
         };
 
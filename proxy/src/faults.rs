@@ -0,0 +1,393 @@
+//! Injects delays and aborts into requests, to test how applications handle
+//! failures.
+//!
+//! Faults are read from a file with one fault per line. Each has one or more
+//! conditions, as in routing rules, and one or more faults, separated by `->`:
+//!
+//! ```text
+//! # conditions                        faults
+//! authority=web path=/slow         -> delay-ms=500 percent=10
+//! header:x-chaos=abort             -> abort=503
+//! path=/helloworld.Greeter         -> abort=grpc:14 percent=50
+//! authority=db                     -> delay-ms=100 abort=reset
+//! ```
+//!
+//! Faults are:
+//!
+//! - `delay-ms=<ms>`: the request is held for the number of milliseconds before
+//!   it is sent (or aborted).
+//! - `abort=<status>`: the request is not sent, and is answered with the HTTP
+//!   status.
+//! - `abort=grpc:<code>`: the request is not sent, and is answered with the
+//!   gRPC status code.
+//! - `abort=reset`: the request is not sent, and its stream is reset (or, for
+//!   HTTP/1, its connection is closed).
+//! - `percent=<percent>`: the faults are injected into only this percentage of
+//!   the matching requests. By default, they are injected into all of them.
+//!
+//! Only the first line whose conditions match a request is used.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{Async, Future, Poll};
+use h2;
+use http;
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use rand;
+use tokio_core::reactor::{Handle, Timeout as ReactorTimeout};
+use tower::Service;
+
+use rules::Conditions;
+
+/// An ordered set of faults.
+#[derive(Clone, Debug, Default)]
+pub struct Faults(Vec<Fault>);
+
+#[derive(Clone, Debug)]
+struct Fault {
+    conditions: Conditions,
+    delay: Option<Duration>,
+    abort: Option<Abort>,
+    percent: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Abort {
+    Status(http::StatusCode),
+    Grpc(u32),
+    Reset,
+}
+
+/// Injects the first matching fault into each request.
+#[derive(Debug)]
+pub struct InjectFaults<S> {
+    /// Shared with the futures of delayed requests, which call it once their
+    /// delay has elapsed.
+    inner: Rc<RefCell<S>>,
+    faults: Arc<Faults>,
+    executor: Handle,
+}
+
+pub struct ResponseFuture<S: Service> {
+    delay: Option<ReactorTimeout>,
+    state: State<S>,
+}
+
+enum State<S: Service> {
+    /// The request is held until the delay has elapsed.
+    Delayed(Rc<RefCell<S>>, Option<S::Request>),
+    Inner(S::Future),
+    Abort(Abort),
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The line (1-indexed) does not have conditions and faults separated by `->`.
+    InvalidLine(usize),
+    InvalidCondition(usize),
+    /// The fault is unknown, or the line has neither a delay nor an abort.
+    InvalidFault(usize),
+}
+
+// ===== impl Faults =====
+
+impl Faults {
+    /// Reads faults from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut s = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut s))
+            .map_err(Error::Io)?;
+        s.parse()
+    }
+
+    /// Returns the first fault that matches the request, if it is chosen to be
+    /// injected.
+    fn choose<B>(&self, req: &http::Request<B>) -> Option<&Fault> {
+        self.0
+            .iter()
+            .find(|f| f.conditions.matches(req))
+            .and_then(|f| {
+                if rand::random::<f64>() * 100.0 < f.percent {
+                    Some(f)
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+impl FromStr for Faults {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut faults = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let lineno = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut sides = line.splitn(2, "->");
+            let (conditions, actions) = match (sides.next(), sides.next()) {
+                (Some(c), Some(a)) if !c.trim().is_empty() && !a.trim().is_empty() => (c, a),
+                _ => return Err(Error::InvalidLine(lineno)),
+            };
+
+            let mut fault = Fault {
+                conditions: Conditions::default(),
+                delay: None,
+                abort: None,
+                percent: 100.0,
+            };
+            for condition in conditions.split_whitespace() {
+                fault.conditions.parse(condition).ok_or(Error::InvalidCondition(lineno))?;
+            }
+            for action in actions.split_whitespace() {
+                fault.parse(action).ok_or(Error::InvalidFault(lineno))?;
+            }
+            if fault.delay.is_none() && fault.abort.is_none() {
+                return Err(Error::InvalidFault(lineno));
+            }
+
+            faults.push(fault);
+        }
+
+        Ok(Faults(faults))
+    }
+}
+
+// ===== impl Fault =====
+
+impl Fault {
+    fn parse(&mut self, s: &str) -> Option<()> {
+        let mut kv = s.splitn(2, '=');
+        let (key, value) = (kv.next()?, kv.next()?);
+        match key {
+            "delay-ms" => self.delay = Some(Duration::from_millis(value.parse().ok()?)),
+            "abort" => {
+                let abort = if value == "reset" {
+                    Abort::Reset
+                } else if value.starts_with("grpc:") {
+                    Abort::Grpc(value["grpc:".len()..].parse().ok()?)
+                } else {
+                    let code = value.parse::<u16>().ok()?;
+                    Abort::Status(http::StatusCode::from_u16(code).ok()?)
+                };
+                self.abort = Some(abort);
+            }
+            "percent" => {
+                let percent = value.parse::<f64>().ok()?;
+                if !(percent >= 0.0 && percent <= 100.0) {
+                    return None;
+                }
+                self.percent = percent;
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+// ===== impl InjectFaults =====
+
+impl<S> InjectFaults<S> {
+    pub fn new(inner: S, faults: Arc<Faults>, executor: Handle) -> Self {
+        InjectFaults {
+            inner: Rc::new(RefCell::new(inner)),
+            faults,
+            executor,
+        }
+    }
+}
+
+impl<S, A, B> Service for InjectFaults<S>
+where
+    S: Service<Request = http::Request<A>, Response = http::Response<B>, Error = h2::Error>,
+    B: Default,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.borrow_mut().poll_ready()
+    }
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        let (delay, abort) = match self.faults.choose(&request) {
+            Some(fault) => {
+                debug!("injecting {:?} into {} {}", fault, request.method(), request.uri());
+                (fault.delay, fault.abort.clone())
+            }
+            None => (None, None),
+        };
+
+        let delay = delay.and_then(|d| match ReactorTimeout::new(d, &self.executor) {
+            Ok(timeout) => Some(timeout),
+            Err(e) => {
+                warn!("failed to inject delay: {}", e);
+                None
+            }
+        });
+        let state = match abort {
+            Some(abort) => State::Abort(abort),
+            None if delay.is_some() => State::Delayed(self.inner.clone(), Some(request)),
+            None => State::Inner(self.inner.borrow_mut().call(request)),
+        };
+
+        ResponseFuture { delay, state }
+    }
+}
+
+// ===== impl ResponseFuture =====
+
+impl<S, A, B> Future for ResponseFuture<S>
+where
+    S: Service<Request = http::Request<A>, Response = http::Response<B>, Error = h2::Error>,
+    B: Default,
+{
+    type Item = S::Response;
+    type Error = S::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let delayed = match self.delay {
+            Some(ref mut delay) => match delay.poll() {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(())) => true,
+                Err(e) => {
+                    warn!("injected delay failed: {}", e);
+                    true
+                }
+            },
+            None => false,
+        };
+        if delayed {
+            self.delay = None;
+        }
+
+        loop {
+            let sent = match self.state {
+                State::Delayed(ref inner, ref mut request) => {
+                    let mut inner = inner.borrow_mut();
+                    try_ready!(inner.poll_ready());
+                    let request = request.take().expect("delayed request is sent once");
+                    inner.call(request)
+                }
+                State::Inner(ref mut f) => return f.poll(),
+                State::Abort(ref abort) => return abort.respond().map(Async::Ready),
+            };
+            self.state = State::Inner(sent);
+        }
+    }
+}
+
+// ===== impl Abort =====
+
+impl Abort {
+    fn respond<B: Default>(&self) -> Result<http::Response<B>, h2::Error> {
+        let mut response = http::Response::builder();
+        response.header(CONTENT_LENGTH, "0");
+        match *self {
+            Abort::Status(status) => {
+                response.status(status);
+            }
+            Abort::Grpc(code) => {
+                response
+                    .status(http::StatusCode::OK)
+                    .header(CONTENT_TYPE, "application/grpc")
+                    .header("grpc-status", code.to_string().as_str());
+            }
+            Abort::Reset => return Err(h2::Reason::INTERNAL_ERROR.into()),
+        }
+        Ok(response.body(B::default()).expect("abort response is valid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use http;
+
+    use super::*;
+
+    #[test]
+    fn parses_faults() {
+        let faults: Faults = "
+            # Comments and blank lines are ignored.
+            authority=web path=/slow  -> delay-ms=500 percent=10
+            header:x-chaos=abort      -> abort=503
+            path=/helloworld.Greeter  -> abort=grpc:14 percent=50
+            authority=db              -> delay-ms=100 abort=reset
+        ".parse().unwrap();
+
+        assert_eq!(faults.0[0].delay, Some(Duration::from_millis(500)));
+        assert_eq!(faults.0[0].abort, None);
+        assert_eq!(faults.0[0].percent, 10.0);
+        assert_eq!(faults.0[1].abort, Some(Abort::Status(http::StatusCode::SERVICE_UNAVAILABLE)));
+        assert_eq!(faults.0[1].percent, 100.0);
+        assert_eq!(faults.0[2].abort, Some(Abort::Grpc(14)));
+        assert_eq!(faults.0[3].delay, Some(Duration::from_millis(100)));
+        assert_eq!(faults.0[3].abort, Some(Abort::Reset));
+    }
+
+    #[test]
+    fn chooses_first_matching_fault() {
+        let faults: Faults = "
+            authority=web path=/slow  -> delay-ms=500
+            header:x-chaos=abort      -> abort=503
+            authority=db              -> abort=reset percent=0
+        ".parse().unwrap();
+
+        let choose = |req: &mut http::request::Builder| {
+            let req = req.body(()).unwrap();
+            faults.choose(&req).and_then(|f| f.delay.or(Some(Duration::from_secs(0))))
+        };
+
+        let slow = choose(http::Request::get("/slow/a").header("host", "WEB:8080"));
+        assert_eq!(slow, Some(Duration::from_millis(500)));
+        assert_eq!(choose(&mut http::Request::get("http://web/slowly")), None);
+        assert_eq!(choose(&mut http::Request::get("http://api/slow")), None);
+
+        let abort = choose(http::Request::get("http://api/").header("x-chaos", "abort"));
+        assert_eq!(abort, Some(Duration::from_secs(0)));
+
+        assert_eq!(choose(&mut http::Request::get("http://db/")), None);
+    }
+
+    #[test]
+    fn rejects_invalid_faults() {
+        match "path=/a -> percent=10".parse::<Faults>() {
+            Err(Error::InvalidFault(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "\npath=/a -> abort=600".parse::<Faults>() {
+            Err(Error::InvalidFault(2)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "path=/a -> abort=503 percent=101".parse::<Faults>() {
+            Err(Error::InvalidFault(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "path=a -> abort=503".parse::<Faults>() {
+            Err(Error::InvalidCondition(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "abort=503".parse::<Faults>() {
+            Err(Error::InvalidLine(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+    }
+}
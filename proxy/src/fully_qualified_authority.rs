@@ -160,6 +160,12 @@ impl FullyQualifiedAuthority {
         }
     }
 
+    /// Uses `authority` as it is, for names that are not qualified according to
+    /// Kubernetes naming conventions.
+    pub fn verbatim(authority: &Authority) -> Self {
+        FullyQualifiedAuthority(authority.clone())
+    }
+
     pub fn without_trailing_dot(&self) -> &Authority {
         &self.0
     }
@@ -9,11 +9,15 @@ use tower_h2;
 use conduit_proxy_router::{Reuse, Recognize};
 
 use bind;
+use control::discovery::Metadata;
 use ctx;
 
 type Bind<B> = bind::Bind<Arc<ctx::Proxy>, B>;
 
-pub struct Inbound<B> {
+pub struct Inbound<B>
+where
+    B: tower_h2::Body + 'static,
+{
     default_addr: Option<SocketAddr>,
     bind: Bind<B>,
 }
@@ -22,7 +26,10 @@ const MAX_IN_FLIGHT: usize = 10_000;
 
 // ===== impl Inbound =====
 
-impl<B> Inbound<B> {
+impl<B> Inbound<B>
+where
+    B: tower_h2::Body + 'static,
+{
     pub fn new(default_addr: Option<SocketAddr>, bind: Bind<B>) -> Self {
         Self {
             default_addr,
@@ -74,7 +81,9 @@ where
         let &(ref addr, ref proto) = key;
         debug!("building inbound {:?} client to {}", proto, addr);
 
-        Buffer::new(self.bind.bind_service(addr, proto), self.bind.executor())
+        // The local application is not described by the Destination API.
+        let svc = self.bind.bind_service(addr, &Metadata::default(), &None, proto);
+        Buffer::new(svc, self.bind.executor())
             .map(|buffer| {
                 InFlightLimit::new(buffer, MAX_IN_FLIGHT)
             })
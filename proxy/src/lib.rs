@@ -47,7 +47,7 @@ use futures::*;
 use std::error::Error;
 use std::io;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 
@@ -57,17 +57,27 @@ use tower_fn::*;
 use conduit_proxy_router::{Recognize, Router, Error as RouteError};
 
 pub mod app;
+mod balance;
 mod bind;
 pub mod config;
 mod connection;
 pub mod control;
 mod ctx;
 mod dns;
+mod faults;
 mod fully_qualified_authority;
 mod inbound;
+mod json;
 mod logging;
 mod map_err;
+mod mirror;
+mod orig_proto;
 mod outbound;
+mod policy;
+mod rate_limit;
+mod request_id;
+mod route;
+mod rules;
 mod telemetry;
 mod transparency;
 mod transport;
@@ -76,11 +86,18 @@ mod tower_fn; // TODO: move to tower-fn
 
 use bind::Bind;
 use connection::BoundPort;
+use faults::InjectFaults;
 use inbound::Inbound;
 use map_err::MapErr;
-use transparency::{HttpBody, Server};
+use mirror::Mirror;
+use orig_proto::Downgrade;
+use request_id::RequestId;
+use rules::ApplyRules;
+use transparency::{Connections, HttpBody, Server, ServerNames, Tunnels};
 pub use transport::{GetOriginalDst, SoOriginalDst};
 use outbound::Outbound;
+use policy::Authorize;
+use rate_limit::RateLimit;
 
 /// Runs a sidecar proxy.
 ///
@@ -159,7 +176,10 @@ where
 
         let control_host_and_port = config.control_host_and_port.clone();
 
-        info!("using controller at {:?}", control_host_and_port);
+        match control_host_and_port {
+            Some(ref addr) => info!("using controller at {:?}", addr),
+            None => info!("running without a controller; writing reports to {:?}", config.report_sink),
+        }
         info!("routing on {:?}", outbound_listener.local_addr());
         info!(
             "proxying on {:?} to {:?}",
@@ -171,9 +191,13 @@ where
             &process_ctx,
             config.event_buffer_capacity,
             config.metrics_flush_interval,
+            config.latency_buckets.clone(),
+            config.trace_collector.clone(),
+            config.routes.clone(),
         );
 
-        let (control, control_bg) = control::new();
+        let (control, control_bg) =
+            control::new(control::Schemes::new(config.destination_schemes.clone()));
 
         let mut core = Core::new().expect("executor");
         let executor = core.handle();
@@ -192,10 +216,23 @@ where
 
             let default_addr = config.private_forward.map(|a| a.into());
 
+            let listener = Listener {
+                rules: rules::Rules::default(),
+                mirror_max_body_bytes: config.mirror_max_body_bytes,
+                faults: config.inbound_faults.clone(),
+                rate_limits: config.inbound_rate_limits.clone(),
+                policy: config.inbound_policy.clone(),
+                opaque_ports: config.inbound_opaque_ports.clone(),
+                sniff_timeout: config.sniff_timeout,
+                tcp_connect_timeout: config.private_connect_timeout,
+                tls_server_names: None,
+                tunnels: None,
+            };
+
             let fut = serve(
                 inbound_listener,
                 Inbound::new(default_addr, bind),
-                config.private_connect_timeout,
+                listener,
                 ctx,
                 sensors.clone(),
                 get_original_dst.clone(),
@@ -212,17 +249,48 @@ where
 
             let bind = bind.clone().with_ctx(ctx.clone());
 
+            // Outbound TLS connections are routed by their server name, when
+            // it is resolved by the Destination service.
+            let tls_server_names = ServerNames::new(
+                control.clone(),
+                config.default_destination_namespace().to_owned(),
+            );
+
+            // Outbound CONNECT requests open tunnels to their targets, if the
+            // allowlist permits.
+            let tunnels = Tunnels::new(
+                dns::Resolver::new(dns_config.clone(), &executor),
+                config.outbound_connect_allowlist.clone(),
+            );
+
             let outgoing = Outbound::new(
                 bind,
                 control,
                 config.default_destination_namespace().to_owned(),
+                config.zone.clone(),
+                config.balance_strategies.clone(),
+                config.hash_keys.clone(),
+                config.traffic_splits.clone(),
                 config.bind_timeout,
             );
 
+            let listener = Listener {
+                rules: config.routing_rules.clone(),
+                mirror_max_body_bytes: config.mirror_max_body_bytes,
+                faults: config.outbound_faults.clone(),
+                rate_limits: rate_limit::RateLimits::default(),
+                policy: policy::Policy::default(),
+                opaque_ports: config.outbound_opaque_ports.clone(),
+                sniff_timeout: config.sniff_timeout,
+                tcp_connect_timeout: config.public_connect_timeout,
+                tls_server_names: Some(tls_server_names),
+                tunnels: Some(tunnels),
+            };
+
             let fut = serve(
                 outbound_listener,
                 outgoing,
-                config.public_connect_timeout,
+                listener,
                 ctx,
                 sensors,
                 get_original_dst,
@@ -256,13 +324,28 @@ where
                         .make_control(&taps, &executor)
                         .expect("bad news in telemetry town");
 
-                    let client = control_bg.bind(
-                        telemetry,
-                        control_host_and_port,
-                        dns_config,
-                        config.report_timeout,
-                        &executor
-                    );
+                    let client = match control_host_and_port {
+                        Some(control_host_and_port) => control_bg.bind(
+                            telemetry,
+                            control_host_and_port,
+                            dns_config,
+                            config.report_timeout,
+                            &executor
+                        ),
+                        None => {
+                            let sink = config.report_sink
+                                .as_ref()
+                                .expect("report sink is required without a controller");
+                            control_bg.standalone(
+                                telemetry,
+                                sink,
+                                config.destinations_path.as_ref().map(|p| p.as_path()),
+                                &config.static_destinations,
+                                config.default_destination_namespace(),
+                                &executor,
+                            ).expect("run without a controller")
+                        }
+                    };
 
                     let fut = client.join(server.map_err(|_| {})).map(|_| {});
                     executor.spawn(::logging::context_future("controller-client", fut));
@@ -283,10 +366,25 @@ where
     }
 }
 
+/// Configures how a listener handles the requests and connections it accepts.
+struct Listener {
+    rules: rules::Rules,
+    /// The largest request body that is buffered so that it may be mirrored.
+    mirror_max_body_bytes: usize,
+    faults: faults::Faults,
+    rate_limits: rate_limit::RateLimits,
+    policy: policy::Policy,
+    opaque_ports: Vec<u16>,
+    sniff_timeout: Duration,
+    tcp_connect_timeout: Duration,
+    tls_server_names: Option<ServerNames>,
+    tunnels: Option<Tunnels>,
+}
+
 fn serve<R, B, E, F, G>(
     bound_port: BoundPort,
     recognize: R,
-    tcp_connect_timeout: Duration,
+    listener: Listener,
     proxy_ctx: Arc<ctx::Proxy>,
     sensors: telemetry::Sensors,
     get_orig_dst: G,
@@ -305,13 +403,40 @@ where
         + 'static,
     G: GetOriginalDst + 'static,
 {
+    let Listener {
+        rules,
+        mirror_max_body_bytes,
+        faults,
+        rate_limits,
+        policy,
+        opaque_ports,
+        sniff_timeout,
+        tcp_connect_timeout,
+        tls_server_names,
+        tunnels,
+    } = listener;
+
     let router = Router::new(recognize);
+    let rules = Arc::new(rules);
+    let request_rules = Arc::clone(&rules);
+    let faults = Arc::new(faults);
+    let mirror_executor = executor.clone();
+    let faults_executor = executor.clone();
+    let rate_limits = Arc::new(Mutex::new(rate_limits));
+    let request_rate_limits = Arc::clone(&rate_limits);
+    let rate_limit_sensors = sensors.clone();
+    let policy = Arc::new(policy);
+    let authorize_policy = Arc::clone(&policy);
+    let authorize_sensors = sensors.clone();
+    let assign_request_ids = proxy_ctx.is_inbound();
+    let downgrade_requests = proxy_ctx.is_inbound();
     let stack = Arc::new(NewServiceFn::new(move || {
         // Clone the router handle
         let router = router.clone();
+        let shadow = router.clone();
 
         // Map errors to appropriate response error codes.
-        MapErr::new(router, |e| {
+        let map_err = MapErr::new(router, |e| {
             match e {
                 RouteError::Route(r) => {
                     error!(" turning route error: {} into 500", r);
@@ -326,7 +451,51 @@ where
                     http::StatusCode::INTERNAL_SERVER_ERROR
                 }
             }
-        })
+        });
+
+        // Mirrored copies of requests are routed by the same router, but their
+        // responses are ignored.
+        let mirror = Mirror::new(
+            map_err,
+            shadow,
+            mirror_executor.clone(),
+            mirror_max_body_bytes,
+        );
+
+        // Rules are applied before the request is recognized, since they may
+        // change where it is routed.
+        let apply_rules = ApplyRules::new(mirror, Arc::clone(&request_rules));
+
+        // Faults are injected before rules are applied, so that they match the
+        // request as it was received.
+        let inject_faults = InjectFaults::new(
+            apply_rules,
+            Arc::clone(&faults),
+            faults_executor.clone(),
+        );
+
+        // Requests are limited before faults, rules or routing do any work for
+        // them, since the point is to shed load.
+        let rate_limit = RateLimit::new(
+            inject_faults,
+            Arc::clone(&request_rate_limits),
+            rate_limit_sensors.clone(),
+        );
+
+        // Denied requests are refused before they count against rate limits.
+        let authorize = Authorize::new(
+            rate_limit,
+            Arc::clone(&authorize_policy),
+            authorize_sensors.clone(),
+        );
+
+        // Requests are identified before they are routed so that error responses
+        // may be correlated, too.
+        let request_id = RequestId::new(authorize, assign_request_ids);
+
+        // Requests that a remote proxy upgraded to HTTP/2 are restored to
+        // their original version before anything else sees them.
+        Downgrade::new(request_id, downgrade_requests)
     }));
 
     let listen_addr = bound_port.local_addr();
@@ -336,7 +505,16 @@ where
         sensors,
         get_orig_dst,
         stack,
-        tcp_connect_timeout,
+        Connections {
+            policy,
+            rate_limits,
+            rules,
+            opaque_ports,
+            sniff_timeout,
+            tcp_connect_timeout,
+            tls_server_names,
+            tunnels,
+        },
         executor.clone(),
     );
 
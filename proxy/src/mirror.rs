@@ -0,0 +1,244 @@
+//! Mirrors outbound requests to a shadow destination.
+//!
+//! A request is mirrored when a routing rule names a `mirror` authority for it.
+//! The request's body is copied as it is read by the primary destination, so
+//! the primary request is not delayed. Once the body has been read, the copy is
+//! sent to the shadow destination in the background, and its response is
+//! ignored.
+//!
+//! Mirrored requests are routed like any other request for their authority, so
+//! their telemetry is reported under the shadow authority, marked as mirrored
+//! by the proxy that makes the copy. They carry the `conduit-mirrored` header,
+//! so that the shadow application may tell them apart, but since any client may
+//! send that header, proxies that receive it do not trust it: requests are only
+//! reported as mirrored by the proxy that mirrors them.
+//!
+//! Only the request's headers and data are mirrored: its trailers, if it has
+//! any, are not sent to the shadow destination.
+
+use std::mem;
+use std::sync::Arc;
+
+use bytes::{Bytes, BytesMut};
+use futures::{future, Async, Future, Poll};
+use futures::sync::oneshot;
+use h2;
+use http;
+use tokio_core::reactor::Handle;
+use tower::Service;
+use tower_h2::Body;
+
+use ctx;
+use rules;
+use transparency::HttpBody;
+
+/// The header that marks a mirrored copy of a request.
+pub const CONDUIT_MIRRORED: &str = "conduit-mirrored";
+
+/// Sends a copy of each request that a rule mirrors to `shadow`.
+#[derive(Debug)]
+pub struct Mirror<S, M> {
+    inner: S,
+    shadow: M,
+    executor: Handle,
+    max_body_bytes: usize,
+}
+
+/// A request extension marking a mirrored copy of a request.
+///
+/// Mirrored requests are only sent to the destination that they name, and
+/// never to the original destination of the connection.
+#[derive(Clone, Copy, Debug)]
+pub struct Mirrored;
+
+/// Copies a request body as it is read, up to a limit.
+#[derive(Debug)]
+pub struct Tee {
+    /// `None` once the body has exceeded `max_bytes`.
+    buf: Option<BytesMut>,
+    max_bytes: usize,
+    tx: Option<oneshot::Sender<Bytes>>,
+}
+
+// ===== impl Mirror =====
+
+impl<S, M> Mirror<S, M> {
+    pub fn new(inner: S, shadow: M, executor: Handle, max_body_bytes: usize) -> Self {
+        Mirror {
+            inner,
+            shadow,
+            executor,
+            max_body_bytes,
+        }
+    }
+}
+
+impl<S, M> Mirror<S, M>
+where
+    M: Service<Request = http::Request<HttpBody>> + Clone + 'static,
+    M::Future: 'static,
+{
+    /// Spawns a task that sends a copy of `req` to `authority` once its body
+    /// has been read.
+    fn mirror(&self, req: &mut http::Request<HttpBody>, authority: &http::uri::Authority) {
+        let mut shadow = http::Request::new(HttpBody::Buffered(None));
+        *shadow.method_mut() = req.method().clone();
+        *shadow.uri_mut() = req.uri().clone();
+        *shadow.version_mut() = req.version();
+        *shadow.headers_mut() = req.headers().clone();
+        if let Some(ctx) = req.extensions().get::<Arc<ctx::transport::Server>>() {
+            shadow.extensions_mut().insert(Arc::clone(ctx));
+        }
+        shadow.extensions_mut().insert(Mirrored);
+        shadow.headers_mut().insert(
+            http::header::HeaderName::from_static(CONDUIT_MIRRORED),
+            http::header::HeaderValue::from_static("true"),
+        );
+        rules::rewrite_authority(&mut shadow, authority);
+
+        let body = if req.body().is_end_stream() {
+            future::Either::A(future::ok(Bytes::new()))
+        } else {
+            let (tee, rx) = Tee::new(self.max_body_bytes);
+            let body = mem::replace(req.body_mut(), HttpBody::Buffered(None));
+            *req.body_mut() = HttpBody::Mirrored(Box::new(body), tee);
+            future::Either::B(rx)
+        };
+
+        let mut svc = self.shadow.clone();
+        let authority = authority.clone();
+        let send = body
+            .map_err(|_| debug!("request body was not mirrored"))
+            .and_then(move |body| {
+                trace!("mirroring request to {}", authority);
+                *shadow.body_mut() = HttpBody::Buffered(Some(body));
+                svc.call(shadow)
+                    .map(|_| ())
+                    .map_err(move |_| debug!("mirrored request to {} failed", authority))
+            });
+        self.executor.spawn(send);
+    }
+}
+
+impl<S, M> Service for Mirror<S, M>
+where
+    S: Service<Request = http::Request<HttpBody>>,
+    M: Service<Request = http::Request<HttpBody>> + Clone + 'static,
+    M::Future: 'static,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, mut request: Self::Request) -> Self::Future {
+        let mirror = request.extensions_mut().remove::<rules::MirrorTo>();
+        if let Some(rules::MirrorTo(authority)) = mirror {
+            self.mirror(&mut request, &authority);
+        }
+        self.inner.call(request)
+    }
+}
+
+/// Returns true if the request is a mirrored copy made by this proxy.
+///
+/// The `conduit-mirrored` header is not consulted, since it may be set by any
+/// client.
+pub fn is_mirrored<B>(req: &http::Request<B>) -> bool {
+    req.extensions().get::<Mirrored>().is_some()
+}
+
+// ===== impl Tee =====
+
+impl Tee {
+    fn new(max_bytes: usize) -> (Self, oneshot::Receiver<Bytes>) {
+        let (tx, rx) = oneshot::channel();
+        let tee = Tee {
+            buf: Some(BytesMut::new()),
+            max_bytes,
+            tx: Some(tx),
+        };
+        (tee, rx)
+    }
+
+    /// Records the result of polling the body for data.
+    ///
+    /// The copy is sent when the body ends, and is abandoned if the body fails
+    /// or grows too large.
+    pub fn observe(&mut self, poll: &Poll<Option<Bytes>, h2::Error>) {
+        match *poll {
+            Ok(Async::NotReady) => {}
+            Ok(Async::Ready(Some(ref data))) => {
+                let fits = self.buf
+                    .as_ref()
+                    .map(|buf| buf.len() + data.len() <= self.max_bytes)
+                    .unwrap_or(false);
+                if fits {
+                    if let Some(ref mut buf) = self.buf {
+                        buf.extend_from_slice(data);
+                    }
+                } else if self.buf.take().is_some() {
+                    debug!("request body exceeds {} bytes; not mirroring", self.max_bytes);
+                    self.tx = None;
+                }
+            }
+            Ok(Async::Ready(None)) => {
+                if let (Some(buf), Some(tx)) = (self.buf.take(), self.tx.take()) {
+                    let _ = tx.send(buf.freeze());
+                }
+            }
+            Err(_) => {
+                self.buf = None;
+                self.tx = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::{Async, Future};
+    use http;
+
+    use super::{is_mirrored, Mirrored, Tee, CONDUIT_MIRRORED};
+
+    #[test]
+    fn detects_mirrored_requests() {
+        let mut req = http::Request::get("http://web/").body(()).unwrap();
+        assert!(!is_mirrored(&req));
+
+        req.extensions_mut().insert(Mirrored);
+        assert!(is_mirrored(&req));
+
+        // Any client may send the header, so it is not trusted.
+        let req = http::Request::get("http://web/")
+            .header(CONDUIT_MIRRORED, "true")
+            .body(())
+            .unwrap();
+        assert!(!is_mirrored(&req));
+    }
+
+    #[test]
+    fn tee_sends_copy_when_body_ends() {
+        let (mut tee, rx) = Tee::new(8);
+        tee.observe(&Ok(Async::Ready(Some(Bytes::from("abc")))));
+        tee.observe(&Ok(Async::NotReady));
+        tee.observe(&Ok(Async::Ready(Some(Bytes::from("defgh")))));
+        tee.observe(&Ok(Async::Ready(None)));
+        assert_eq!(rx.wait().unwrap(), Bytes::from("abcdefgh"));
+    }
+
+    #[test]
+    fn tee_abandons_copy_of_large_body() {
+        let (mut tee, rx) = Tee::new(4);
+        tee.observe(&Ok(Async::Ready(Some(Bytes::from("abc")))));
+        tee.observe(&Ok(Async::Ready(Some(Bytes::from("de")))));
+        tee.observe(&Ok(Async::Ready(None)));
+        assert!(rx.wait().is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! Carries HTTP/1 requests between proxies over HTTP/2.
+//!
+//! When the Destination API reports that an endpoint runs a proxy, the outbound
+//! proxy sends HTTP/1 requests for it over a single HTTP/2 connection, rather
+//! than opening an HTTP/1 connection for each request in flight. The request's
+//! original version is sent in the `conduit-orig-proto` header, and the inbound
+//! proxy restores it before the request is forwarded to the application.
+//!
+//! Only the version is preserved: requests that were sent in absolute-form
+//! reach the application in origin-form, with a `Host` header.
+//!
+//! The inbound proxy cannot tell whether an HTTP/2 request came from another
+//! proxy, so it trusts `conduit-orig-proto` from any HTTP/2 client: a client
+//! that sets the header has its request forwarded to the application as
+//! HTTP/1. This grants it nothing that an HTTP/1 connection would not, since
+//! the request is authorized, limited and routed like any other.
+
+use futures::{Async, Future, Poll};
+use http;
+use http::header::{HeaderName, HeaderValue, HOST};
+use http::uri::Uri;
+use tower::{NewService, Service};
+
+use transparency::h1;
+
+/// The header that records the original version of an upgraded request.
+pub const CONDUIT_ORIG_PROTO: &str = "conduit-orig-proto";
+
+/// Headers that are specific to an HTTP/1 connection, and so may not be sent
+/// over HTTP/2 (RFC 7540, section 8.1.2.2).
+const CONNECTION_HEADERS: &[&str] = &[
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Sends HTTP/1 requests as HTTP/2 requests, if the endpoint runs a proxy.
+///
+/// The inner service must speak HTTP/2 if requests are upgraded.
+#[derive(Debug)]
+pub struct Upgrade<S> {
+    inner: S,
+    enabled: bool,
+}
+
+/// Restores the original version of requests upgraded by a remote proxy.
+///
+/// Only the inbound proxy downgrades requests. Any HTTP/2 request that carries
+/// `conduit-orig-proto` is downgraded, whether or not a proxy sent it.
+#[derive(Debug)]
+pub struct Downgrade<S> {
+    inner: S,
+    enabled: bool,
+}
+
+/// Future returned by `Upgrade`'s `NewService`.
+#[derive(Debug)]
+pub struct NewUpgrade<F> {
+    inner: F,
+    enabled: bool,
+}
+
+/// Sets the version of a response to that of the request that it answers.
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    inner: F,
+    version: Option<http::Version>,
+}
+
+// ===== impl Upgrade =====
+
+impl<S> Upgrade<S> {
+    pub fn new(inner: S, enabled: bool) -> Self {
+        Upgrade {
+            inner,
+            enabled,
+        }
+    }
+}
+
+impl<N, A, B> NewService for Upgrade<N>
+where
+    N: NewService<Request = http::Request<A>, Response = http::Response<B>>,
+{
+    type Request = N::Request;
+    type Response = N::Response;
+    type Error = N::Error;
+    type Service = Upgrade<N::Service>;
+    type InitError = N::InitError;
+    type Future = NewUpgrade<N::Future>;
+
+    fn new_service(&self) -> Self::Future {
+        NewUpgrade {
+            inner: self.inner.new_service(),
+            enabled: self.enabled,
+        }
+    }
+}
+
+impl<S, A, B> Service for Upgrade<S>
+where
+    S: Service<Request = http::Request<A>, Response = http::Response<B>>,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, mut request: Self::Request) -> Self::Future {
+        let version = if self.enabled {
+            upgrade(&mut request)
+        } else {
+            None
+        };
+
+        ResponseFuture {
+            inner: self.inner.call(request),
+            version,
+        }
+    }
+}
+
+/// Sends an HTTP/1 request as HTTP/2, and returns the version that its
+/// response must have.
+fn upgrade<B>(request: &mut http::Request<B>) -> Option<http::Version> {
+    let orig = match request.version() {
+        http::Version::HTTP_10 => "HTTP/1.0",
+        http::Version::HTTP_11 => "HTTP/1.1",
+        _ => return None,
+    };
+    trace!("upgrading {} request to HTTP/2", orig);
+
+    let version = request.version();
+    strip_connection_headers(request.headers_mut());
+    request.headers_mut().insert(
+        HeaderName::from_static(CONDUIT_ORIG_PROTO),
+        HeaderValue::from_static(orig),
+    );
+    *request.version_mut() = http::Version::HTTP_2;
+    Some(version)
+}
+
+impl<F, S> Future for NewUpgrade<F>
+where
+    F: Future<Item = S>,
+{
+    type Item = Upgrade<S>;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let inner = try_ready!(self.inner.poll());
+        Ok(Async::Ready(Upgrade::new(inner, self.enabled)))
+    }
+}
+
+// ===== impl Downgrade =====
+
+impl<S> Downgrade<S> {
+    pub fn new(inner: S, enabled: bool) -> Self {
+        Downgrade {
+            inner,
+            enabled,
+        }
+    }
+}
+
+impl<S, A, B> Service for Downgrade<S>
+where
+    S: Service<Request = http::Request<A>, Response = http::Response<B>>,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, mut request: Self::Request) -> Self::Future {
+        let version = if self.enabled && request.version() == http::Version::HTTP_2 {
+            downgrade(&mut request)
+        } else {
+            None
+        };
+
+        ResponseFuture {
+            inner: self.inner.call(request),
+            version,
+        }
+    }
+}
+
+/// Restores the original version of the request, if it was upgraded, and
+/// returns the version that its response must have.
+fn downgrade<B>(request: &mut http::Request<B>) -> Option<http::Version> {
+    let version = match request.headers_mut().remove(CONDUIT_ORIG_PROTO) {
+        Some(ref v) if *v == "HTTP/1.0" => http::Version::HTTP_10,
+        Some(ref v) if *v == "HTTP/1.1" => http::Version::HTTP_11,
+        Some(v) => {
+            debug!("ignoring unknown {}: {:?}", CONDUIT_ORIG_PROTO, v);
+            return None;
+        }
+        None => return None,
+    };
+    trace!("downgrading HTTP/2 request to {:?}", version);
+
+    // HTTP/2 requests are always in absolute-form, so the authority is moved
+    // to the `Host` header if the original request had none.
+    if !request.headers().contains_key(HOST) {
+        let host = request.uri()
+            .authority_part()
+            .and_then(|a| HeaderValue::from_str(a.as_str()).ok());
+        if let Some(host) = host {
+            request.headers_mut().insert(HOST, host);
+        }
+    }
+    let origin = request.uri()
+        .path_and_query()
+        .and_then(|pq| pq.as_str().parse::<Uri>().ok());
+    if let Some(origin) = origin {
+        *request.uri_mut() = origin;
+    }
+
+    *request.version_mut() = version;
+    Some(http::Version::HTTP_2)
+}
+
+// ===== impl ResponseFuture =====
+
+impl<F, B> Future for ResponseFuture<F>
+where
+    F: Future<Item = http::Response<B>>,
+{
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let mut response = try_ready!(self.inner.poll());
+        if let Some(version) = self.version {
+            if version == http::Version::HTTP_2 {
+                strip_connection_headers(response.headers_mut());
+            }
+            *response.version_mut() = version;
+        }
+        Ok(Async::Ready(response))
+    }
+}
+
+/// Removes headers that may not be sent over HTTP/2.
+fn strip_connection_headers(headers: &mut http::HeaderMap) {
+    h1::strip_connection_headers(headers);
+    for name in CONNECTION_HEADERS {
+        headers.remove(*name);
+    }
+
+    // `TE` may only be sent to say that trailers are accepted.
+    let te_trailers = headers.get(http::header::TE)
+        .map(|te| *te == "trailers")
+        .unwrap_or(true);
+    if !te_trailers {
+        headers.remove(http::header::TE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http;
+    use http::header::{HOST, TE, TRANSFER_ENCODING};
+
+    use super::{downgrade, upgrade, CONDUIT_ORIG_PROTO};
+
+    #[test]
+    fn upgrades_and_downgrades_requests() {
+        let mut req = http::Request::get("http://web.example.com/users?id=1")
+            .version(http::Version::HTTP_10)
+            .header(HOST, "web.example.com")
+            .header(TRANSFER_ENCODING, "chunked")
+            .header(TE, "gzip")
+            .body(())
+            .unwrap();
+
+        assert_eq!(upgrade(&mut req), Some(http::Version::HTTP_10));
+        assert_eq!(req.version(), http::Version::HTTP_2);
+        assert_eq!(req.headers()[CONDUIT_ORIG_PROTO], "HTTP/1.0");
+        assert!(!req.headers().contains_key(TRANSFER_ENCODING));
+        assert!(!req.headers().contains_key(TE));
+
+        assert_eq!(downgrade(&mut req), Some(http::Version::HTTP_2));
+        assert_eq!(req.version(), http::Version::HTTP_10);
+        assert_eq!(req.uri(), "/users?id=1");
+        assert_eq!(req.headers()[HOST], "web.example.com");
+        assert!(!req.headers().contains_key(CONDUIT_ORIG_PROTO));
+    }
+
+    #[test]
+    fn downgrade_sets_host_from_authority() {
+        let mut req = http::Request::get("http://web.example.com:8080/")
+            .version(http::Version::HTTP_2)
+            .header(CONDUIT_ORIG_PROTO, "HTTP/1.1")
+            .header(TE, "trailers")
+            .body(())
+            .unwrap();
+
+        assert_eq!(downgrade(&mut req), Some(http::Version::HTTP_2));
+        assert_eq!(req.version(), http::Version::HTTP_11);
+        assert_eq!(req.uri(), "/");
+        assert_eq!(req.headers()[HOST], "web.example.com:8080");
+        assert_eq!(req.headers()[TE], "trailers");
+    }
+
+    #[test]
+    fn only_known_versions_are_changed() {
+        let mut req = http::Request::get("http://web.example.com/")
+            .version(http::Version::HTTP_2)
+            .body(())
+            .unwrap();
+        assert_eq!(upgrade(&mut req), None);
+        assert_eq!(downgrade(&mut req), None);
+        assert_eq!(req.uri(), "http://web.example.com/");
+
+        req.headers_mut().insert(CONDUIT_ORIG_PROTO, "HTTP/3".parse().unwrap());
+        assert_eq!(downgrade(&mut req), None);
+        assert_eq!(req.version(), http::Version::HTTP_2);
+        assert_eq!(req.uri(), "http://web.example.com/");
+    }
+}
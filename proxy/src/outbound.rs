@@ -5,29 +5,40 @@ use std::sync::Arc;
 
 use futures::{Async, Poll};
 use http;
-use rand;
+use rand::{self, Rng};
 use tower;
-use tower_balance::{self, choose, load, Balance};
+use tower_balance::Balance;
 use tower_buffer::Buffer;
 use tower_discover::{Change, Discover};
 use tower_in_flight_limit::InFlightLimit;
 use tower_h2;
 use conduit_proxy_router::{Reuse, Recognize};
 
+use balance::{Balancer, BindEndpoint, ChooseEndpoint, Endpoint, HashRing, SharedStrategy};
 use bind::{self, Bind, Protocol};
+use config::{BalanceStrategy, HashKey};
 use control::{self, discovery};
 use control::discovery::Bind as BindTrait;
 use ctx;
-use fully_qualified_authority::{FullyQualifiedAuthority, NamedAddress};
+use fully_qualified_authority::FullyQualifiedAuthority;
+use mirror;
+use rules;
 use timeout::Timeout;
 use transparency::h1;
 
 type BindProtocol<B> = bind::BindProtocol<Arc<ctx::Proxy>, B>;
 
-pub struct Outbound<B> {
+pub struct Outbound<B>
+where
+    B: tower_h2::Body + 'static,
+{
     bind: Bind<Arc<ctx::Proxy>, B>,
     discovery: control::Control,
     default_namespace: String,
+    zone: Option<String>,
+    balance_strategies: Vec<(String, BalanceStrategy)>,
+    hash_keys: Vec<(String, HashKey)>,
+    traffic_splits: Vec<(String, Vec<(String, u32)>)>,
     bind_timeout: Duration,
 }
 
@@ -35,19 +46,53 @@ const MAX_IN_FLIGHT: usize = 10_000;
 
 // ===== impl Outbound =====
 
-impl<B> Outbound<B> {
+impl<B> Outbound<B>
+where
+    B: tower_h2::Body + 'static,
+{
     pub fn new(bind: Bind<Arc<ctx::Proxy>, B>,
                discovery: control::Control,
                default_namespace: String,
+               zone: Option<String>,
+               balance_strategies: Vec<(String, BalanceStrategy)>,
+               hash_keys: Vec<(String, HashKey)>,
+               traffic_splits: Vec<(String, Vec<(String, u32)>)>,
                bind_timeout: Duration,)
                -> Outbound<B> {
         Self {
             bind,
             discovery,
             default_namespace,
+            zone,
+            balance_strategies,
+            hash_keys,
+            traffic_splits,
             bind_timeout,
         }
     }
+
+    /// Chooses a backend for `name` by weight, if its traffic is split.
+    ///
+    /// Each backend is a destination of its own, with its own discovery and
+    /// load balancer.
+    fn split(&self, name: &FullyQualifiedAuthority) -> Option<FullyQualifiedAuthority> {
+        let authority = name.without_trailing_dot();
+        let &(_, ref backends) = self.traffic_splits.iter()
+            .find(|&&(ref n, _)| n.eq_ignore_ascii_case(authority.host()))?;
+        let backend = choose_backend(backends, &mut rand::thread_rng())?;
+
+        // Backends are requested on the same port as the split destination.
+        let backend = match authority.port() {
+            Some(port) => format!("{}:{}", backend, port),
+            None => backend.to_owned(),
+        };
+        let backend = backend.parse::<http::uri::Authority>().ok()?;
+        let resolved = self.discovery.resolvable_name(&backend, &self.default_namespace);
+        if resolved.is_none() {
+            warn!("backend {} of {:?} cannot be resolved", backend, name);
+        }
+        resolved
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -80,9 +125,9 @@ where
     type Error = <Self::Service as tower::Service>::Error;
     type Key = (Destination, Protocol);
     type RouteError = bind::BufferSpawnError;
-    type Service = InFlightLimit<Timeout<Buffer<Balance<
-        load::WithPendingRequests<Discovery<B>>,
-        choose::PowerOfTwoChoices<rand::ThreadRng>
+    type Service = InFlightLimit<Timeout<Buffer<Balancer<
+        Discovery<B>,
+        ChooseEndpoint<rand::ThreadRng>
     >>>>;
 
     fn recognize(&self, req: &Self::Request) -> Option<Reuse<Self::Key>> {
@@ -92,30 +137,38 @@ where
         // by `NormalizeUri`, as we need to know whether the request will
         // be routed by Host/authority or by SO_ORIGINAL_DST, in order to
         // determine whether the service is reusable.
-        let local = req.uri().authority_part()
-            .cloned()
+        //
+        // An authority chosen by a routing rule takes precedence over both.
+        let routed = req.extensions().get::<rules::Route>()
+            .map(|route| route.0.clone());
+        let local = routed
+            .or_else(|| req.uri().authority_part().cloned())
         // Therefore, we need to check the host header as well as the URI
         // for a valid authority, before we fall back to SO_ORIGINAL_DST.
             .or_else(|| h1::authority_from_host(req))
-            .map(|authority| {
-                FullyQualifiedAuthority::normalize(
+            .and_then(|authority| {
+                self.discovery.resolvable_name(
                     &authority,
                     &self.default_namespace)
             });
 
-        // If we can't fully qualify the authority as a local service,
-        // and there is no original dst, then we have nothing! In that
+        // If we can't fully qualify the authority as a local service, and
+        // no destination scheme is configured for it, and there is no
+        // original dst, then we have nothing! In that
         // case, we return `None`, which results an "unrecognized" error.
         //
         // In practice, this shouldn't ever happen, since we expect the proxy
         // to be run on Linux servers, with iptables setup, so there should
         // always be an original destination.
-        let dest = if let Some(NamedAddress {
-            name,
-            use_destination_service: true
-        }) = local {
-            Destination::LocalSvc(name)
+        let dest = if let Some(name) = local {
+            let backend = self.split(&name);
+            Destination::LocalSvc(backend.unwrap_or(name))
         } else {
+            // Mirrored requests must not be sent to the primary request's
+            // destination.
+            if req.extensions().get::<mirror::Mirrored>().is_some() {
+                return None;
+            }
             let orig_dst = req.extensions()
                 .get::<Arc<ctx::transport::Server>>()
                 .and_then(|ctx| {
@@ -131,7 +184,11 @@ where
     /// Builds a dynamic, load balancing service.
     ///
     /// Resolves the authority in service discovery and initializes a service that buffers
-    /// and load balances requests across.
+    /// and load balances requests across, preferring endpoints in the proxy's zone.
+    ///
+    /// Destinations with a configured hash key are routed by consistent hashing.
+    /// Others are balanced with the strategy configured for them locally or,
+    /// otherwise, the one chosen by the controller.
     ///
     /// # TODO
     ///
@@ -144,12 +201,32 @@ where
         let &(ref dest, ref protocol) = key;
         debug!("building outbound {:?} client to {:?}", protocol, dest);
 
+        let hash_key = match *dest {
+            Destination::LocalSvc(ref authority) => configured(&self.hash_keys, authority).cloned(),
+            Destination::External(_) => None,
+        };
+
+        let strategy = SharedStrategy::new(BalanceStrategy::LeastRequests);
         let resolve = match *dest {
             Destination::LocalSvc(ref authority) => {
-                Discovery::LocalSvc(self.discovery.resolve(
+                let controlled = match configured(&self.balance_strategies, authority) {
+                    Some(&s) => {
+                        strategy.set(s);
+                        None
+                    }
+                    None => Some(strategy.clone()),
+                };
+
+                let watch = self.discovery.resolve(
                     authority,
-                    self.bind.clone().with_protocol(protocol.clone()),
-                ))
+                    BindEndpoint::new(
+                        self.bind.clone()
+                            .with_protocol(protocol.clone())
+                            .with_destination_name(authority.clone()),
+                        self.zone.clone(),
+                    ),
+                );
+                Discovery::LocalSvc(watch, controlled)
             },
             Destination::External(addr) => {
                 Discovery::External(Some((addr, self.bind.clone()
@@ -157,9 +234,13 @@ where
             }
         };
 
-        let loaded = tower_balance::load::WithPendingRequests::new(resolve);
-
-        let balance = tower_balance::power_of_two_choices(loaded, rand::thread_rng());
+        let balance = match hash_key {
+            Some(key) => Balancer::Hash(HashRing::new(resolve, key)),
+            None => {
+                let choose = ChooseEndpoint::new(strategy, rand::thread_rng());
+                Balancer::Choose(Balance::new(resolve, choose))
+            }
+        };
 
         // use the same executor as the underlying `Bind` for the `Buffer` and
         // `Timeout`.
@@ -175,8 +256,42 @@ where
     }
 }
 
-pub enum Discovery<B> {
-    LocalSvc(discovery::Watch<BindProtocol<B>>),
+/// Returns the value of the first rule whose suffix matches `authority`.
+fn configured<'a, T>(
+    rules: &'a [(String, T)],
+    authority: &FullyQualifiedAuthority,
+) -> Option<&'a T> {
+    let host = authority.without_trailing_dot().host();
+    rules.iter()
+        .find(|&&(ref suffix, _)| discovery::host_has_suffix(host, suffix))
+        .map(|&(_, ref value)| value)
+}
+
+/// Chooses one of `backends` at random, in proportion to its weight.
+fn choose_backend<'a, R: Rng>(backends: &'a [(String, u32)], rng: &mut R) -> Option<&'a str> {
+    let total = backends.iter().map(|&(_, weight)| u64::from(weight)).sum::<u64>();
+    if total == 0 {
+        return None;
+    }
+
+    let mut n = rng.gen_range(0, total);
+    for &(ref backend, weight) in backends {
+        let weight = u64::from(weight);
+        if n < weight {
+            return Some(backend);
+        }
+        n -= weight;
+    }
+    None
+}
+
+pub enum Discovery<B>
+where
+    B: tower_h2::Body + 'static,
+{
+    /// Also holds the balancer's strategy, unless it was configured locally, so
+    /// that it may be changed by the controller.
+    LocalSvc(discovery::Watch<BindEndpoint<BindProtocol<B>>>, Option<SharedStrategy>),
     External(Option<(SocketAddr, BindProtocol<B>)>),
 }
 
@@ -188,13 +303,21 @@ where
     type Request = http::Request<B>;
     type Response = bind::HttpResponse;
     type Error = <bind::Service<B> as tower::Service>::Error;
-    type Service = bind::Service<B>;
+    type Service = Endpoint<bind::Service<B>>;
     type DiscoverError = BindError;
 
     fn poll(&mut self) -> Poll<Change<Self::Key, Self::Service>, Self::DiscoverError> {
         match *self {
-            Discovery::LocalSvc(ref mut w) => w.poll()
-                .map_err(|_| BindError::Internal),
+            Discovery::LocalSvc(ref mut w, ref strategy) => {
+                let change = w.poll().map_err(|_| BindError::Internal);
+                if let Some(ref strategy) = *strategy {
+                    if let Some(s) = w.take_strategy() {
+                        debug!("balancing with {:?}", s);
+                        strategy.set(s);
+                    }
+                }
+                change
+            }
             Discovery::External(ref mut opt) => {
                 // This "discovers" a single address for an external service
                 // that never has another change. This can mean it floats
@@ -202,9 +325,9 @@ where
                 // circuit-breaking, this should be able to take care of itself,
                 // closing down when the connection is no longer usable.
                 if let Some((addr, bind)) = opt.take() {
-                    let svc = bind.bind(&addr)
+                    let svc = bind.bind(&addr, &discovery::Metadata::default())
                         .map_err(|_| BindError::External{ addr })?;
-                    Ok(Async::Ready(Change::Insert(addr, svc)))
+                    Ok(Async::Ready(Change::Insert(addr, Endpoint::new(svc, false))))
                 } else {
                     Ok(Async::NotReady)
                 }
@@ -240,3 +363,32 @@ impl error::Error for BindError {
 
     fn cause(&self) -> Option<&error::Error> { None }
 }
+
+#[cfg(test)]
+mod tests {
+    use rand;
+
+    use super::choose_backend;
+
+    #[test]
+    fn chooses_backends_by_weight() {
+        let backends = vec![
+            ("web-v1".to_owned(), 3),
+            ("web-v2".to_owned(), 1),
+            ("web-v3".to_owned(), 0),
+        ];
+        let mut rng = rand::thread_rng();
+        let mut v1 = 0;
+        for _ in 0..4000 {
+            match choose_backend(&backends, &mut rng) {
+                Some("web-v1") => v1 += 1,
+                Some("web-v2") => {}
+                other => panic!("unexpected backend: {:?}", other),
+            }
+        }
+        assert!(v1 > 2800 && v1 < 3200, "web-v1 chosen {} times", v1);
+
+        let none = vec![("web-v1".to_owned(), 0)];
+        assert_eq!(choose_backend(&none, &mut rng), None);
+    }
+}
@@ -0,0 +1,383 @@
+//! Authorizes inbound requests and connections before they are forwarded to the
+//! local application.
+//!
+//! The policy is read from a file with one rule per line. Each rule is `allow`
+//! or `deny`, followed by zero or more conditions:
+//!
+//! ```text
+//! # decision  conditions
+//! allow       source=10.1.0.0/16 method=GET path=/api
+//! deny        path=/admin
+//! deny        port=9090
+//! allow       source=10.0.0.0/8
+//! deny
+//! ```
+//!
+//! In addition to the conditions of routing rules, conditions are:
+//!
+//! - `source=<network>`: the peer's address is in the network, given as an
+//!   address or in CIDR notation.
+//! - `port=<port>`: the connection is to the port on the local application.
+//!
+//! Rules are tried in order and the first whose conditions all hold decides.
+//! Requests that match no rule are allowed, so a policy that should deny by
+//! default ends with a bare `deny`.
+//!
+//! Denied HTTP requests are answered with `403 Forbidden`. Connections that are
+//! not HTTP are checked once, when they are accepted, and are closed if they are
+//! denied. Since they carry no requests, rules with conditions on requests (such
+//! as `method` or `path`) never match them.
+//!
+//! Two things are not supported yet. Rules cannot match a peer's TLS identity,
+//! since the proxy does not terminate TLS and so never learns one. And the
+//! policy is only read from a file when the proxy starts: the controller has no
+//! API with which to push a policy, or changes to one, to the proxy.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use futures::{future, Poll};
+use http;
+use http::header::CONTENT_LENGTH;
+use ipnet::{Contains, Ipv4Net, Ipv6Net};
+use tower::Service;
+
+use ctx;
+use rules::Conditions;
+use telemetry::Sensors;
+
+/// An ordered set of allow and deny rules.
+#[derive(Clone, Debug, Default)]
+pub struct Policy(Vec<Rule>);
+
+#[derive(Clone, Debug)]
+struct Rule {
+    allow: bool,
+    source: Option<Net>,
+    port: Option<u16>,
+    conditions: Conditions,
+}
+
+#[derive(Clone, Debug)]
+enum Net {
+    V4(Ipv4Net),
+    V6(Ipv6Net),
+}
+
+/// Refuses requests that the policy denies.
+#[derive(Debug)]
+pub struct Authorize<S> {
+    inner: S,
+    policy: Arc<Policy>,
+    sensors: Sensors,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The line (1-indexed) does not start with `allow` or `deny`.
+    InvalidDecision(usize),
+    InvalidCondition(usize),
+}
+
+// ===== impl Policy =====
+
+impl Policy {
+    /// Reads a policy from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut s = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut s))
+            .map_err(Error::Io)?;
+        s.parse()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns true if the policy allows the request, received on the
+    /// connection described by `ctx`.
+    pub fn allows_request<B>(&self, ctx: &ctx::transport::Server, req: &http::Request<B>) -> bool {
+        self.0
+            .iter()
+            .find(|r| r.matches_transport(ctx) && r.conditions.matches(req))
+            .map(|r| r.allow)
+            .unwrap_or(true)
+    }
+
+    /// Returns true if the policy allows a connection that is not HTTP.
+    pub fn allows_connection(&self, ctx: &ctx::transport::Server) -> bool {
+        self.0
+            .iter()
+            .find(|r| r.conditions.is_empty() && r.matches_transport(ctx))
+            .map(|r| r.allow)
+            .unwrap_or(true)
+    }
+}
+
+impl FromStr for Policy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let lineno = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split_whitespace();
+            let allow = match fields.next() {
+                Some("allow") => true,
+                Some("deny") => false,
+                _ => return Err(Error::InvalidDecision(lineno)),
+            };
+
+            let mut rule = Rule {
+                allow,
+                source: None,
+                port: None,
+                conditions: Conditions::default(),
+            };
+            for condition in fields {
+                rule.parse(condition).ok_or(Error::InvalidCondition(lineno))?;
+            }
+
+            rules.push(rule);
+        }
+
+        Ok(Policy(rules))
+    }
+}
+
+// ===== impl Rule =====
+
+impl Rule {
+    fn parse(&mut self, s: &str) -> Option<()> {
+        if s.starts_with("source=") {
+            self.source = Some(s["source=".len()..].parse().ok()?);
+        } else if s.starts_with("port=") {
+            self.port = Some(s["port=".len()..].parse().ok()?);
+        } else {
+            self.conditions.parse(s)?;
+        }
+        Some(())
+    }
+
+    /// Returns true if the connection satisfies the rule's `source` and `port`
+    /// conditions.
+    fn matches_transport(&self, ctx: &ctx::transport::Server) -> bool {
+        if let Some(ref net) = self.source {
+            if !net.contains(&ctx.remote.ip()) {
+                return false;
+            }
+        }
+
+        if let Some(port) = self.port {
+            let dst = ctx.orig_dst.unwrap_or(ctx.local);
+            if dst.port() != port {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+// ===== impl Net =====
+
+impl Net {
+    fn contains(&self, addr: &IpAddr) -> bool {
+        match (self, addr) {
+            (&Net::V4(ref net), &IpAddr::V4(ref addr)) => net.contains(addr),
+            (&Net::V6(ref net), &IpAddr::V6(ref addr)) => net.contains(addr),
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for Net {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '/');
+        let addr = parts.next().and_then(|a| a.parse::<IpAddr>().ok()).ok_or(())?;
+        let prefix = match parts.next() {
+            Some(p) => Some(p.parse::<u8>().map_err(|_| ())?),
+            None => None,
+        };
+
+        match addr {
+            IpAddr::V4(addr) => {
+                let net = Ipv4Net::new(addr, prefix.unwrap_or(32)).map_err(|_| ())?;
+                Ok(Net::V4(net))
+            }
+            IpAddr::V6(addr) => {
+                let net = Ipv6Net::new(addr, prefix.unwrap_or(128)).map_err(|_| ())?;
+                Ok(Net::V6(net))
+            }
+        }
+    }
+}
+
+// ===== impl Authorize =====
+
+impl<S> Authorize<S> {
+    pub fn new(inner: S, policy: Arc<Policy>, sensors: Sensors) -> Self {
+        Authorize {
+            inner,
+            policy,
+            sensors,
+        }
+    }
+}
+
+impl<S, A, B> Service for Authorize<S>
+where
+    S: Service<Request = http::Request<A>, Response = http::Response<B>>,
+    B: Default,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = future::Either<S::Future, future::FutureResult<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        if self.policy.is_empty() {
+            return future::Either::A(self.inner.call(request));
+        }
+
+        let allowed = match request.extensions().get::<Arc<ctx::transport::Server>>() {
+            Some(ctx) => {
+                let allowed = self.policy.allows_request(ctx, &request);
+                if allowed {
+                    debug!("allowed {} {} from {}", request.method(), request.uri(), ctx.remote);
+                } else {
+                    info!("denied {} {} from {}", request.method(), request.uri(), ctx.remote);
+                }
+                self.sensors.authorized(ctx, allowed);
+                allowed
+            }
+            None => {
+                warn!("denied {} {} without a server context", request.method(), request.uri());
+                false
+            }
+        };
+
+        if allowed {
+            return future::Either::A(self.inner.call(request));
+        }
+
+        let response = http::Response::builder()
+            .status(http::StatusCode::FORBIDDEN)
+            .header(CONTENT_LENGTH, "0")
+            .body(B::default())
+            .expect("forbidden response is valid");
+        future::Either::B(future::ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+    use std::sync::Arc;
+
+    use conduit_proxy_controller_grpc::common::Protocol;
+    use http;
+
+    use ctx;
+    use super::*;
+
+    fn server(remote: [u8; 4], port: u16, protocol: Protocol) -> Arc<ctx::transport::Server> {
+        let process = ctx::Process::test("node", "pod", "ns");
+        let proxy = ctx::Proxy::inbound(&process);
+        let local = SocketAddr::from(([10, 0, 0, 1], 4143));
+        let remote = SocketAddr::from((remote, 55555));
+        let orig_dst = Some(SocketAddr::from(([10, 0, 0, 1], port)));
+        ctx::transport::Server::new(&proxy, &local, &remote, &orig_dst, protocol)
+    }
+
+    fn policy() -> Policy {
+        "
+            # Comments and blank lines are ignored.
+            allow  source=10.1.0.0/16 method=GET path=/api
+            deny   path=/admin
+            deny   port=9090
+            allow  source=10.0.0.0/8
+            deny
+        ".parse().unwrap()
+    }
+
+    #[test]
+    fn first_matching_rule_decides_requests() {
+        let policy = policy();
+        let allows = |remote: [u8; 4], req: &mut http::request::Builder| {
+            let req = req.body(()).unwrap();
+            policy.allows_request(&server(remote, 8080, Protocol::Http), &req)
+        };
+
+        assert!(allows([10, 1, 2, 3], &mut http::Request::get("/api/users")));
+        assert!(!allows([10, 1, 2, 3], &mut http::Request::get("/admin")));
+        assert!(allows([10, 2, 2, 3], &mut http::Request::get("/api/users")));
+        assert!(!allows([192, 168, 0, 1], &mut http::Request::get("/api/users")));
+        assert!(!allows([192, 168, 0, 1], &mut http::Request::post("/api/users")));
+    }
+
+    #[test]
+    fn denies_equivalent_paths() {
+        let policy = policy();
+        let server = server([10, 1, 2, 3], 8080, Protocol::Http);
+
+        for path in &["/admin", "//admin", "/./admin", "/x/../admin", "/%61dmin"] {
+            let req = http::Request::get(*path).body(()).unwrap();
+            assert!(!policy.allows_request(&server, &req), "{}", path);
+        }
+    }
+
+    #[test]
+    fn request_conditions_never_match_connections() {
+        let policy = policy();
+        let allows = |remote: [u8; 4], port: u16| {
+            policy.allows_connection(&server(remote, port, Protocol::Tcp))
+        };
+
+        assert!(allows([10, 1, 2, 3], 8080));
+        assert!(!allows([10, 1, 2, 3], 9090));
+        assert!(!allows([192, 168, 0, 1], 8080));
+    }
+
+    #[test]
+    fn empty_policy_allows_everything() {
+        let policy = Policy::default();
+        let req = http::Request::get("/admin").body(()).unwrap();
+        assert!(policy.allows_request(&server([192, 168, 0, 1], 9090, Protocol::Http), &req));
+        assert!(policy.allows_connection(&server([192, 168, 0, 1], 9090, Protocol::Tcp)));
+    }
+
+    #[test]
+    fn rejects_invalid_policies() {
+        match "permit source=10.0.0.0/8".parse::<Policy>() {
+            Err(Error::InvalidDecision(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "\nallow source=10.0.0.0/33".parse::<Policy>() {
+            Err(Error::InvalidCondition(2)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "allow port=http".parse::<Policy>() {
+            Err(Error::InvalidCondition(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+    }
+}
@@ -0,0 +1,491 @@
+//! Limits the rate of inbound requests, so that a single noisy peer cannot
+//! overwhelm the local application.
+//!
+//! Limits are read from a file with one limit per line:
+//!
+//! ```text
+//! # key        requests per second   burst
+//! source       100                   200
+//! authority    1000                  1000
+//! route        20                    50
+//! ```
+//!
+//! Each limit keeps a token bucket for each distinct value of its key: the
+//! request's source IP address, its authority, or the name of the route that
+//! it matches. Requests that have no value for a key, such as requests that
+//! match no route, are not limited by it.
+//!
+//! A request is refused unless every limit that applies to it has a token.
+//! Refused requests are answered with `429 Too Many Requests`, or, for gRPC
+//! requests, with a `RESOURCE_EXHAUSTED` status.
+//!
+//! Limits may not yet be keyed by a peer's `identity`: the proxy does not
+//! terminate TLS, so it has no identity for the peer. Files that name the
+//! `identity` key are rejected with `Error::UnsupportedKey`, rather than
+//! loaded with a limit that never applies.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::net::IpAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::{future, Poll};
+use http;
+use http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use tower::Service;
+
+use ctx;
+use telemetry::Sensors;
+use transparency::h1;
+
+/// The maximum number of buckets that each limit tracks.
+///
+/// Once a limit is full, buckets that have refilled are forgotten. If none
+/// have, new keys share a single overflow bucket, so that memory use is
+/// bounded regardless of how many peers the proxy sees.
+const MAX_BUCKETS: usize = 10_000;
+
+/// How often a full limit looks for buckets that have refilled.
+///
+/// Between sweeps, new keys use the overflow bucket, so that the cost of a
+/// sweep is paid at most once per interval rather than on every new key.
+const SWEEP_INTERVAL_SECS: u64 = 1;
+
+/// The gRPC status code for `RESOURCE_EXHAUSTED`.
+const GRPC_RESOURCE_EXHAUSTED: u32 = 8;
+
+/// A set of rate limits.
+#[derive(Clone, Debug, Default)]
+pub struct RateLimits(Vec<Limit>);
+
+#[derive(Clone, Debug)]
+struct Limit {
+    key: Key,
+    /// Tokens added to each bucket per second.
+    rate: f64,
+    /// The most tokens that a bucket may hold.
+    burst: f64,
+    /// Requests for new keys share the `None` bucket once the limit is full.
+    buckets: HashMap<Option<String>, Bucket>,
+    /// When full buckets were last forgotten.
+    swept: Option<Instant>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Key {
+    Source,
+    Authority,
+    Route,
+}
+
+#[derive(Clone, Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// The values of a request's keys.
+#[derive(Debug, Default)]
+struct Subject {
+    source: Option<IpAddr>,
+    authority: Option<String>,
+    route: Option<Arc<str>>,
+}
+
+/// Refuses requests that exceed a rate limit.
+#[derive(Debug)]
+pub struct RateLimit<S> {
+    inner: S,
+    limits: Arc<Mutex<RateLimits>>,
+    sensors: Sensors,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The line (1-indexed) does not have exactly three fields.
+    InvalidLine(usize),
+    InvalidKey(usize),
+    /// The line (1-indexed) names a key that is recognized but not yet
+    /// supported, such as `identity`.
+    UnsupportedKey(usize),
+    /// The rate is not positive, or the burst is less than one request.
+    InvalidRate(usize),
+}
+
+// ===== impl RateLimits =====
+
+impl RateLimits {
+    /// Reads rate limits from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut s = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut s))
+            .map_err(Error::Io)?;
+        s.parse()
+    }
+
+    /// Takes a token for the request from each limit that applies to it.
+    ///
+    /// If any of the limits has no token, no tokens are taken and the key of
+    /// that limit is returned.
+    fn acquire(&mut self, subject: &Subject, now: Instant) -> Result<(), Key> {
+        for limit in &mut self.0 {
+            let exhausted = limit.bucket(subject, now)
+                .map(|bucket| bucket.tokens < 1.0)
+                .unwrap_or(false);
+            if exhausted {
+                return Err(limit.key);
+            }
+        }
+
+        for limit in &mut self.0 {
+            if let Some(bucket) = limit.bucket(subject, now) {
+                bucket.tokens -= 1.0;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for RateLimits {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut limits = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let lineno = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() != 3 {
+                return Err(Error::InvalidLine(lineno));
+            }
+
+            let key = match fields[0] {
+                "source" => Key::Source,
+                "authority" => Key::Authority,
+                "route" => Key::Route,
+                "identity" => return Err(Error::UnsupportedKey(lineno)),
+                _ => return Err(Error::InvalidKey(lineno)),
+            };
+            let rate = fields[1].parse::<f64>().map_err(|_| Error::InvalidRate(lineno))?;
+            let burst = fields[2].parse::<f64>().map_err(|_| Error::InvalidRate(lineno))?;
+            if !(rate > 0.0 && burst >= 1.0) {
+                return Err(Error::InvalidRate(lineno));
+            }
+
+            limits.push(Limit {
+                key,
+                rate,
+                burst,
+                buckets: HashMap::new(),
+                swept: None,
+            });
+        }
+
+        Ok(RateLimits(limits))
+    }
+}
+
+// ===== impl Limit =====
+
+impl Limit {
+    /// Returns the subject's bucket, refilled as of `now`, or `None` if the
+    /// subject has no value for this limit's key.
+    fn bucket(&mut self, subject: &Subject, now: Instant) -> Option<&mut Bucket> {
+        let key = match self.key {
+            Key::Source => subject.source.map(|ip| ip.to_string()),
+            Key::Authority => subject.authority.clone(),
+            Key::Route => subject.route.as_ref().map(|r| r.to_string()),
+        }?;
+
+        let (rate, burst) = (self.rate, self.burst);
+        let key = if self.buckets.contains_key(&Some(key.clone())) {
+            Some(key)
+        } else {
+            if self.buckets.len() >= MAX_BUCKETS {
+                self.sweep(now);
+            }
+            if self.buckets.len() >= MAX_BUCKETS {
+                None
+            } else {
+                Some(key)
+            }
+        };
+
+        let bucket = self.buckets.entry(key).or_insert_with(|| Bucket {
+            tokens: burst,
+            updated: now,
+        });
+        bucket.refill(rate, burst, now);
+        Some(bucket)
+    }
+
+    /// Forgets buckets that have refilled, unless that was done less than
+    /// `SWEEP_INTERVAL_SECS` ago.
+    fn sweep(&mut self, now: Instant) {
+        let due = self.swept
+            .map(|swept| now >= swept + Duration::from_secs(SWEEP_INTERVAL_SECS))
+            .unwrap_or(true);
+        if !due {
+            return;
+        }
+        self.swept = Some(now);
+
+        let (rate, burst) = (self.rate, self.burst);
+        self.buckets.retain(|_, b| {
+            b.refill(rate, burst, now);
+            b.tokens < burst
+        });
+    }
+}
+
+// ===== impl Bucket =====
+
+impl Bucket {
+    fn refill(&mut self, rate: f64, burst: f64, now: Instant) {
+        if now <= self.updated {
+            return;
+        }
+        let elapsed = secs(now - self.updated);
+        self.tokens = (self.tokens + elapsed * rate).min(burst);
+        self.updated = now;
+    }
+}
+
+fn secs(d: Duration) -> f64 {
+    d.as_secs() as f64 + f64::from(d.subsec_nanos()) / 1_000_000_000.0
+}
+
+// ===== impl Subject =====
+
+impl Subject {
+    fn new<B>(req: &http::Request<B>, sensors: &Sensors) -> Self {
+        let source = req.extensions()
+            .get::<Arc<ctx::transport::Server>>()
+            .map(|ctx| ctx.remote.ip());
+        let authority = req.uri().authority_part()
+            .cloned()
+            .or_else(|| h1::authority_from_host(req))
+            .map(|a| a.host().to_ascii_lowercase());
+        Subject {
+            source,
+            authority,
+            route: sensors.route(req),
+        }
+    }
+}
+
+// ===== impl RateLimit =====
+
+impl<S> RateLimit<S> {
+    pub fn new(inner: S, limits: Arc<Mutex<RateLimits>>, sensors: Sensors) -> Self {
+        RateLimit {
+            inner,
+            limits,
+            sensors,
+        }
+    }
+}
+
+impl<S, A, B> Service for RateLimit<S>
+where
+    S: Service<Request = http::Request<A>, Response = http::Response<B>>,
+    B: Default,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = future::Either<S::Future, future::FutureResult<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, request: Self::Request) -> Self::Future {
+        if acquire(&self.limits, &request, &self.sensors) {
+            future::Either::A(self.inner.call(request))
+        } else {
+            future::Either::B(future::ok(refuse(&request)))
+        }
+    }
+}
+
+/// Takes a token for the request from each limit that applies to it, returning
+/// false if the request exceeds a limit.
+pub fn acquire<B>(
+    limits: &Mutex<RateLimits>,
+    request: &http::Request<B>,
+    sensors: &Sensors,
+) -> bool {
+    let acquired = {
+        let mut limits = limits.lock().expect("rate limits lock");
+        if limits.0.is_empty() {
+            Ok(())
+        } else {
+            let subject = Subject::new(request, sensors);
+            limits.acquire(&subject, Instant::now())
+        }
+    };
+
+    match acquired {
+        Ok(()) => true,
+        Err(key) => {
+            debug!(
+                "{} {} exceeds the {:?} rate limit",
+                request.method(),
+                request.uri(),
+                key
+            );
+            if let Some(ctx) = request.extensions().get::<Arc<ctx::transport::Server>>() {
+                sensors.rate_limited(ctx);
+            }
+            false
+        }
+    }
+}
+
+/// Builds the response to a request that exceeds a rate limit.
+fn refuse<A, B: Default>(req: &http::Request<A>) -> http::Response<B> {
+    let is_grpc = req.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .map(|ct| ct.starts_with("application/grpc"))
+        .unwrap_or(false);
+
+    let mut response = http::Response::builder();
+    response.header(CONTENT_LENGTH, "0");
+    if is_grpc {
+        response
+            .status(http::StatusCode::OK)
+            .header(CONTENT_TYPE, "application/grpc")
+            .header("grpc-status", GRPC_RESOURCE_EXHAUSTED.to_string().as_str());
+    } else {
+        response.status(http::StatusCode::TOO_MANY_REQUESTS);
+    }
+    response.body(B::default()).expect("rate limit response is valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn subject(source: [u8; 4], authority: &str, route: Option<&str>) -> Subject {
+        Subject {
+            source: Some(IpAddr::from(source)),
+            authority: Some(authority.to_owned()),
+            route: route.map(Arc::from),
+        }
+    }
+
+    #[test]
+    fn parses_rate_limits() {
+        let limits: RateLimits = "
+            # Comments and blank lines are ignored.
+            source     100    200
+            authority  0.5    1  # trailing comments, too
+        ".parse().unwrap();
+
+        assert_eq!(limits.0.len(), 2);
+        assert_eq!(limits.0[0].key, Key::Source);
+        assert_eq!(limits.0[0].rate, 100.0);
+        assert_eq!(limits.0[0].burst, 200.0);
+        assert_eq!(limits.0[1].key, Key::Authority);
+        assert_eq!(limits.0[1].rate, 0.5);
+    }
+
+    #[test]
+    fn rejects_invalid_rate_limits() {
+        match "source 100".parse::<RateLimits>() {
+            Err(Error::InvalidLine(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "\npeer 100 100".parse::<RateLimits>() {
+            Err(Error::InvalidKey(2)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "identity 100 100".parse::<RateLimits>() {
+            Err(Error::UnsupportedKey(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "source 0 100".parse::<RateLimits>() {
+            Err(Error::InvalidRate(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "source 100 0.5".parse::<RateLimits>() {
+            Err(Error::InvalidRate(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn limits_each_key_separately() {
+        let mut limits: RateLimits = "source 1 2".parse().unwrap();
+        let now = Instant::now();
+
+        let a = subject([10, 0, 0, 1], "web", None);
+        let b = subject([10, 0, 0, 2], "web", None);
+        assert!(limits.acquire(&a, now).is_ok());
+        assert!(limits.acquire(&a, now).is_ok());
+        assert_eq!(limits.acquire(&a, now), Err(Key::Source));
+        assert!(limits.acquire(&b, now).is_ok());
+
+        // Buckets refill at the limit's rate.
+        let later = now + Duration::from_millis(1500);
+        assert!(limits.acquire(&a, later).is_ok());
+        assert_eq!(limits.acquire(&a, later), Err(Key::Source));
+    }
+
+    #[test]
+    fn refused_requests_take_no_tokens() {
+        let mut limits: RateLimits = "
+            authority  1  2
+            route      1  1
+        ".parse().unwrap();
+        let now = Instant::now();
+
+        let get_user = subject([10, 0, 0, 1], "web", Some("get-user"));
+        let unrouted = subject([10, 0, 0, 1], "web", None);
+        assert!(limits.acquire(&get_user, now).is_ok());
+        assert_eq!(limits.acquire(&get_user, now), Err(Key::Route));
+        assert!(limits.acquire(&unrouted, now).is_ok());
+        assert_eq!(limits.acquire(&unrouted, now), Err(Key::Authority));
+    }
+
+    #[test]
+    fn full_limits_are_swept_at_most_once_per_interval() {
+        let mut limits: RateLimits = "source 1 1".parse().unwrap();
+        let now = Instant::now();
+
+        for i in 0..MAX_BUCKETS {
+            let s = subject([10, 0, (i >> 8) as u8, i as u8], "web", None);
+            assert!(limits.acquire(&s, now).is_ok());
+        }
+
+        // The limit is full, so new sources share the overflow bucket.
+        let a = subject([10, 1, 0, 1], "web", None);
+        let b = subject([10, 1, 0, 2], "web", None);
+        assert!(limits.acquire(&a, now).is_ok());
+        assert_eq!(limits.acquire(&b, now), Err(Key::Source));
+
+        // Once the buckets have refilled, the next sweep forgets them.
+        let later = now + Duration::from_secs(2);
+        assert!(limits.acquire(&a, later).is_ok());
+        assert!(limits.acquire(&b, later).is_ok());
+        assert_eq!(limits.0[0].buckets.len(), 2);
+    }
+}
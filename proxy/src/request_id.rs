@@ -0,0 +1,131 @@
+use futures::{Async, Future, Poll};
+use http;
+use http::header::{HeaderName, HeaderValue};
+use rand;
+use tower::Service;
+
+/// The header used to correlate a request across the proxy and the application.
+pub const X_REQUEST_ID: &str = "x-request-id";
+
+/// Ensures that each request carries an `x-request-id` header.
+///
+/// Requests that already have an ID keep it, so that an ID assigned by an
+/// upstream proxy is propagated. Every response, including those that the proxy
+/// makes itself, echoes the request's ID unless it already carries one.
+#[derive(Debug)]
+pub struct RequestId<S> {
+    inner: S,
+    header: HeaderName,
+
+    /// Whether requests without an ID are assigned one.
+    ///
+    /// Only the inbound proxy assigns IDs.
+    assign: bool,
+}
+
+/// Echoes the request's ID in its response.
+#[derive(Debug)]
+pub struct ResponseFuture<F> {
+    inner: F,
+    request_id: Option<HeaderValue>,
+}
+
+// ===== impl RequestId =====
+
+impl<S> RequestId<S> {
+    pub fn new(inner: S, assign: bool) -> Self {
+        RequestId {
+            inner,
+            header: HeaderName::from_static(X_REQUEST_ID),
+            assign,
+        }
+    }
+}
+
+impl<S, A, B> Service for RequestId<S>
+where
+    S: Service<Request = http::Request<A>, Response = http::Response<B>>,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, mut request: Self::Request) -> Self::Future {
+        if self.assign && !request.headers().contains_key(&self.header) {
+            let id = generate();
+            trace!("assigning {}: {:?}", X_REQUEST_ID, id);
+            request.headers_mut().insert(self.header.clone(), id);
+        }
+
+        let request_id = request.headers().get(&self.header).cloned();
+        ResponseFuture {
+            inner: self.inner.call(request),
+            request_id,
+        }
+    }
+}
+
+// ===== impl ResponseFuture =====
+
+impl<F, B> Future for ResponseFuture<F>
+where
+    F: Future<Item = http::Response<B>>,
+{
+    type Item = F::Item;
+    type Error = F::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let mut response = try_ready!(self.inner.poll());
+        if let Some(id) = self.request_id.take() {
+            if !response.headers().contains_key(X_REQUEST_ID) {
+                response.headers_mut().insert(X_REQUEST_ID, id);
+            }
+        }
+        Ok(Async::Ready(response))
+    }
+}
+
+/// Returns the request's ID, if it has one.
+pub fn get<B>(request: &http::Request<B>) -> Option<&HeaderValue> {
+    request.headers().get(X_REQUEST_ID)
+}
+
+/// Generates a random (version 4) UUID.
+fn generate() -> HeaderValue {
+    let (hi, lo): (u64, u64) = rand::random();
+    let hi = (hi & !0xf000) | 0x4000;
+    let lo = (lo & !(0xc << 60)) | (0x8 << 60);
+    let id = format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:012x}",
+        hi >> 32,
+        (hi >> 16) & 0xffff,
+        hi & 0xffff,
+        lo >> 48,
+        lo & 0xffff_ffff_ffff,
+    );
+    HeaderValue::from_str(&id).expect("request id is a valid header value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate;
+
+    #[test]
+    fn generates_v4_uuids() {
+        let id = generate();
+        let id = id.to_str().unwrap();
+        assert_eq!(id.len(), 36);
+
+        let groups = id.split('-').map(str::len).collect::<Vec<_>>();
+        assert_eq!(groups, vec![8, 4, 4, 4, 12]);
+        assert_eq!(&id[14..15], "4");
+        assert!("89ab".contains(&id[19..20]), "{}", id);
+
+        assert_ne!(generate(), generate());
+    }
+}
@@ -0,0 +1,228 @@
+//! Names requests by route so that telemetry is not keyed by raw paths.
+//!
+//! Routes are read from a file with one definition per line:
+//!
+//! ```text
+//! # method  path template       route name
+//! GET       /users/{id}         get-user
+//! *         /static/**          static
+//! ```
+//!
+//! A method of `*` matches any method. In a path template, `{name}` and `*` match
+//! exactly one path segment and a trailing `**` matches any remaining segments.
+//! Definitions are tried in order and the first match wins. Requests that match
+//! no definition are not named, and are aggregated into a default bucket.
+//!
+//! Routes are only read from a local file when the proxy starts. The controller
+//! can't distribute them yet: neither the Destination nor the Telemetry API has a
+//! way to carry route definitions to the proxy.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use http;
+
+/// An ordered set of route definitions.
+#[derive(Clone, Debug, Default)]
+pub struct Routes(Vec<Route>);
+
+#[derive(Clone, Debug)]
+struct Route {
+    method: Option<http::Method>,
+    path: Vec<Segment>,
+    name: Arc<str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    /// Matches exactly one segment.
+    Any,
+    /// Matches all remaining segments, including none.
+    Rest,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The line (1-indexed) does not have exactly three fields.
+    InvalidLine(usize),
+    InvalidMethod(usize),
+    /// The path template is not absolute or has `**` before its last segment.
+    InvalidTemplate(usize),
+}
+
+// ===== impl Routes =====
+
+impl Routes {
+    /// Reads route definitions from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut s = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut s))
+            .map_err(Error::Io)?;
+        s.parse()
+    }
+
+    /// Returns the name of the first route that matches the request.
+    pub fn recognize<B>(&self, req: &http::Request<B>) -> Option<Arc<str>> {
+        self.0
+            .iter()
+            .find(|r| r.matches(req.method(), req.uri().path()))
+            .map(|r| Arc::clone(&r.name))
+    }
+}
+
+impl FromStr for Routes {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut routes = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let lineno = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            if fields.len() != 3 {
+                return Err(Error::InvalidLine(lineno));
+            }
+
+            let method = match fields[0] {
+                "*" => None,
+                m => {
+                    let m = http::Method::from_bytes(m.as_bytes())
+                        .map_err(|_| Error::InvalidMethod(lineno))?;
+                    Some(m)
+                }
+            };
+            let path = parse_template(fields[1]).ok_or(Error::InvalidTemplate(lineno))?;
+
+            routes.push(Route {
+                method,
+                path,
+                name: Arc::from(fields[2]),
+            });
+        }
+
+        Ok(Routes(routes))
+    }
+}
+
+// ===== impl Route =====
+
+impl Route {
+    fn matches(&self, method: &http::Method, path: &str) -> bool {
+        if let Some(ref m) = self.method {
+            if m != method {
+                return false;
+            }
+        }
+
+        let mut segments = path.trim_left_matches('/').split('/');
+        for template in &self.path {
+            match *template {
+                Segment::Rest => return true,
+                Segment::Any => match segments.next() {
+                    Some(s) if !s.is_empty() => {}
+                    _ => return false,
+                },
+                Segment::Literal(ref lit) => {
+                    if segments.next() != Some(lit.as_str()) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        segments.next().is_none()
+    }
+}
+
+fn parse_template(s: &str) -> Option<Vec<Segment>> {
+    if !s.starts_with('/') {
+        return None;
+    }
+
+    let mut path = Vec::new();
+    for seg in s[1..].split('/') {
+        if path.last() == Some(&Segment::Rest) {
+            return None;
+        }
+
+        let seg = match seg {
+            "*" => Segment::Any,
+            "**" => Segment::Rest,
+            s if s.starts_with('{') && s.ends_with('}') => Segment::Any,
+            s => Segment::Literal(s.to_owned()),
+        };
+        path.push(seg);
+    }
+
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use http;
+
+    use super::*;
+
+    fn recognize(routes: &Routes, method: &str, uri: &str) -> Option<String> {
+        let req = http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(())
+            .unwrap();
+        routes.recognize(&req).map(|n| n.to_string())
+    }
+
+    #[test]
+    fn matches_templates_in_order() {
+        let routes: Routes = "
+            # Comments and blank lines are ignored.
+            GET    /users/{id}        get-user
+            *      /users/{id}        user
+            *      /static/**         static
+            POST   /users             create-user
+            *      /                  root
+        ".parse().unwrap();
+
+        assert_eq!(recognize(&routes, "GET", "/users/123"), Some("get-user".into()));
+        assert_eq!(recognize(&routes, "PUT", "/users/123"), Some("user".into()));
+        assert_eq!(recognize(&routes, "POST", "/users"), Some("create-user".into()));
+        assert_eq!(recognize(&routes, "GET", "/static"), Some("static".into()));
+        assert_eq!(recognize(&routes, "GET", "/static/a/b.css"), Some("static".into()));
+        assert_eq!(recognize(&routes, "GET", "/?q=1"), Some("root".into()));
+
+        assert_eq!(recognize(&routes, "GET", "/users"), None);
+        assert_eq!(recognize(&routes, "GET", "/users/"), None);
+        assert_eq!(recognize(&routes, "GET", "/users/123/friends"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        match "GET /users".parse::<Routes>() {
+            Err(Error::InvalidLine(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "\nG(T /users users".parse::<Routes>() {
+            Err(Error::InvalidMethod(2)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "GET users users".parse::<Routes>() {
+            Err(Error::InvalidTemplate(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "GET /a/**/b ab".parse::<Routes>() {
+            Err(Error::InvalidTemplate(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+    }
+}
@@ -0,0 +1,532 @@
+//! Routes outbound requests by local rules, before they are routed by authority.
+//!
+//! Rules are read from a file with one rule per line. Each rule has one or more
+//! conditions and one or more actions, separated by `->`:
+//!
+//! ```text
+//! # conditions                          actions
+//! path=/api/v2 header:x-api=beta     -> route=api-beta
+//! method=GET path=/legacy            -> rewrite-authority=legacy.web rewrite-path=/
+//! header:x-dark-launch=1             -> route=web-dark
+//! path=/search                       -> mirror=search-next
+//! ```
+//!
+//! Conditions are:
+//!
+//! - `method=<method>`: the request has the method.
+//! - `authority=<host>`: the request's authority has the host, ignoring case.
+//! - `path=<prefix>`: the request's path is `prefix` or a path beneath it.
+//! - `header:<name>=<value>`: the request has a header with exactly the value.
+//!
+//! Actions are:
+//!
+//! - `route=<authority>`: the request is sent to `authority`'s destination,
+//!   without being changed.
+//! - `rewrite-authority=<authority>`: the request's authority is replaced, so
+//!   that it is also sent to `authority`'s destination.
+//! - `rewrite-path=<prefix>`: the prefix matched by the rule's `path` condition
+//!   is replaced. This requires a `path` condition.
+//! - `mirror=<authority>`: a copy of the request is also sent to `authority`'s
+//!   destination, and its response is ignored.
+//!
+//! A rule applies if all of its conditions hold. Rules are tried in order and
+//! only the first that applies is used.
+//!
+//! Paths are normalized before they are matched, so that a path cannot avoid a
+//! rule by being spelled differently: percent-encoded unreserved characters are
+//! decoded, repeated slashes are collapsed, and `.` and `..` segments are
+//! resolved. A rewritten path is normalized, too.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::Poll;
+use http;
+use http::header::{HeaderName, HeaderValue, HOST};
+use http::uri::{Authority, Parts, PathAndQuery, Scheme, Uri};
+use tower::Service;
+
+use transparency::h1;
+
+/// An ordered set of routing rules.
+#[derive(Clone, Debug, Default)]
+pub struct Rules(Vec<Rule>);
+
+#[derive(Clone, Debug, Default)]
+struct Rule {
+    conditions: Conditions,
+    route: Option<Authority>,
+    rewrite_authority: Option<Authority>,
+    rewrite_path: Option<String>,
+    mirror: Option<Authority>,
+}
+
+/// The conditions under which a rule applies to a request.
+#[derive(Clone, Debug, Default)]
+pub struct Conditions {
+    method: Option<http::Method>,
+    authority: Option<String>,
+    path: Option<String>,
+    headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+/// A request extension naming the authority to which a rule routed the request.
+///
+/// This takes precedence over the request's own authority when it is routed.
+#[derive(Clone, Debug)]
+pub struct Route(pub Authority);
+
+/// A request extension naming the authority to which a rule mirrors the request.
+#[derive(Clone, Debug)]
+pub struct MirrorTo(pub Authority);
+
+/// Applies the first matching rule to each request before it is routed.
+#[derive(Debug)]
+pub struct ApplyRules<S> {
+    inner: S,
+    rules: Arc<Rules>,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    /// The line (1-indexed) does not have conditions and actions separated by `->`.
+    InvalidLine(usize),
+    InvalidCondition(usize),
+    /// The action is unknown, or is `rewrite-path` without a `path` condition.
+    InvalidAction(usize),
+}
+
+// ===== impl Rules =====
+
+impl Rules {
+    /// Reads rules from the file at `path`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut s = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut s))
+            .map_err(Error::Io)?;
+        s.parse()
+    }
+
+    /// Applies the first rule that matches the request, if any.
+    pub fn apply<B>(&self, req: &mut http::Request<B>) {
+        let rule = match self.0.iter().find(|r| r.conditions.matches(req)) {
+            Some(rule) => rule,
+            None => return,
+        };
+        trace!("applying {:?} to {} {}", rule, req.method(), req.uri());
+
+        if let Some(ref prefix) = rule.rewrite_path {
+            let matched = rule.conditions.path.as_ref().map(|p| p.len()).unwrap_or(0);
+            rewrite_path(req, matched, prefix);
+        }
+        if let Some(ref authority) = rule.rewrite_authority {
+            rewrite_authority(req, authority);
+        }
+        if let Some(ref authority) = rule.route {
+            req.extensions_mut().insert(Route(authority.clone()));
+        }
+        if let Some(ref authority) = rule.mirror {
+            req.extensions_mut().insert(MirrorTo(authority.clone()));
+        }
+    }
+}
+
+impl FromStr for Rules {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rules = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let lineno = i + 1;
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut sides = line.splitn(2, "->");
+            let (conditions, actions) = match (sides.next(), sides.next()) {
+                (Some(c), Some(a)) if !c.trim().is_empty() && !a.trim().is_empty() => (c, a),
+                _ => return Err(Error::InvalidLine(lineno)),
+            };
+
+            let mut rule = Rule::default();
+            for condition in conditions.split_whitespace() {
+                rule.conditions.parse(condition).ok_or(Error::InvalidCondition(lineno))?;
+            }
+            for action in actions.split_whitespace() {
+                rule.action(action).ok_or(Error::InvalidAction(lineno))?;
+            }
+            if rule.rewrite_path.is_some() && rule.conditions.path.is_none() {
+                return Err(Error::InvalidAction(lineno));
+            }
+
+            rules.push(rule);
+        }
+
+        Ok(Rules(rules))
+    }
+}
+
+// ===== impl Conditions =====
+
+impl Conditions {
+    /// Adds the condition `s`, returning `None` if it is invalid.
+    pub fn parse(&mut self, s: &str) -> Option<()> {
+        let mut kv = s.splitn(2, '=');
+        let (key, value) = (kv.next()?, kv.next()?);
+
+        if key.starts_with("header:") {
+            let name = HeaderName::from_bytes(key["header:".len()..].as_bytes()).ok()?;
+            let value = HeaderValue::from_str(value).ok()?;
+            self.headers.push((name, value));
+            return Some(());
+        }
+
+        match key {
+            "method" => {
+                self.method = Some(http::Method::from_bytes(value.as_bytes()).ok()?);
+            }
+            "authority" if !value.is_empty() => {
+                self.authority = Some(value.to_ascii_lowercase());
+            }
+            "path" if value.starts_with('/') => {
+                self.path = Some(value.trim_right_matches('/').to_owned());
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Returns true if there are no conditions, so that every request matches.
+    pub fn is_empty(&self) -> bool {
+        self.method.is_none()
+            && self.authority.is_none()
+            && self.path.is_none()
+            && self.headers.is_empty()
+    }
+
+    /// Returns true if the request satisfies every condition.
+    pub fn matches<B>(&self, req: &http::Request<B>) -> bool {
+        if let Some(ref m) = self.method {
+            if m != req.method() {
+                return false;
+            }
+        }
+
+        if let Some(ref host) = self.authority {
+            let authority = req.uri().authority_part()
+                .cloned()
+                .or_else(|| h1::authority_from_host(req));
+            match authority {
+                Some(ref a) if a.host().eq_ignore_ascii_case(host) => {}
+                _ => return false,
+            }
+        }
+
+        if let Some(ref prefix) = self.path {
+            let path = normalize_path(req.uri().path());
+            let beneath = path.starts_with(prefix.as_str()) && match path[prefix.len()..].chars().next() {
+                None | Some('/') => true,
+                Some(_) => false,
+            };
+            if !beneath {
+                return false;
+            }
+        }
+
+        self.headers.iter().all(|&(ref name, ref value)| {
+            req.headers().get_all(name).iter().any(|v| v == value)
+        })
+    }
+}
+
+// ===== impl Rule =====
+
+impl Rule {
+    fn action(&mut self, s: &str) -> Option<()> {
+        let mut kv = s.splitn(2, '=');
+        let (key, value) = (kv.next()?, kv.next()?);
+        match key {
+            "route" => self.route = Some(value.parse().ok()?),
+            "rewrite-authority" => self.rewrite_authority = Some(value.parse().ok()?),
+            "mirror" => self.mirror = Some(value.parse().ok()?),
+            "rewrite-path" if value.starts_with('/') => {
+                self.rewrite_path = Some(value.trim_right_matches('/').to_owned());
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+/// Replaces the first `matched` bytes of the request's normalized path with
+/// `prefix`.
+fn rewrite_path<B>(req: &mut http::Request<B>, matched: usize, prefix: &str) {
+    let path = {
+        let uri = req.uri();
+        let normalized = normalize_path(uri.path());
+        let mut path = format!("{}{}", prefix, &normalized[matched..]);
+        if path.is_empty() {
+            path.push('/');
+        }
+        if let Some(query) = uri.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        path
+    };
+
+    let mut parts = Parts::from(req.uri().clone());
+    match PathAndQuery::from_shared(Bytes::from(path)) {
+        Ok(path) => parts.path_and_query = Some(path),
+        Err(_) => return,
+    }
+    if let Ok(uri) = Uri::from_parts(parts) {
+        *req.uri_mut() = uri;
+    }
+}
+
+/// Returns the path with percent-encoded unreserved characters decoded,
+/// repeated slashes collapsed, and dot segments resolved.
+pub fn normalize_path(path: &str) -> String {
+    let decoded = decode_unreserved(path);
+
+    let mut segments = Vec::new();
+    let mut trailing_slash = false;
+    for segment in decoded.split('/') {
+        trailing_slash = true;
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            s => {
+                segments.push(s);
+                trailing_slash = false;
+            }
+        }
+    }
+
+    let mut normalized = String::with_capacity(decoded.len());
+    for segment in &segments {
+        normalized.push('/');
+        normalized.push_str(segment);
+    }
+    if trailing_slash || normalized.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
+
+/// Decodes percent-encoded unreserved characters (RFC 3986, section 2.3),
+/// which mean the same whether or not they are encoded. Other escapes are
+/// left as they are.
+fn decode_unreserved(path: &str) -> String {
+    fn hex(b: u8) -> Option<u8> {
+        match b {
+            b'0'...b'9' => Some(b - b'0'),
+            b'a'...b'f' => Some(b - b'a' + 10),
+            b'A'...b'F' => Some(b - b'A' + 10),
+            _ => None,
+        }
+    }
+
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(hi), Some(lo)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                let b = (hi << 4) | lo;
+                let unreserved = match b {
+                    b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' | b'-' | b'.' | b'_' | b'~' => true,
+                    _ => false,
+                };
+                if unreserved {
+                    decoded.push(b);
+                    i += 3;
+                    continue;
+                }
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(decoded).expect("decoding unreserved characters preserves UTF-8")
+}
+
+/// Replaces the authority in the request's URI and `Host` header.
+pub fn rewrite_authority<B>(req: &mut http::Request<B>, authority: &Authority) {
+    let is_h2 = req.version() == http::Version::HTTP_2;
+
+    if is_h2 || req.uri().authority_part().is_some() {
+        let mut parts = Parts::from(req.uri().clone());
+        if parts.scheme.is_none() {
+            parts.scheme = Some(Scheme::HTTP);
+        }
+        parts.authority = Some(authority.clone());
+        if let Ok(uri) = Uri::from_parts(parts) {
+            *req.uri_mut() = uri;
+        }
+    }
+
+    if !is_h2 || req.headers().contains_key(HOST) {
+        let host = HeaderValue::from_str(authority.as_str())
+            .expect("authority is a valid header value");
+        req.headers_mut().insert(HOST, host);
+    }
+}
+
+// ===== impl ApplyRules =====
+
+impl<S> ApplyRules<S> {
+    pub fn new(inner: S, rules: Arc<Rules>) -> Self {
+        ApplyRules { inner, rules }
+    }
+}
+
+impl<S, B> Service for ApplyRules<S>
+where
+    S: Service<Request = http::Request<B>>,
+{
+    type Request = S::Request;
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self) -> Poll<(), Self::Error> {
+        self.inner.poll_ready()
+    }
+
+    fn call(&mut self, mut request: Self::Request) -> Self::Future {
+        self.rules.apply(&mut request);
+        self.inner.call(request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http;
+
+    use super::*;
+
+    fn apply(rules: &Rules, req: &mut http::request::Builder) -> http::Request<()> {
+        let mut req = req.body(()).unwrap();
+        rules.apply(&mut req);
+        req
+    }
+
+    fn route<B>(req: &http::Request<B>) -> Option<&str> {
+        req.extensions().get::<Route>().map(|r| r.0.as_str())
+    }
+
+    #[test]
+    fn applies_first_matching_rule() {
+        let rules: Rules = "
+            # Comments and blank lines are ignored.
+            path=/api/v2 header:x-api=beta  -> route=api-beta
+            method=GET path=/legacy/        -> rewrite-authority=legacy.web rewrite-path=/
+            header:x-dark-launch=1          -> route=web-dark
+            path=/search                    -> mirror=search-next
+        ".parse().unwrap();
+
+        let req = apply(&rules, http::Request::get("/api/v2/users").header("x-api", "beta"));
+        assert_eq!(route(&req), Some("api-beta"));
+        assert_eq!(req.uri().path(), "/api/v2/users");
+
+        let req = apply(&rules, http::Request::get("/api/v20").header("x-api", "beta"));
+        assert_eq!(route(&req), None);
+
+        let req = apply(&rules, &mut http::Request::get("/api/v2"));
+        assert_eq!(route(&req), None);
+
+        let req = apply(&rules, http::Request::get("/legacy/users?id=1").header("host", "web"));
+        assert_eq!(route(&req), None);
+        assert_eq!(req.uri().path(), "/users");
+        assert_eq!(req.uri().query(), Some("id=1"));
+        assert_eq!(req.headers()["host"], "legacy.web");
+
+        let req = apply(&rules, &mut http::Request::get("http://web/legacy"));
+        assert_eq!(req.uri(), "http://legacy.web/");
+
+        let req = apply(&rules, http::Request::post("/legacy/users").header("x-dark-launch", "1"));
+        assert_eq!(route(&req), Some("web-dark"));
+        assert_eq!(req.uri().path(), "/legacy/users");
+
+        let req = apply(&rules, http::Request::get("/search?q=a").header("host", "search"));
+        let mirror = req.extensions().get::<MirrorTo>().map(|m| m.0.as_str());
+        assert_eq!(mirror, Some("search-next"));
+        assert_eq!(route(&req), None);
+        assert_eq!(req.headers()["host"], "search");
+    }
+
+    #[test]
+    fn normalizes_paths() {
+        assert_eq!(normalize_path("/admin"), "/admin");
+        assert_eq!(normalize_path("//admin"), "/admin");
+        assert_eq!(normalize_path("/./admin"), "/admin");
+        assert_eq!(normalize_path("/x/../admin"), "/admin");
+        assert_eq!(normalize_path("/../../admin"), "/admin");
+        assert_eq!(normalize_path("/%61dmin"), "/admin");
+        assert_eq!(normalize_path("/%2e%2E/admin"), "/admin");
+        assert_eq!(normalize_path("/admin//users/"), "/admin/users/");
+        assert_eq!(normalize_path("/admin/."), "/admin/");
+        assert_eq!(normalize_path("/admin/.."), "/");
+        assert_eq!(normalize_path(""), "/");
+
+        // Reserved characters mean something else when they are encoded.
+        assert_eq!(normalize_path("/a%2Fb"), "/a%2Fb");
+        assert_eq!(normalize_path("/a%2"), "/a%2");
+    }
+
+    #[test]
+    fn path_conditions_match_equivalent_paths() {
+        let rules: Rules = "
+            path=/admin      -> route=admin
+            path=/legacy     -> rewrite-path=/v1
+        ".parse().unwrap();
+
+        for path in &["//admin", "/./admin", "/x/../admin", "/%61dmin", "/admin//users"] {
+            let req = apply(&rules, &mut http::Request::get(*path));
+            assert_eq!(route(&req), Some("admin"), "{}", path);
+        }
+
+        let req = apply(&rules, &mut http::Request::get("/adminx"));
+        assert_eq!(route(&req), None);
+
+        let req = apply(&rules, &mut http::Request::get("/x/..//legacy/./users?id=1"));
+        assert_eq!(req.uri().path(), "/v1/users");
+        assert_eq!(req.uri().query(), Some("id=1"));
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        match "path=/a".parse::<Rules>() {
+            Err(Error::InvalidLine(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "\npath=a -> route=b".parse::<Rules>() {
+            Err(Error::InvalidCondition(2)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "host=a -> route=b".parse::<Rules>() {
+            Err(Error::InvalidCondition(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "method=GET -> rewrite-path=/v2".parse::<Rules>() {
+            Err(Error::InvalidAction(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+        match "method=GET -> send=b".parse::<Rules>() {
+            Err(Error::InvalidAction(1)) => {}
+            r => panic!("unexpected: {:?}", r),
+        }
+    }
+}
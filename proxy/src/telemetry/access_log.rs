@@ -0,0 +1,48 @@
+//! Logs a line for each completed request.
+//!
+//! Access logs are emitted at the `info` level under this module's target, so they
+//! may be enabled independently of the proxy's other logs, e.g. with
+//! `CONDUIT_PROXY_LOG=conduit_proxy::telemetry::access_log=info`.
+
+use std::time::Duration;
+
+use log::Level;
+
+use ctx;
+use telemetry::event::Event;
+
+/// Logs `ev` if it completes a request.
+pub fn record(ev: &Event) {
+    if !log_enabled!(Level::Info) {
+        return;
+    }
+
+    match *ev {
+        Event::StreamRequestFail(ref req, ref fail) => {
+            log(req, "-", fail.since_request_open, &format!("{:?}", fail.error));
+        }
+        Event::StreamResponseFail(ref rsp, ref fail) => {
+            let status = rsp.status.as_str();
+            log(&rsp.request, status, fail.since_request_open, &format!("{:?}", fail.error));
+        }
+        Event::StreamResponseEnd(ref rsp, ref end) => {
+            log(&rsp.request, rsp.status.as_str(), end.since_request_open, "-");
+        }
+        _ => {}
+    }
+}
+
+fn log(req: &ctx::http::Request, status: &str, latency: Duration, error: &str) {
+    let proxy = if req.server.proxy.is_inbound() { "inbound" } else { "outbound" };
+    info!(
+        "{} {} {} {} {} {}ms request_id={} error={}",
+        proxy,
+        req.server.remote,
+        req.method,
+        req.uri,
+        status,
+        latency.as_secs() * 1_000 + u64::from(latency.subsec_nanos() / 1_000_000),
+        req.request_id.as_ref().map(String::as_str).unwrap_or("-"),
+        error,
+    );
+}
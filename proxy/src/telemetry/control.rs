@@ -3,13 +3,18 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use futures::{Async, Future, Poll, Stream};
-use futures_mpsc_lossy::Receiver;
+use futures_mpsc_lossy::{self, Receiver, Sender};
+use http;
+use hyper;
 use tokio_core::reactor::{Handle, Timeout};
 
+use super::access_log;
 use super::event::Event;
 use super::metrics::Metrics;
 use super::tap::Taps;
+use super::tracing::{zipkin, Span};
 use conduit_proxy_controller_grpc::telemetry::ReportRequest;
+use config::LatencyBuckets;
 use ctx;
 
 /// A `Control` which has been configured but not initialized.
@@ -23,8 +28,17 @@ pub struct MakeControl {
     flush_interval: Duration,
 
     process_ctx: Arc<ctx::Process>,
+
+    /// The layout of latency histograms.
+    latency_buckets: LatencyBuckets,
+
+    /// Where spans are reported, if tracing is enabled.
+    trace_collector: Option<http::Uri>,
 }
 
+/// The number of spans that may be buffered before being handed to the reporter.
+const SPAN_CAPACITY: usize = 1_000;
+
 /// Handles the receipt of events.
 ///
 /// `Control` exposes a `Stream` that summarizes events accumulated over the past
@@ -33,9 +47,6 @@ pub struct MakeControl {
 /// As `Control` is polled, events are proceesed for the purposes of metrics export _as
 /// well as_ for Tap, which supports subscribing to a stream of events that match
 /// criteria.
-///
-/// # TODO
-/// Limit the amount of memory that may be consumed for metrics aggregation.
 pub struct Control {
     /// Holds the current state of aggregated metrics.
     metrics: Option<Metrics>,
@@ -43,9 +54,18 @@ pub struct Control {
     /// Receives telemetry events.
     rx: Option<Receiver<Event>>,
 
+    /// The number of events that `rx` had rejected as of the last report.
+    events_rejected: usize,
+
     /// Holds the current state of tap observations, as configured by an external source.
     taps: Option<Arc<Mutex<Taps>>>,
 
+    /// Sends completed spans to the span reporter, if tracing is enabled.
+    spans: Option<Sender<Span>>,
+
+    /// The number of spans that `spans` had rejected as of the last report.
+    spans_rejected: usize,
+
     /// Limits the amount of time metrics may be buffered before being flushed to the
     /// controller.
     flush_interval: Duration,
@@ -64,15 +84,21 @@ impl MakeControl {
     /// - `rx`: the `Receiver` side of the channel on which events are sent.
     /// - `flush_interval`: the maximum amount of time between sending reports to the
     ///   controller.
+    /// - `latency_buckets`: the layout of latency histograms.
+    /// - `trace_collector`: the URL to which spans are posted, if any.
     pub(super) fn new(
         rx: Receiver<Event>,
         flush_interval: Duration,
         process_ctx: &Arc<ctx::Process>,
+        latency_buckets: LatencyBuckets,
+        trace_collector: Option<http::Uri>,
     ) -> Self {
         Self {
             rx,
             flush_interval,
             process_ctx: Arc::clone(process_ctx),
+            latency_buckets,
+            trace_collector,
         }
     }
 
@@ -82,6 +108,8 @@ impl MakeControl {
     /// - `handle`: a `Handle` on an event loop that will track the timeout.
     /// - `taps`: shares a `Taps` instance.
     ///
+    /// If a trace collector is configured, a span reporter is spawned on `handle`.
+    ///
     /// # Returns
     /// - `Ok(())` if the timeout was successfully created.
     /// - `Err(io::Error)` if the timeout could not be created.
@@ -90,10 +118,29 @@ impl MakeControl {
 
         let flush_timeout = Timeout::new(self.flush_interval, handle)?;
 
+        let process_ctx = self.process_ctx;
+        let flush_interval = self.flush_interval;
+        let spans = self.trace_collector.map(|collector| {
+            trace!("reporting spans to {}", collector);
+            let (tx, rx) = futures_mpsc_lossy::channel(SPAN_CAPACITY);
+            let reporter = zipkin::Reporter::new(
+                rx,
+                hyper::Uri::from(collector),
+                &process_ctx,
+                flush_interval,
+                handle,
+            );
+            handle.spawn(reporter);
+            tx
+        });
+
         Ok(Control {
-            metrics: Some(Metrics::new(self.process_ctx)),
+            metrics: Some(Metrics::new(process_ctx, &self.latency_buckets)),
             rx: Some(self.rx),
+            events_rejected: 0,
             taps: Some(taps.clone()),
+            spans,
+            spans_rejected: 0,
             flush_interval: self.flush_interval,
             flush_timeout,
         })
@@ -119,22 +166,56 @@ impl Control {
     /// - we have aggregated `flush_bytes` bytes of data,
     /// - we haven't sent a report in `flush_interval` seconds.
     fn flush_report(&mut self) -> Option<ReportRequest> {
-        let metrics = if self.flush_timeout_expired() {
-            trace!("flush timeout expired");
-            self.metrics.as_mut()
-        } else {
-            None
-        };
+        if !self.flush_timeout_expired() {
+            return None;
+        }
+        trace!("flush timeout expired");
 
-        metrics.map(Self::generate_report)
+        let dropped = self.dropped_events();
+        self.log_dropped_spans();
+        self.metrics
+            .as_mut()
+            .map(|m| Self::generate_report(m, dropped))
     }
 
-    fn generate_report(m: &mut Metrics) -> ReportRequest {
+    fn generate_report(m: &mut Metrics, dropped_events: u64) -> ReportRequest {
         let mut r = m.generate_report();
         r.proxy = 0; // 0 = Inbound, 1 = Outbound
+        r.dropped_events = dropped_events;
         r
     }
 
+    /// Returns the number of events that sensors have dropped since the last report
+    /// because the event buffer was full.
+    fn dropped_events(&mut self) -> u64 {
+        let rejected = match self.rx.as_ref() {
+            Some(rx) => rx.rejected(),
+            None => return 0,
+        };
+
+        let dropped = rejected.wrapping_sub(self.events_rejected);
+        self.events_rejected = rejected;
+        if dropped > 0 {
+            warn!("dropped {} telemetry events; the event buffer is full", dropped);
+        }
+        dropped as u64
+    }
+
+    /// Logs the number of spans that were dropped since the last report because
+    /// the span reporter was not keeping up.
+    fn log_dropped_spans(&mut self) {
+        let rejected = match self.spans.as_ref() {
+            Some(spans) => spans.rejected(),
+            None => return,
+        };
+
+        let dropped = rejected.wrapping_sub(self.spans_rejected);
+        self.spans_rejected = rejected;
+        if dropped > 0 {
+            warn!("dropped {} spans; the span buffer is full", dropped);
+        }
+    }
+
     /// Reset the flush timeout.
     fn reset_timeout(&mut self) {
         trace!("flushing in {:?}", self.flush_interval);
@@ -142,18 +223,16 @@ impl Control {
             .reset(Instant::now() + self.flush_interval);
     }
 
+    /// Polls `rx` for the next event.
+    ///
+    /// `rx` is left in place once it has finished, so that events it rejected
+    /// may still be counted in the final report.
     fn recv(&mut self) -> Async<Option<Event>> {
-        match self.rx.take() {
+        match self.rx.as_mut() {
             None => Async::Ready(None),
-            Some(mut rx) => {
+            Some(rx) => {
                 trace!("recv.poll({:?})", rx);
-                match rx.poll().expect("recv telemetry") {
-                    Async::Ready(None) => Async::Ready(None),
-                    ev => {
-                        self.rx = Some(rx);
-                        ev
-                    }
-                }
+                rx.poll().expect("recv telemetry")
             }
         }
     }
@@ -175,6 +254,15 @@ impl Stream for Control {
                         }
                     }
 
+                    access_log::record(&ev);
+
+                    // Spans are reported for both inbound and outbound requests.
+                    if let Some(spans) = self.spans.as_ref() {
+                        if let Some(span) = Span::from_event(&ev) {
+                            let _ = spans.lossy_send(span);
+                        }
+                    }
+
                     // XXX Only inbound events are currently aggregated.
                     if ev.proxy().is_inbound() {
                         if let Some(metrics) = self.metrics.as_mut() {
@@ -186,9 +274,11 @@ impl Stream for Control {
                 }
                 Async::Ready(None) => {
                     warn!("events finished");
+                    let dropped = self.dropped_events();
+                    self.rx = None;
                     let report = self.metrics
                         .take()
-                        .map(|mut m| Self::generate_report(&mut m));
+                        .map(|mut m| Self::generate_report(&mut m, dropped));
                     if report.is_none() {
                         return Ok(Async::Ready(None));
                     }
@@ -227,7 +317,9 @@ impl fmt::Debug for Control {
         fmt.debug_struct("Control")
             .field("metrics", &self.metrics)
             .field("rx", &self.rx)
+            .field("events_rejected", &self.events_rejected)
             .field("taps", &self.taps)
+            .field("spans", &self.spans.is_some())
             .field("flush_interval", &self.flush_interval)
             .field(
                 "flush_timeout",
@@ -16,6 +16,13 @@ pub enum Event {
     StreamResponseOpen(Arc<ctx::http::Response>, StreamResponseOpen),
     StreamResponseFail(Arc<ctx::http::Response>, StreamResponseFail),
     StreamResponseEnd(Arc<ctx::http::Response>, StreamResponseEnd),
+
+    /// A request was refused by a rate limit before it was routed.
+    RequestRateLimited(Arc<ctx::transport::Server>),
+
+    /// The inbound authorization policy decided whether to allow a request, or
+    /// a connection that is not HTTP.
+    Authorized(Arc<ctx::transport::Server>, Authorized),
 }
 
 #[derive(Clone, Debug)]
@@ -31,10 +38,17 @@ pub struct TransportClose {
     //pub tx_bytes: usize,
 }
 
+#[derive(Clone, Debug)]
+pub struct Authorized {
+    pub allowed: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct StreamRequestFail {
     pub since_request_open: Duration,
     pub error: h2::Reason,
+    pub request_bytes_sent: u64,
+    pub request_frames_sent: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -49,6 +63,8 @@ pub struct StreamResponseFail {
     pub error: h2::Reason,
     pub bytes_sent: u64,
     pub frames_sent: u32,
+    pub request_bytes_sent: u64,
+    pub request_frames_sent: u32,
 }
 
 #[derive(Clone, Debug)]
@@ -58,6 +74,8 @@ pub struct StreamResponseEnd {
     pub since_response_open: Duration,
     pub bytes_sent: u64,
     pub frames_sent: u32,
+    pub request_bytes_sent: u64,
+    pub request_frames_sent: u32,
 }
 
 // ===== impl Event =====
@@ -0,0 +1,50 @@
+#![deny(missing_docs)]
+use std::{slice, u64};
+use std::time::Duration;
+
+/// The number of buckets in a connection duration histogram.
+pub const NUM_BUCKETS: usize = 9;
+
+/// The maximum value (inclusive) for each connection duration bucket, in
+/// milliseconds.
+pub const BUCKET_BOUNDS_MS: [u64; NUM_BUCKETS] = [
+    10,
+    100,
+    1_000,
+    10_000,
+    60_000,
+    600_000,
+    3_600_000,
+    36_000_000,
+    // Everything else.
+    u64::MAX,
+];
+
+/// Counts connection lifetimes in buckets bounded by `BUCKET_BOUNDS_MS`.
+///
+/// Unlike a list of per-connection summaries, a histogram's size does not depend
+/// on the number of connections that are closed between reports.
+#[derive(Debug, Default)]
+pub struct Histogram([u32; NUM_BUCKETS]);
+
+// ===== impl Histogram =====
+
+impl Histogram {
+    /// Observe the lifetime of a connection.
+    pub fn observe(&mut self, duration: Duration) {
+        let ms = super::dur_to_ms(duration);
+        let i = BUCKET_BOUNDS_MS.iter()
+            .position(|&max| ms <= max)
+            .expect("the last bucket is unbounded");
+        self.0[i] = self.0[i].saturating_add(1);
+    }
+}
+
+impl<'a> IntoIterator for &'a Histogram {
+    type Item = &'a u32;
+    type IntoIter = slice::Iter<'a, u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
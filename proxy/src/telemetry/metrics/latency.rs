@@ -1,89 +1,168 @@
 #![deny(missing_docs)]
-use std::{ops, slice, u32};
-use std::default::Default;
+use std::{ops, slice, u32, u64};
+use std::sync::Arc;
 use std::time::Duration;
 
-/// The number of buckets in a  latency histogram.
-pub const NUM_BUCKETS: usize = 26;
-
-/// The maximum value (inclusive) for each latency bucket in
-/// tenths of a millisecond.
-pub const BUCKET_BOUNDS: [Latency; NUM_BUCKETS] = [
-    // The controller telemetry server creates 5 sets of 5 linear buckets
-    // each:
-    // TODO: it would be nice if we didn't have to hard-code each
-    //       individual bucket and could use Rust ranges or something.
-    //       However, because we're using a raw fixed size array rather
-    //       than a vector (as we don't ever expect to grow this array
-    //       and thus don't _need_ a vector) we can't concatenate it
-    //       from smaller arrays, making it difficult to construct
-    //       programmatically...
-    // in the controller:
+use config::LatencyBuckets;
+
+/// The maximum number of buckets in a latency histogram.
+///
+/// Every response scope holds a histogram, so the number of buckets is bounded to
+/// bound the memory used by each scope.
+pub const MAX_BUCKETS: usize = 256;
+
+/// The maximum value (inclusive) for each of the default latency buckets, in
+/// microseconds.
+///
+/// These match the buckets that the controller's telemetry server creates: 5 sets
+/// of 5 linear buckets each.
+const DEFAULT_BUCKET_BOUNDS_US: [u64; 25] = [
     // prometheus.LinearBuckets(1, 1, 5),
-    Latency(10),
-    Latency(20),
-    Latency(30),
-    Latency(40),
-    Latency(50),
+    1_000, 2_000, 3_000, 4_000, 5_000,
     // prometheus.LinearBuckets(10, 10, 5),
-    Latency(100),
-    Latency(200),
-    Latency(300),
-    Latency(400),
-    Latency(500),
+    10_000, 20_000, 30_000, 40_000, 50_000,
     // prometheus.LinearBuckets(100, 100, 5),
-    Latency(1_000),
-    Latency(2_000),
-    Latency(3_000),
-    Latency(4_000),
-    Latency(5_000),
+    100_000, 200_000, 300_000, 400_000, 500_000,
     // prometheus.LinearBuckets(1000, 1000, 5),
-    Latency(10_000),
-    Latency(20_000),
-    Latency(30_000),
-    Latency(40_000),
-    Latency(50_000),
+    1_000_000, 2_000_000, 3_000_000, 4_000_000, 5_000_000,
     // prometheus.LinearBuckets(10000, 10000, 5),
-    Latency(100_000),
-    Latency(200_000),
-    Latency(300_000),
-    Latency(400_000),
-    Latency(500_000),
-    // Prometheus implicitly creates a max bucket for everything that
-    // falls outside of the highest-valued bucket, but we need to
-    // create it explicitly.
-    Latency(u32::MAX),
+    10_000_000, 20_000_000, 30_000_000, 40_000_000, 50_000_000,
 ];
 
+/// The layout of a latency histogram.
+///
+/// Each bound is the maximum value (inclusive) of a bucket. The last bucket is
+/// always unbounded: Prometheus implicitly creates a max bucket for everything
+/// that falls outside of the highest-valued bucket, but we need to create it
+/// explicitly.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Buckets(Arc<Vec<Latency>>);
+
 /// A series of latency values and counts.
 #[derive(Debug)]
-pub struct Histogram([u32; NUM_BUCKETS]);
+pub struct Histogram {
+    buckets: Buckets,
+    counts: Vec<u32>,
+}
 
-/// A latency in tenths of a millisecond.
+/// A latency in microseconds.
 #[derive(Debug, Default, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash)]
-pub struct Latency(u32);
+pub struct Latency(u64);
+
+// ===== impl Buckets =====
+
+impl Buckets {
+    /// Builds a layout from inclusive upper bounds, which must be strictly
+    /// increasing.
+    ///
+    /// Returns `None` if the bounds are not increasing or if there are too many.
+    pub fn from_bounds<I>(bounds: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = Latency>,
+    {
+        let mut bounds = bounds.into_iter().collect::<Vec<_>>();
+        if bounds.windows(2).any(|w| w[0] >= w[1]) {
+            return None;
+        }
+        if bounds.last() != Some(&Latency::MAX) {
+            bounds.push(Latency::MAX);
+        }
+        if bounds.len() > MAX_BUCKETS {
+            return None;
+        }
 
+        Some(Buckets(Arc::new(bounds)))
+    }
+
+    /// Builds a log-linear layout, like that of an HDR histogram.
+    ///
+    /// Each power of ten from `min` up to `max` is divided into `per_decade`
+    /// linear buckets, so that the relative error of each bucket is roughly
+    /// constant.
+    pub fn log_linear(min: Latency, max: Latency, per_decade: u32) -> Option<Self> {
+        if min.0 == 0 || min >= max || per_decade == 0 {
+            return None;
+        }
+
+        let mut bounds = vec![min];
+        let mut decade = min.0;
+        'decades: loop {
+            let step = (decade.saturating_mul(9) / u64::from(per_decade)).max(1);
+            for i in 1..(u64::from(per_decade) + 1) {
+                let bound = decade.saturating_add(step.saturating_mul(i));
+                if bound >= max.0 {
+                    break 'decades;
+                }
+                if bounds.len() >= MAX_BUCKETS {
+                    return None;
+                }
+                if Latency(bound) > bounds[bounds.len() - 1] {
+                    bounds.push(Latency(bound));
+                }
+            }
+            decade = decade.saturating_mul(10);
+        }
+        bounds.push(max);
+
+        Self::from_bounds(bounds)
+    }
+
+    /// Returns the number of buckets, including the unbounded bucket.
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Iterates over the buckets' upper bounds.
+    pub fn bounds(&self) -> slice::Iter<Latency> {
+        self.0.iter()
+    }
+}
+
+impl Default for Buckets {
+    fn default() -> Self {
+        let bounds = DEFAULT_BUCKET_BOUNDS_US.iter().map(|&us| Latency(us));
+        Self::from_bounds(bounds).expect("default buckets must be valid")
+    }
+}
+
+impl<'a> From<&'a LatencyBuckets> for Buckets {
+    fn from(config: &'a LatencyBuckets) -> Self {
+        // The configuration is validated when it is parsed.
+        let buckets = match *config {
+            LatencyBuckets::Default => Some(Buckets::default()),
+            LatencyBuckets::Bounds(ref bounds) => {
+                Buckets::from_bounds(bounds.iter().map(|&b| Latency::from(b)))
+            }
+            LatencyBuckets::LogLinear { min, max, per_decade } => {
+                Buckets::log_linear(min.into(), max.into(), per_decade)
+            }
+        };
+        buckets.expect("latency bucket configuration must be valid")
+    }
+}
 
 // ===== impl Histogram =====
 
 impl Histogram {
 
+    /// Construct a new, empty `Histogram` with the given layout.
+    pub fn new(buckets: &Buckets) -> Self {
+        Histogram {
+            buckets: buckets.clone(),
+            counts: vec![0; buckets.len()],
+        }
+    }
+
     /// Observe a measurement
     pub fn observe<I>(&mut self, measurement: I)
     where
         I: Into<Latency>,
     {
         let measurement = measurement.into();
-        let i = BUCKET_BOUNDS.iter()
+        let i = self.buckets.bounds()
             .position(|max| &measurement <= max)
-            .expect("latency value greater than u32::MAX; this shouldn't be \
-                     possible.");
-        self.0[i] += 1;
-    }
-
-    /// Construct a new, empty `Histogram`.
-    pub fn new() -> Self {
-        Histogram([0; NUM_BUCKETS])
+            .expect("the last bucket is unbounded");
+        self.counts[i] = self.counts[i].saturating_add(1);
     }
 
 }
@@ -105,73 +184,101 @@ impl<'a> IntoIterator for &'a Histogram {
     type IntoIter = slice::Iter<'a, u32>;
 
     fn into_iter(self) -> Self::IntoIter {
-        self.0.iter()
+        self.counts.iter()
     }
 
 }
 
-
-impl Default for Histogram {
-    #[inline]
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 // ===== impl Latency =====
 
-
-const SEC_TO_MS: u32 = 1_000;
-const SEC_TO_TENTHS_OF_A_MS: u32 = SEC_TO_MS * 10;
-const TENTHS_OF_MS_TO_NS: u32 =  MS_TO_NS / 10;
+const SEC_TO_US: u64 = 1_000_000;
+const US_TO_NS: u32 = 1_000;
+const TENTHS_OF_MS_TO_US: u64 = 100;
 /// Conversion ratio from milliseconds to nanoseconds.
 pub const MS_TO_NS: u32 = 1_000_000;
 
+impl Latency {
+    /// The largest representable latency, which bounds the last bucket.
+    pub const MAX: Latency = Latency(u64::MAX);
+
+    /// Returns the latency in whole microseconds.
+    pub fn as_micros(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the latency in tenths of a millisecond, rounded up.
+    ///
+    /// Latencies that are too large to represent saturate to `u32::MAX`.
+    pub fn as_tenths_of_ms(&self) -> u32 {
+        let tenths = self.0 / TENTHS_OF_MS_TO_US + (self.0 % TENTHS_OF_MS_TO_US).min(1);
+        if tenths > u64::from(u32::MAX) {
+            u32::MAX
+        } else {
+            tenths as u32
+        }
+    }
+}
+
 impl From<Duration> for Latency {
     fn from(dur: Duration) -> Self {
-        let secs = dur.as_secs();
-        // checked conversion from u64 -> u32.
-        let secs =
-            if secs >= u64::from(u32::MAX) {
-                None
-            } else {
-                Some(secs as u32)
-            };
-        // represent the duration as tenths of a ms.
-        let tenths_of_ms = {
-            let t = secs.and_then(|as_secs|
-                // convert the number of seconds to tenths of a ms, or
-                // None on overflow.
-                as_secs.checked_mul(SEC_TO_TENTHS_OF_A_MS)
-            );
-            let t = t.and_then(|as_tenths_ms| {
-                // convert the subsecond part of the duration (in ns) to
-                // tenths of a millisecond.
-                let subsec_tenths_ms = dur.subsec_nanos() / TENTHS_OF_MS_TO_NS;
-                as_tenths_ms.checked_add(subsec_tenths_ms)
+        let us = dur.as_secs()
+            .checked_mul(SEC_TO_US)
+            .and_then(|us| us.checked_add(u64::from(dur.subsec_nanos() / US_TO_NS)))
+            .unwrap_or_else(|| {
+                debug!("{:?} too large to represent as microseconds!", dur);
+                u64::MAX
             });
-            t.unwrap_or_else(|| {
-                debug!(
-                    "{:?} too large to represent as tenths of a \
-                     millisecond!",
-                     dur
-                );
-                u32::MAX
-            })
-        };
-        Latency(tenths_of_ms)
+        Latency(us)
     }
 }
 
-impl From<u32> for Latency {
-    #[inline]
-    fn from(value: u32) -> Self {
-        Latency(value)
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn default_buckets_match_the_controller() {
+        let tenths = Buckets::default()
+            .bounds()
+            .map(Latency::as_tenths_of_ms)
+            .collect::<Vec<_>>();
+        assert_eq!(tenths.len(), 26);
+        assert_eq!(tenths[0], 10);
+        assert_eq!(tenths[24], 500_000);
+        assert_eq!(tenths[25], u32::MAX);
     }
-}
 
-impl Into<u32> for Latency {
-    fn into(self) -> u32 {
-        self.0
+    #[test]
+    fn log_linear_buckets() {
+        let b = Buckets::log_linear(Latency(1_000), Latency(100_000), 3).unwrap();
+        let us = b.bounds().map(Latency::as_micros).collect::<Vec<_>>();
+        assert_eq!(us, vec![
+            1_000, 4_000, 7_000, 10_000, 40_000, 70_000, 100_000, u64::MAX,
+        ]);
+    }
+
+    #[test]
+    fn observes_with_microsecond_resolution() {
+        let b = Buckets::from_bounds(vec![Latency(50), Latency(100)]).unwrap();
+        let mut h = Histogram::new(&b);
+        h += Duration::new(0, 50_000);
+        h += Duration::new(0, 51_000);
+        h += Duration::from_secs(100);
+        assert_eq!(h.into_iter().cloned().collect::<Vec<_>>(), vec![1, 1, 1]);
+
+        assert_eq!(Latency(50).as_tenths_of_ms(), 1);
+        assert_eq!(Latency(100).as_tenths_of_ms(), 1);
+        assert_eq!(Latency(101).as_tenths_of_ms(), 2);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn rejects_invalid_bounds() {
+        assert!(Buckets::from_bounds(vec![Latency(2), Latency(1)]).is_none());
+        assert!(Buckets::from_bounds((1..1_000).map(Latency)).is_none());
+        assert!(Buckets::log_linear(Latency(0), Latency(10), 1).is_none());
+        assert!(Buckets::log_linear(Latency(10), Latency(10), 1).is_none());
+        assert!(Buckets::log_linear(Latency(1_000), Latency(100_000_000), 200).is_none());
+    }
+}
@@ -1,10 +1,12 @@
+use std::hash::Hash;
+use std::mem;
 use std::net;
 use std::sync::Arc;
 use std::time::Duration;
 use std::{u32, u64};
 
 use http;
-use indexmap::IndexMap;
+use indexmap::{self, IndexMap};
 
 use conduit_proxy_controller_grpc::common::{
     TcpAddress,
@@ -15,33 +17,54 @@ use conduit_proxy_controller_grpc::telemetry::{
     eos_ctx,
     EosCtx,
     EosScope,
+    Overflow,
     ReportRequest,
     RequestCtx,
     RequestScope,
     ResponseCtx,
     ResponseScope,
     ServerTransport,
-    TransportSummary,
 };
+use config::LatencyBuckets;
 use ctx;
+use fully_qualified_authority::FullyQualifiedAuthority;
 use telemetry::event::Event;
 
+mod connection;
 mod latency;
 
+/// The maximum number of distinct sources, destinations, and requests that are
+/// aggregated between reports.
+///
+/// Once a map is full, events for new transport keys are aggregated into an
+/// overflow scope, identified by the `None` key, so that memory use is bounded
+/// regardless of how many peers the proxy sees. Requests for new keys are only
+/// counted in the report's `Overflow`, since a request scope must have a context.
+const MAX_SCOPES: usize = 10_000;
+
 #[derive(Debug)]
 pub struct Metrics {
-    sources: IndexMap<net::IpAddr, TransportStats>,
-    destinations: IndexMap<net::SocketAddr, TransportStats>,
+    sources: IndexMap<Option<net::IpAddr>, TransportStats>,
+    destinations: IndexMap<Option<net::SocketAddr>, TransportStats>,
     requests: IndexMap<RequestKey, RequestStats>,
+    max_scopes: usize,
+    overflow: Overflow,
+    latency_buckets: latency::Buckets,
     process_ctx: Arc<ctx::Process>,
 }
 
+/// Requests are aggregated by route, rather than by path, so that the number of
+/// keys does not grow with the number of distinct URIs.
 #[derive(Debug, Eq, PartialEq, Hash)]
 struct RequestKey {
     source: net::IpAddr,
     destination: net::SocketAddr,
-    uri: http::Uri,
+    authority: Option<http::uri::Authority>,
     method: http::Method,
+    /// Requests that match no route share the `None` bucket.
+    route: Option<Arc<str>>,
+    destination_name: Option<FullyQualifiedAuthority>,
+    mirrored: bool,
 }
 
 #[derive(Debug, Default)]
@@ -50,14 +73,26 @@ struct RequestStats {
     responses: IndexMap<Option<http::StatusCode>, ResponseStats>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 struct ResponseStats {
     ends: IndexMap<End, u32>,
-    /// Response latencies in tenths of a millisecond.
+    /// Response latencies, from the request being opened until the response's
+    /// headers are received (i.e. time-to-first-byte).
     ///
     /// Observed latencies are mapped to a count of the times that
     /// latency value was seen.
     latencies: latency::Histogram,
+    /// Stream durations, from the request being opened until the response's
+    /// stream ends or fails.
+    durations: latency::Histogram,
+    /// The number of response body bytes sent.
+    bytes_sent: u64,
+    /// The number of response body frames sent.
+    frames_sent: u64,
+    /// The number of request body bytes sent before the response ended.
+    request_bytes_sent: u64,
+    /// The number of request body frames sent before the response ended.
+    request_frames_sent: u64,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
@@ -71,7 +106,16 @@ enum End {
 struct TransportStats {
     protocol: Protocol,
     connects: u32,
-    disconnects: Vec<TransportSummary>,
+    disconnects: connection::Histogram,
+    rate_limited: u32,
+    authz_allowed: u32,
+    authz_denied: u32,
+    /// The TLS server name of the first connection to a client transport's
+    /// target.
+    tls_server_name: Option<String>,
+    /// The protocol that the first connection to a client transport's target
+    /// was upgraded to.
+    upgrade: Option<String>,
 }
 
 impl RequestKey {
@@ -79,18 +123,53 @@ impl RequestKey {
         Self {
             source: ctx.server.remote.ip(),
             destination: ctx.client.remote,
-            uri: ctx.uri.clone(),
+            authority: ctx.uri.authority_part().cloned(),
             method: ctx.method.clone(),
+            route: ctx.route.clone(),
+            destination_name: ctx.client.destination_name.clone(),
+            mirrored: ctx.mirrored,
+        }
+    }
+}
+
+impl ResponseStats {
+    fn new(latency_buckets: &latency::Buckets) -> Self {
+        ResponseStats {
+            ends: IndexMap::new(),
+            latencies: latency::Histogram::new(latency_buckets),
+            durations: latency::Histogram::new(latency_buckets),
+            bytes_sent: 0,
+            frames_sent: 0,
+            request_bytes_sent: 0,
+            request_frames_sent: 0,
         }
     }
+
+    /// Records the end of a response stream.
+    fn end(&mut self, end: End, since_request_open: Duration, bytes_sent: u64, frames_sent: u32) {
+        *self.ends.entry(end).or_insert(0) += 1;
+        self.durations += since_request_open;
+        self.bytes_sent = self.bytes_sent.saturating_add(bytes_sent);
+        self.frames_sent = self.frames_sent.saturating_add(u64::from(frames_sent));
+    }
+
+    /// Records the size of the request body sent before a response ended.
+    fn request_body(&mut self, bytes_sent: u64, frames_sent: u32) {
+        self.request_bytes_sent = self.request_bytes_sent.saturating_add(bytes_sent);
+        self.request_frames_sent = self.request_frames_sent
+            .saturating_add(u64::from(frames_sent));
+    }
 }
 
 impl Metrics {
-    pub fn new(process_ctx: Arc<ctx::Process>) -> Self {
+    pub fn new(process_ctx: Arc<ctx::Process>, latency_buckets: &LatencyBuckets) -> Self {
         Metrics {
             sources: IndexMap::new(),
             destinations: IndexMap::new(),
             requests: IndexMap::new(),
+            max_scopes: MAX_SCOPES,
+            overflow: Overflow::default(),
+            latency_buckets: latency_buckets.into(),
             process_ctx,
         }
     }
@@ -103,115 +182,162 @@ impl Metrics {
             Event::TransportClose(ref transport, ref close) => {
                 self.transport(transport)
                     .disconnects
-                    .push(TransportSummary {
-                        duration_ms: dur_to_ms(close.duration),
-                        bytes_sent: 0,
-                    });
+                    .observe(close.duration);
             }
 
             Event::StreamRequestOpen(ref req) => {
-                self.request(req).count += 1;
+                let counted = match self.request(req) {
+                    Some(stats) => {
+                        stats.count += 1;
+                        true
+                    }
+                    None => false,
+                };
+                if !counted {
+                    self.overflow.requests = self.overflow.requests.saturating_add(1);
+                }
             }
             Event::StreamRequestFail(ref req, ref fail) => {
-                let stats = self.request(req)
-                    .responses
-                    .entry(None)
-                    .or_insert_with(Default::default);
-
-                let ends = stats
-                    .ends
-                    .entry(End::Reset(fail.error.into()))
-                    .or_insert_with(Default::default);
-
-                stats.latencies += fail.since_request_open;
-                *ends += 1;
+                let buckets = self.latency_buckets.clone();
+                if let Some(req) = self.request(req) {
+                    let stats = req.responses
+                        .entry(None)
+                        .or_insert_with(|| ResponseStats::new(&buckets));
+
+                    stats.latencies += fail.since_request_open;
+                    stats.end(End::Reset(fail.error.into()), fail.since_request_open, 0, 0);
+                    stats.request_body(fail.request_bytes_sent, fail.request_frames_sent);
+                }
             }
 
             Event::StreamResponseOpen(ref res, ref open) => {
-                self.response(res).latencies += open.since_request_open;
+                if let Some(stats) = self.response(res) {
+                    stats.latencies += open.since_request_open;
+                }
             },
             Event::StreamResponseFail(ref res, ref fail) => {
-                *self.response_end(res, End::Reset(fail.error.into()))
-                    += 1;
+                if let Some(stats) = self.response(res) {
+                    stats.end(
+                        End::Reset(fail.error.into()),
+                        fail.since_request_open,
+                        fail.bytes_sent,
+                        fail.frames_sent,
+                    );
+                    stats.request_body(fail.request_bytes_sent, fail.request_frames_sent);
+                }
             }
             Event::StreamResponseEnd(ref res, ref end) => {
                 let e = end.grpc_status.map(End::Grpc).unwrap_or(End::Other);
-                *self.response_end(res, e) += 1;
+                if let Some(stats) = self.response(res) {
+                    stats.end(
+                        e,
+                        end.since_request_open,
+                        end.bytes_sent,
+                        end.frames_sent,
+                    );
+                    stats.request_body(end.request_bytes_sent, end.request_frames_sent);
+                }
+            }
+
+            Event::RequestRateLimited(ref server) => {
+                let transport = ctx::transport::Ctx::Server(Arc::clone(server));
+                self.transport(&transport).rate_limited += 1;
+            }
+            Event::Authorized(ref server, ref authz) => {
+                let transport = ctx::transport::Ctx::Server(Arc::clone(server));
+                let stats = self.transport(&transport);
+                if authz.allowed {
+                    stats.authz_allowed += 1;
+                } else {
+                    stats.authz_denied += 1;
+                }
             }
         }
     }
 
-    fn request<'a>(&mut self, req: &'a Arc<ctx::http::Request>) -> &mut RequestStats {
-        self.requests
-            .entry(RequestKey::from_ctx(req))
-            .or_insert_with(RequestStats::default)
+    /// Returns the stats for the request's key, or `None` if there are already
+    /// `max_scopes` keys and the request's key is new.
+    fn request<'a>(&mut self, req: &'a Arc<ctx::http::Request>) -> Option<&mut RequestStats> {
+        let key = RequestKey::from_ctx(req);
+        if self.requests.len() >= self.max_scopes && !self.requests.contains_key(&key) {
+            return None;
+        }
+        Some(self.requests.entry(key).or_insert_with(RequestStats::default))
     }
 
-    fn response<'a>(&mut self, res: &'a Arc<ctx::http::Response>) -> &mut ResponseStats {
-        let req = self.request(&res.request);
-        req.responses
+    fn response<'a>(&mut self, res: &'a Arc<ctx::http::Response>) -> Option<&mut ResponseStats> {
+        let buckets = self.latency_buckets.clone();
+        let req = self.request(&res.request)?;
+        let stats = req.responses
             .entry(Some(res.status))
-            .or_insert_with(Default::default)
-    }
-
-    fn response_end<'a>(
-        &mut self,
-        res: &'a Arc<ctx::http::Response>,
-        end: End,
-    ) -> &mut u32 {
-        self.response(res)
-            .ends
-            .entry(end)
-            .or_insert_with(Default::default)
+            .or_insert_with(|| ResponseStats::new(&buckets));
+        Some(stats)
     }
 
     fn transport<'a>(&mut self, transport: &'a ctx::transport::Ctx) -> &mut TransportStats {
         match *transport {
             ctx::transport::Ctx::Server(ref s) => {
                 let source = s.remote.ip();
-                self.sources
-                    .entry(source)
+                let overflow = &mut self.overflow.server_transports;
+                scope(&mut self.sources, source, self.max_scopes, overflow)
                     .or_insert_with(|| TransportStats {
                         protocol: s.protocol,
                         ..TransportStats::default()
                     })
             }
-            ctx::transport::Ctx::Client(ref c) => self.destinations
-                .entry(c.remote)
-                .or_insert_with(|| TransportStats {
-                    protocol: c.protocol,
-                    ..TransportStats::default()
-                })
+            ctx::transport::Ctx::Client(ref c) => {
+                let overflow = &mut self.overflow.client_transports;
+                scope(&mut self.destinations, c.remote, self.max_scopes, overflow)
+                    .or_insert_with(|| TransportStats {
+                        protocol: c.protocol,
+                        tls_server_name: c.tls_server_name.clone(),
+                        upgrade: c.upgrade.clone(),
+                        ..TransportStats::default()
+                    })
+            }
         }
     }
 
     pub fn generate_report(&mut self) -> ReportRequest {
         let histogram_bucket_bounds_tenth_ms: Vec<u32> =
-            latency::BUCKET_BOUNDS.iter()
-                .map(|&latency| latency.into())
+            self.latency_buckets.bounds()
+                .map(latency::Latency::as_tenths_of_ms)
                 .collect();
+        let histogram_bucket_bounds_us: Vec<u64> =
+            self.latency_buckets.bounds()
+                .map(latency::Latency::as_micros)
+                .collect();
+        let connection_duration_bucket_bounds_ms =
+            connection::BUCKET_BOUNDS_MS.to_vec();
 
         let mut server_transports = Vec::new();
         let mut client_transports = Vec::new();
 
         for (ip, stats) in self.sources.drain(..) {
             server_transports.push(ServerTransport {
-                source_ip: Some(ip.into()),
+                source_ip: ip.map(Into::into),
                 connects: stats.connects,
-                disconnects: stats.disconnects,
+                disconnects: Vec::new(),
                 protocol: stats.protocol as i32,
+                disconnect_duration_counts: stats.disconnects.into_iter().cloned().collect(),
+                rate_limited: stats.rate_limited,
+                authz_allowed: stats.authz_allowed,
+                authz_denied: stats.authz_denied,
             })
         }
 
         for (addr, stats) in self.destinations.drain(..) {
             client_transports.push(ClientTransport {
-                target_addr: Some(TcpAddress {
+                target_addr: addr.map(|addr| TcpAddress {
                     ip: Some(addr.ip().into()),
                     port: u32::from(addr.port()),
                 }),
                 connects: stats.connects,
-                disconnects: stats.disconnects,
+                disconnects: Vec::new(),
                 protocol: stats.protocol as i32,
+                disconnect_duration_counts: stats.disconnects.into_iter().cloned().collect(),
+                tls_server_name: stats.tls_server_name.unwrap_or_default(),
+                upgrade: stats.upgrade.unwrap_or_default(),
             });
         }
 
@@ -249,13 +375,20 @@ impl Metrics {
                         .into_iter()
                         .map(|l| *l)
                         .collect(),
+                    stream_duration_counts: res_stats.durations
+                        .into_iter()
+                        .map(|l| *l)
+                        .collect(),
+                    bytes_sent: res_stats.bytes_sent,
+                    frames_sent: res_stats.frames_sent,
+                    request_bytes_sent: res_stats.request_bytes_sent,
+                    request_frames_sent: res_stats.request_frames_sent,
                 });
             }
 
             requests.push(RequestScope {
                 ctx: Some(RequestCtx {
-                    authority: req.uri
-                        .authority_part()
+                    authority: req.authority
                         .map(|a| a.to_string())
                         .unwrap_or_else(String::new),
                     source_ip: Some(req.source.into()),
@@ -263,6 +396,13 @@ impl Metrics {
                         ip: Some(req.destination.ip().into()),
                         port: u32::from(req.destination.port()),
                     }),
+                    route: req.route
+                        .map(|r| r.to_string())
+                        .unwrap_or_else(String::new),
+                    destination_name: req.destination_name
+                        .map(|n| n.without_trailing_dot().to_string())
+                        .unwrap_or_else(String::new),
+                    mirrored: req.mirrored,
                 }),
                 count: stats.count,
                 responses,
@@ -277,10 +417,34 @@ impl Metrics {
             client_transports,
             requests,
             histogram_bucket_bounds_tenth_ms,
+            histogram_bucket_bounds_us,
+            connection_duration_bucket_bounds_ms,
+            overflow: Some(mem::replace(&mut self.overflow, Overflow::default())),
+            // Set by `Control`, which owns the event channel.
+            dropped_events: 0,
         }
     }
 }
 
+/// Returns the entry for `key` in `map`, or the overflow entry if `map` already
+/// holds `max` scopes and `key` is new.
+fn scope<'a, K, V>(
+    map: &'a mut IndexMap<Option<K>, V>,
+    key: K,
+    max: usize,
+    overflow: &mut u32,
+) -> indexmap::map::Entry<'a, Option<K>, V>
+where
+    K: Hash + Eq,
+{
+    let key = Some(key);
+    if map.len() >= max && !map.contains_key(&key) {
+        *overflow = overflow.saturating_add(1);
+        return map.entry(None);
+    }
+    map.entry(key)
+}
+
 fn dur_to_ms(dur: Duration) -> u64 {
     dur.as_secs()
         // note that this could just be saturating addition if we didn't want
@@ -295,3 +459,171 @@ fn dur_to_ms(dur: Duration) -> u64 {
             u64::MAX
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use http;
+
+    use conduit_proxy_controller_grpc::common::Protocol;
+
+    use control::discovery::Metadata;
+    use ctx;
+    use telemetry::event::{self, Event};
+    use super::Metrics;
+
+    fn server(proxy: &Arc<ctx::Proxy>, ip: [u8; 4]) -> Arc<ctx::transport::Ctx> {
+        let local = net::SocketAddr::from(([10, 0, 0, 1], 4143));
+        let remote = net::SocketAddr::from((ip, 55555));
+        let s = ctx::transport::Server::new(proxy, &local, &remote, &None, Protocol::Http);
+        Arc::new(s.into())
+    }
+
+    #[test]
+    fn sources_beyond_the_limit_are_aggregated_as_overflow() {
+        let process = ctx::Process::test("node", "pod", "ns");
+        let proxy = ctx::Proxy::inbound(&process);
+        let mut metrics = Metrics::new(process, &Default::default());
+        metrics.max_scopes = 2;
+
+        for ip in &[[10, 0, 0, 2], [10, 0, 0, 3], [10, 0, 0, 4], [10, 0, 0, 5], [10, 0, 0, 2]] {
+            let t = server(&proxy, *ip);
+            metrics.record_event(&Event::TransportOpen(t.clone()));
+            metrics.record_event(&Event::TransportClose(t, event::TransportClose {
+                clean: true,
+                duration: Duration::from_millis(50),
+            }));
+        }
+
+        let report = metrics.generate_report();
+        assert_eq!(report.server_transports.len(), 3);
+        assert_eq!(report.overflow.as_ref().unwrap().server_transports, 4);
+
+        let known = &report.server_transports[0];
+        assert!(known.source_ip.is_some());
+        assert_eq!(known.connects, 2);
+        // 50ms falls in the (10ms, 100ms] bucket.
+        assert_eq!(known.disconnect_duration_counts[1], 2);
+
+        let overflow = &report.server_transports[2];
+        assert!(overflow.source_ip.is_none());
+        assert_eq!(overflow.connects, 2);
+
+        // Reports reset the overflow counters.
+        let report = metrics.generate_report();
+        assert!(report.server_transports.is_empty());
+        assert_eq!(report.overflow.as_ref().unwrap().server_transports, 0);
+    }
+
+    #[test]
+    fn requests_beyond_the_limit_are_only_counted_as_overflow() {
+        let process = ctx::Process::test("node", "pod", "ns");
+        let proxy = ctx::Proxy::outbound(&process);
+        let mut metrics = Metrics::new(process, &Default::default());
+        metrics.max_scopes = 2;
+
+        let local = net::SocketAddr::from(([10, 0, 0, 1], 4140));
+        let client = ctx::transport::Client::new(
+            &proxy,
+            &net::SocketAddr::from(([10, 0, 0, 9], 80)),
+            &Metadata::default(),
+            &None,
+            &None,
+            &None,
+            Protocol::Http,
+        );
+        let sources = [[10, 0, 0, 2], [10, 0, 0, 3], [10, 0, 0, 4], [10, 0, 0, 2]];
+        for (id, ip) in sources.iter().enumerate() {
+            let remote = net::SocketAddr::from((*ip, 55555));
+            let server = ctx::transport::Server::new(&proxy, &local, &remote, &None, Protocol::Http);
+            let req = http::Request::get("http://web.example.com/").body(()).unwrap();
+            let req = ctx::http::Request::new(&req, &server, &client, id, None, None);
+            let rsp = http::Response::builder().status(200).body(()).unwrap();
+            let rsp = ctx::http::Response::new(&rsp, &req);
+
+            metrics.record_event(&Event::StreamRequestOpen(req));
+            metrics.record_event(&Event::StreamResponseEnd(rsp, event::StreamResponseEnd {
+                grpc_status: None,
+                since_request_open: Duration::from_millis(10),
+                since_response_open: Duration::from_millis(5),
+                bytes_sent: 0,
+                frames_sent: 0,
+                request_bytes_sent: 0,
+                request_frames_sent: 0,
+            }));
+        }
+
+        let report = metrics.generate_report();
+        assert_eq!(report.requests.len(), 2);
+        assert!(report.requests.iter().all(|r| r.ctx.is_some()));
+        assert_eq!(report.requests[0].count, 2);
+        assert_eq!(report.overflow.as_ref().unwrap().requests, 1);
+    }
+
+    #[test]
+    fn request_body_sizes_are_counted_per_response() {
+        let process = ctx::Process::test("node", "pod", "ns");
+        let proxy = ctx::Proxy::outbound(&process);
+        let mut metrics = Metrics::new(process, &Default::default());
+
+        let local = net::SocketAddr::from(([10, 0, 0, 1], 4140));
+        let remote = net::SocketAddr::from(([10, 0, 0, 2], 55555));
+        let server = ctx::transport::Server::new(&proxy, &local, &remote, &None, Protocol::Http);
+        let client = ctx::transport::Client::new(
+            &proxy,
+            &net::SocketAddr::from(([10, 0, 0, 9], 80)),
+            &Metadata::default(),
+            &None,
+            &None,
+            &None,
+            Protocol::Http,
+        );
+        for id in 0..2 {
+            let req = http::Request::post("http://web.example.com/").body(()).unwrap();
+            let req = ctx::http::Request::new(&req, &server, &client, id, None, None);
+            let rsp = http::Response::builder().status(200).body(()).unwrap();
+            let rsp = ctx::http::Response::new(&rsp, &req);
+
+            metrics.record_event(&Event::StreamRequestOpen(req));
+            metrics.record_event(&Event::StreamResponseEnd(rsp, event::StreamResponseEnd {
+                grpc_status: None,
+                since_request_open: Duration::from_millis(10),
+                since_response_open: Duration::from_millis(5),
+                bytes_sent: 5,
+                frames_sent: 1,
+                request_bytes_sent: 12,
+                request_frames_sent: 3,
+            }));
+        }
+
+        let report = metrics.generate_report();
+        let res = &report.requests[0].responses[0];
+        assert_eq!(res.bytes_sent, 10);
+        assert_eq!(res.frames_sent, 2);
+        assert_eq!(res.request_bytes_sent, 24);
+        assert_eq!(res.request_frames_sent, 6);
+    }
+
+    #[test]
+    fn rate_limited_requests_are_counted_by_source() {
+        let process = ctx::Process::test("node", "pod", "ns");
+        let proxy = ctx::Proxy::inbound(&process);
+        let mut metrics = Metrics::new(process, &Default::default());
+
+        for ip in &[[10, 0, 0, 2], [10, 0, 0, 3], [10, 0, 0, 2]] {
+            let local = net::SocketAddr::from(([10, 0, 0, 1], 4143));
+            let remote = net::SocketAddr::from((*ip, 55555));
+            let s = ctx::transport::Server::new(&proxy, &local, &remote, &None, Protocol::Http);
+            metrics.record_event(&Event::RequestRateLimited(s));
+        }
+
+        let report = metrics.generate_report();
+        assert_eq!(report.server_transports.len(), 2);
+        assert_eq!(report.server_transports[0].rate_limited, 2);
+        assert_eq!(report.server_transports[0].connects, 0);
+        assert_eq!(report.server_transports[1].rate_limited, 1);
+    }
+}
@@ -4,14 +4,19 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use futures_mpsc_lossy;
+use http;
 
+use config::LatencyBuckets;
 use ctx;
+use route::Routes;
 
+mod access_log;
 mod control;
 pub mod event;
 mod metrics;
 pub mod sensor;
 pub mod tap;
+pub mod tracing;
 
 pub use self::control::{Control, MakeControl};
 pub use self::event::Event;
@@ -29,6 +34,9 @@ pub use self::sensor::Sensors;
 /// - `capacity`: the number of events to aggregate.
 /// - `flush_interval`: the length of time after which a metrics report should be sent,
 ///   regardless of how many events have been aggregated.
+/// - `latency_buckets`: the layout of response latency histograms.
+/// - `trace_collector`: where to send spans, if tracing is enabled.
+/// - `routes`: names requests so that metrics and taps are keyed by route.
 ///
 /// [`Sensors`]: struct.Sensors.html
 /// [`Control`]: struct.Control.html
@@ -36,9 +44,12 @@ pub fn new(
     process: &Arc<ctx::Process>,
     capacity: usize,
     flush_interval: Duration,
+    latency_buckets: LatencyBuckets,
+    trace_collector: Option<http::Uri>,
+    routes: Routes,
 ) -> (Sensors, MakeControl) {
     let (tx, rx) = futures_mpsc_lossy::channel(capacity);
-    let s = Sensors::new(tx);
-    let c = MakeControl::new(rx, flush_interval, process);
+    let s = Sensors::new(tx, trace_collector.is_some(), routes);
+    let c = MakeControl::new(rx, flush_interval, process, latency_buckets, trace_collector);
     (s, c)
 }
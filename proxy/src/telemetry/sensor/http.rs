@@ -10,7 +10,9 @@ use tower::{NewService, Service};
 use tower_h2::{client, Body};
 
 use ctx;
+use route::Routes;
 use telemetry::event::{self, Event};
+use telemetry::tracing::{Formats, SpanContext};
 
 const GRPC_STATUS: &str = "grpc-status";
 
@@ -19,6 +21,8 @@ pub struct NewHttp<N, A, B> {
     new_service: N,
     handle: super::Handle,
     client_ctx: Arc<ctx::transport::Client>,
+    start_traces: bool,
+    routes: Arc<Routes>,
     _p: PhantomData<(A, B)>,
 }
 
@@ -27,6 +31,8 @@ pub struct Init<F, A, B> {
     future: F,
     handle: super::Handle,
     client_ctx: Arc<ctx::transport::Client>,
+    start_traces: bool,
+    routes: Arc<Routes>,
     _p: PhantomData<(A, B)>,
 }
 
@@ -37,6 +43,8 @@ pub struct Http<S, A, B> {
     service: S,
     handle: super::Handle,
     client_ctx: Arc<ctx::transport::Client>,
+    start_traces: bool,
+    routes: Arc<Routes>,
     _p: PhantomData<(A, B)>,
 }
 
@@ -52,6 +60,24 @@ struct RespondInner {
     handle: super::Handle,
     ctx: Arc<ctx::http::Request>,
     request_open: Instant,
+    request_body: Arc<BodySize>,
+}
+
+/// Wraps a request body, counting the data sent to the server.
+#[derive(Debug)]
+pub struct RequestBody<B> {
+    body: B,
+    size: Option<Arc<BodySize>>,
+}
+
+/// The size of a request body sent so far.
+///
+/// It is shared with the response, so that the request body's size may be
+/// reported when the response ends.
+#[derive(Debug, Default)]
+struct BodySize {
+    bytes: AtomicUsize,
+    frames: AtomicUsize,
 }
 
 #[derive(Default, Debug)]
@@ -69,6 +95,7 @@ struct ResponseBodyInner {
     frames_sent: u32,
     request_open: Instant,
     response_open: Instant,
+    request_body: Arc<BodySize>,
 }
 
 // === NewHttp ===
@@ -78,7 +105,7 @@ where
     A: Body + 'static,
     B: Body + 'static,
     N: NewService<
-        Request = http::Request<A>,
+        Request = http::Request<RequestBody<A>>,
         Response = http::Response<B>,
         Error = client::Error,
     >
@@ -89,12 +116,16 @@ where
         new_service: N,
         handle: &super::Handle,
         client_ctx: &Arc<ctx::transport::Client>,
+        start_traces: bool,
+        routes: &Arc<Routes>,
     ) -> Self {
         Self {
             next_id,
             new_service,
             handle: handle.clone(),
             client_ctx: Arc::clone(client_ctx),
+            start_traces,
+            routes: Arc::clone(routes),
             _p: PhantomData,
         }
     }
@@ -105,13 +136,13 @@ where
     A: Body + 'static,
     B: Body + 'static,
     N: NewService<
-        Request = http::Request<A>,
+        Request = http::Request<RequestBody<A>>,
         Response = http::Response<B>,
         Error = client::Error,
     >
         + 'static,
 {
-    type Request = N::Request;
+    type Request = http::Request<A>;
     type Response = http::Response<ResponseBody<B>>;
     type Error = N::Error;
     type InitError = N::InitError;
@@ -124,6 +155,8 @@ where
             future: self.new_service.new_service(),
             handle: self.handle.clone(),
             client_ctx: Arc::clone(&self.client_ctx),
+            start_traces: self.start_traces,
+            routes: Arc::clone(&self.routes),
             _p: PhantomData,
         }
     }
@@ -136,7 +169,7 @@ where
     A: Body + 'static,
     B: Body + 'static,
     F: Future,
-    F::Item: Service<Request = http::Request<A>, Response = http::Response<B>>,
+    F::Item: Service<Request = http::Request<RequestBody<A>>, Response = http::Response<B>>,
 {
     type Item = Http<F::Item, A, B>;
     type Error = F::Error;
@@ -149,6 +182,8 @@ where
             handle: self.handle.clone(),
             next_id: self.next_id.clone(),
             client_ctx: self.client_ctx.clone(),
+            start_traces: self.start_traces,
+            routes: Arc::clone(&self.routes),
             _p: PhantomData,
         }))
     }
@@ -161,13 +196,13 @@ where
     A: Body + 'static,
     B: Body + 'static,
     S: Service<
-        Request = http::Request<A>,
+        Request = http::Request<RequestBody<A>>,
         Response = http::Response<B>,
         Error = client::Error,
     >
         + 'static,
 {
-    type Request = S::Request;
+    type Request = http::Request<A>;
     type Response = http::Response<ResponseBody<B>>;
     type Error = S::Error;
     type Future = Respond<S::Future, B>;
@@ -181,7 +216,16 @@ where
             None => None,
             Some(ctx) => {
                 let id = self.next_id.fetch_add(1, Ordering::SeqCst);
-                let ctx = ctx::http::Request::new(&req, &ctx, &self.client_ctx, id);
+                let trace = propagate_trace(&mut req, self.start_traces);
+                let route = self.routes.recognize(&req);
+                let ctx = ctx::http::Request::new(
+                    &req,
+                    &ctx,
+                    &self.client_ctx,
+                    id,
+                    trace,
+                    route,
+                );
 
                 self.handle
                     .send(|| Event::StreamRequestOpen(Arc::clone(&ctx)));
@@ -190,11 +234,14 @@ where
                     ctx,
                     handle: self.handle.clone(),
                     request_open: Instant::now(),
+                    request_body: Arc::new(BodySize::default()),
                 })
             }
         };
 
         // TODO measure request lifetime.
+        let size = inner.as_ref().map(|i| Arc::clone(&i.request_body));
+        let req = req.map(|body| RequestBody { body, size });
         let future = self.service.call(req);
 
         Respond {
@@ -205,6 +252,25 @@ where
     }
 }
 
+/// Joins the trace propagated by `req`, starting a new trace if there is none.
+///
+/// The request's headers are updated to name the proxy's span as the parent of the
+/// next hop. `start_traces` is only set when spans are reported; otherwise, the
+/// request is left as it is, since the proxy's span would never be reported.
+fn propagate_trace<A>(req: &mut http::Request<A>, start_traces: bool) -> Option<SpanContext> {
+    if !start_traces {
+        return None;
+    }
+
+    let (span, formats) = match SpanContext::from_headers(req.headers()) {
+        Some(parent) => (parent.child(), Formats::from_headers(req.headers())),
+        None => (SpanContext::root(), Formats::all()),
+    };
+
+    span.inject(formats, req.headers_mut());
+    Some(span)
+}
+
 // === Measured ===
 
 impl<F, B> Future for Respond<F, B>
@@ -225,6 +291,7 @@ where
                         ctx,
                         mut handle,
                         request_open,
+                        request_body,
                     } = i;
 
                     let ctx = ctx::http::Response::new(&rsp, &ctx);
@@ -253,6 +320,8 @@ where
                                     since_response_open: Duration::default(),
                                     bytes_sent: 0,
                                     frames_sent: 0,
+                                    request_bytes_sent: request_body.bytes(),
+                                    request_frames_sent: request_body.frames(),
                                 },
                             )
                         });
@@ -266,6 +335,7 @@ where
                             frames_sent: 0,
                             request_open,
                             response_open: Instant::now(),
+                            request_body,
                         })
                     }
                 });
@@ -290,6 +360,7 @@ where
                             ctx,
                             mut handle,
                             request_open,
+                            request_body,
                         } = i;
 
                         handle.send(|| {
@@ -298,6 +369,8 @@ where
                                 event::StreamRequestFail {
                                     error,
                                     since_request_open: request_open.elapsed(),
+                                    request_bytes_sent: request_body.bytes(),
+                                    request_frames_sent: request_body.frames(),
                                 },
                             )
                         });
@@ -310,6 +383,49 @@ where
     }
 }
 
+// === RequestBody ===
+
+impl<B> Body for RequestBody<B>
+where
+    B: Body + 'static,
+{
+    /// The body chunk type
+    type Data = <B::Data as IntoBuf>::Buf;
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn poll_data(&mut self) -> Poll<Option<Self::Data>, h2::Error> {
+        let frame = try_ready!(self.body.poll_data());
+        let frame = frame.map(|frame| {
+            let frame = frame.into_buf();
+            if let Some(ref size) = self.size {
+                size.frames.fetch_add(1, Ordering::SeqCst);
+                size.bytes.fetch_add(frame.remaining(), Ordering::SeqCst);
+            }
+            frame
+        });
+        Ok(Async::Ready(frame))
+    }
+
+    fn poll_trailers(&mut self) -> Poll<Option<http::HeaderMap>, h2::Error> {
+        self.body.poll_trailers()
+    }
+}
+
+// === BodySize ===
+
+impl BodySize {
+    fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::SeqCst) as u64
+    }
+
+    fn frames(&self) -> u32 {
+        self.frames.load(Ordering::SeqCst) as u32
+    }
+}
+
 // === ResponseBody ===
 
 impl<B> ResponseBody<B> {
@@ -333,7 +449,7 @@ impl<B> ResponseBody<B> {
                             response_open,
                             bytes_sent,
                             frames_sent,
-                            ..
+                            request_body,
                         } = i;
 
                         handle.send(|| {
@@ -345,6 +461,8 @@ impl<B> ResponseBody<B> {
                                     since_response_open: response_open.elapsed(),
                                     bytes_sent,
                                     frames_sent,
+                                    request_bytes_sent: request_body.bytes(),
+                                    request_frames_sent: request_body.frames(),
                                 },
                             )
                         });
@@ -394,6 +512,7 @@ where
                         response_open,
                         bytes_sent,
                         frames_sent,
+                        request_body,
                     } = i;
 
                     handle.send(|| {
@@ -410,6 +529,8 @@ where
                                 since_response_open: response_open.elapsed(),
                                 bytes_sent,
                                 frames_sent,
+                                request_bytes_sent: request_body.bytes(),
+                                request_frames_sent: request_body.frames(),
                             },
                         )
                     })
@@ -10,12 +10,13 @@ use tower::NewService;
 use tower_h2::{client, Body};
 
 use ctx;
+use route::Routes;
 use telemetry::event;
 
 pub mod http;
 mod transport;
 
-pub use self::http::{Http, NewHttp};
+pub use self::http::{Http, NewHttp, RequestBody};
 pub use self::transport::{Connect, Transport};
 
 /// Accepts events from sensors.
@@ -24,7 +25,15 @@ struct Handle(Option<Sender<event::Event>>);
 
 /// Supports the creation of telemetry scopes.
 #[derive(Clone, Debug)]
-pub struct Sensors(Handle);
+pub struct Sensors {
+    handle: Handle,
+
+    /// Whether requests that aren't already part of a trace should start one.
+    start_traces: bool,
+
+    /// Names requests for telemetry.
+    routes: Arc<Routes>,
+}
 
 impl Handle {
     fn send<F>(&mut self, mk: F)
@@ -46,12 +55,20 @@ impl Handle {
 }
 
 impl Sensors {
-    pub(super) fn new(h: Sender<event::Event>) -> Self {
-        Sensors(Handle(Some(h)))
+    pub(super) fn new(h: Sender<event::Event>, start_traces: bool, routes: Routes) -> Self {
+        Sensors {
+            handle: Handle(Some(h)),
+            start_traces,
+            routes: Arc::new(routes),
+        }
     }
 
     pub fn null() -> Sensors {
-        Sensors(Handle(None))
+        Sensors {
+            handle: Handle(None),
+            start_traces: false,
+            routes: Arc::new(Routes::default()),
+        }
     }
 
     pub fn accept<T>(
@@ -65,14 +82,34 @@ impl Sensors {
     {
         debug!("server connection open");
         let ctx = Arc::new(ctx::transport::Ctx::Server(Arc::clone(ctx)));
-        Transport::open(io, opened_at, &self.0, ctx)
+        Transport::open(io, opened_at, &self.handle, ctx)
+    }
+
+    /// Records that a request from `ctx` was refused by a rate limit.
+    pub fn rate_limited(&self, ctx: &Arc<ctx::transport::Server>) {
+        let mut handle = self.handle.clone();
+        handle.send(|| event::Event::RequestRateLimited(Arc::clone(ctx)));
+    }
+
+    /// Records the authorization policy's decision for a request or connection
+    /// from `ctx`.
+    pub fn authorized(&self, ctx: &Arc<ctx::transport::Server>, allowed: bool) {
+        let mut handle = self.handle.clone();
+        handle.send(|| {
+            event::Event::Authorized(Arc::clone(ctx), event::Authorized { allowed })
+        });
+    }
+
+    /// Returns the name of the route that `req` matches, if any.
+    pub fn route<B>(&self, req: &Request<B>) -> Option<Arc<str>> {
+        self.routes.recognize(req)
     }
 
     pub fn connect<C>(&self, connect: C, ctx: &Arc<ctx::transport::Client>) -> Connect<C>
     where
         C: tokio_connect::Connect,
     {
-        Connect::new(connect, &self.0, ctx)
+        Connect::new(connect, &self.handle, ctx)
     }
 
     pub fn http<N, A, B>(
@@ -84,9 +121,20 @@ impl Sensors {
     where
         A: Body + 'static,
         B: Body + 'static,
-        N: NewService<Request = Request<A>, Response = Response<B>, Error = client::Error>
+        N: NewService<
+            Request = Request<RequestBody<A>>,
+            Response = Response<B>,
+            Error = client::Error,
+        >
             + 'static,
     {
-        NewHttp::new(next_id, new_service, &self.0, client_ctx)
+        NewHttp::new(
+            next_id,
+            new_service,
+            &self.handle,
+            client_ctx,
+            self.start_traces,
+            &self.routes,
+        )
     }
 }
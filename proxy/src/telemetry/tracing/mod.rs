@@ -0,0 +1,528 @@
+//! Distributed tracing.
+//!
+//! The proxy joins traces that are propagated with either W3C `traceparent` or B3
+//! headers. Each proxied request is described by a span whose parent is the span
+//! named in the request's headers. The headers are then rewritten so that the next
+//! hop sees the proxy's span as its parent. Only the formats that the request
+//! carried are rewritten, so that the next hop reads the same headers as the proxy
+//! did. Requests that start a trace carry both formats.
+//!
+//! Traces are only joined when spans are reported. Without a collector, the proxy's
+//! spans would never be reported, so propagated headers are left as they are.
+//!
+//! Finished spans are reported to a collector by a [`zipkin::Reporter`].
+//!
+//! [`zipkin::Reporter`]: zipkin/struct.Reporter.html
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use http::{self, HeaderMap};
+use http::header::HeaderValue;
+use rand;
+
+use ctx;
+use telemetry::event::Event;
+
+pub mod zipkin;
+
+const TRACEPARENT: &str = "traceparent";
+const B3: &str = "b3";
+const B3_TRACE_ID: &str = "x-b3-traceid";
+const B3_SPAN_ID: &str = "x-b3-spanid";
+const B3_PARENT_SPAN_ID: &str = "x-b3-parentspanid";
+const B3_SAMPLED: &str = "x-b3-sampled";
+const B3_FLAGS: &str = "x-b3-flags";
+
+/// The only `traceparent` version that is understood.
+const TRACEPARENT_VERSION: &str = "00";
+const TRACEPARENT_FLAG_SAMPLED: u8 = 0x01;
+
+/// A 128-bit trace identifier.
+///
+/// 64-bit B3 trace IDs are represented with `hi` set to zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraceId {
+    hi: u64,
+    lo: u64,
+}
+
+/// Identifies a span within a trace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SpanContext {
+    pub trace_id: TraceId,
+    pub span_id: u64,
+    pub parent_id: Option<u64>,
+
+    /// Indicates whether the span should be reported to the collector.
+    pub sampled: bool,
+}
+
+/// The header formats in which a trace context is propagated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Formats {
+    traceparent: bool,
+    b3_single: bool,
+    b3_multi: bool,
+}
+
+/// Whether a span describes the server or the client side of a request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Server,
+    Client,
+}
+
+/// A finished span, ready to be reported.
+#[derive(Clone, Debug)]
+pub struct Span {
+    pub context: SpanContext,
+    pub kind: Kind,
+    pub name: String,
+    pub timestamp: SystemTime,
+    pub duration: Duration,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    pub tags: Vec<(&'static str, String)>,
+}
+
+// ===== impl TraceId =====
+
+impl TraceId {
+    fn random() -> Self {
+        TraceId {
+            hi: rand::random(),
+            lo: rand::random(),
+        }
+    }
+
+    /// Parses a 16- or 32-character hex-encoded trace ID.
+    fn parse(s: &str) -> Option<Self> {
+        let id = match s.len() {
+            16 => TraceId {
+                hi: 0,
+                lo: parse_hex_u64(s)?,
+            },
+            32 => TraceId {
+                hi: parse_hex_u64(&s[..16])?,
+                lo: parse_hex_u64(&s[16..])?,
+            },
+            _ => return None,
+        };
+
+        // An all-zero trace ID is invalid in both W3C and B3.
+        if id.hi == 0 && id.lo == 0 {
+            return None;
+        }
+
+        Some(id)
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}{:016x}", self.hi, self.lo)
+    }
+}
+
+// ===== impl SpanContext =====
+
+impl SpanContext {
+    /// Starts a new, sampled trace.
+    pub fn root() -> Self {
+        SpanContext {
+            trace_id: TraceId::random(),
+            span_id: rand::random(),
+            parent_id: None,
+            sampled: true,
+        }
+    }
+
+    /// Reads the trace context propagated in `headers`, if any.
+    ///
+    /// `traceparent` takes precedence over the single `b3` header, which takes
+    /// precedence over the `x-b3-*` headers.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        Self::from_traceparent(headers)
+            .or_else(|| Self::from_b3_single(headers))
+            .or_else(|| Self::from_b3_multi(headers))
+    }
+
+    /// Returns a new span in the same trace whose parent is this span.
+    pub fn child(&self) -> Self {
+        SpanContext {
+            trace_id: self.trace_id,
+            span_id: rand::random(),
+            parent_id: Some(self.span_id),
+            sampled: self.sampled,
+        }
+    }
+
+    /// Writes this context into `headers`, in each of the given formats.
+    ///
+    /// Any previously propagated context in those formats is replaced.
+    pub fn inject(&self, formats: Formats, headers: &mut HeaderMap) {
+        let sampled = if self.sampled { "1" } else { "0" };
+
+        if formats.traceparent {
+            let flags = if self.sampled { TRACEPARENT_FLAG_SAMPLED } else { 0 };
+            insert(
+                headers,
+                TRACEPARENT,
+                format!(
+                    "{}-{}-{:016x}-{:02x}",
+                    TRACEPARENT_VERSION, self.trace_id, self.span_id, flags
+                ),
+            );
+        }
+
+        if formats.b3_single {
+            let mut value = format!("{}-{:016x}-{}", self.trace_id, self.span_id, sampled);
+            if let Some(parent) = self.parent_id {
+                value.push_str(&format!("-{:016x}", parent));
+            }
+            insert(headers, B3, value);
+        }
+
+        if formats.b3_multi {
+            headers.remove(B3_FLAGS);
+            insert(headers, B3_TRACE_ID, self.trace_id.to_string());
+            insert(headers, B3_SPAN_ID, format!("{:016x}", self.span_id));
+            match self.parent_id {
+                Some(parent) => insert(headers, B3_PARENT_SPAN_ID, format!("{:016x}", parent)),
+                None => {
+                    headers.remove(B3_PARENT_SPAN_ID);
+                }
+            }
+            headers.insert(B3_SAMPLED, HeaderValue::from_static(sampled));
+        }
+    }
+
+    /// `traceparent: {version}-{trace-id}-{parent-id}-{flags}`
+    fn from_traceparent(headers: &HeaderMap) -> Option<Self> {
+        let value = header_str(headers, TRACEPARENT)?;
+        let mut parts = value.split('-');
+
+        if parts.next()? != TRACEPARENT_VERSION {
+            return None;
+        }
+        let trace_id = parts.next()?;
+        if trace_id.len() != 32 {
+            return None;
+        }
+        let trace_id = TraceId::parse(trace_id)?;
+        let span_id = parse_span_id(parts.next()?)?;
+        let flags = parts.next()?;
+        if flags.len() != 2 || parts.next().is_some() {
+            return None;
+        }
+        let flags = u8::from_str_radix(flags, 16).ok()?;
+
+        Some(SpanContext {
+            trace_id,
+            span_id,
+            parent_id: None,
+            sampled: flags & TRACEPARENT_FLAG_SAMPLED != 0,
+        })
+    }
+
+    /// `b3: {trace-id}-{span-id}[-{sampling}[-{parent-span-id}]]`
+    ///
+    /// A header that only carries a sampling decision does not identify a span.
+    fn from_b3_single(headers: &HeaderMap) -> Option<Self> {
+        let value = header_str(headers, B3)?;
+        let mut parts = value.split('-');
+
+        let trace_id = TraceId::parse(parts.next()?)?;
+        let span_id = parse_span_id(parts.next()?)?;
+        let sampled = match parts.next() {
+            None => true,
+            Some("1") | Some("d") => true,
+            Some("0") => false,
+            Some(_) => return None,
+        };
+
+        Some(SpanContext {
+            trace_id,
+            span_id,
+            parent_id: None,
+            sampled,
+        })
+    }
+
+    fn from_b3_multi(headers: &HeaderMap) -> Option<Self> {
+        let trace_id = TraceId::parse(header_str(headers, B3_TRACE_ID)?)?;
+        let span_id = parse_span_id(header_str(headers, B3_SPAN_ID)?)?;
+
+        // The debug flag implies an accept decision. If no decision was made
+        // upstream, the proxy makes one: it samples.
+        let debug = header_str(headers, B3_FLAGS) == Some("1");
+        let sampled = debug || match header_str(headers, B3_SAMPLED) {
+            Some("0") | Some("false") => false,
+            _ => true,
+        };
+
+        Some(SpanContext {
+            trace_id,
+            span_id,
+            parent_id: None,
+            sampled,
+        })
+    }
+}
+
+// ===== impl Formats =====
+
+impl Formats {
+    /// Every format, for traces that the proxy starts.
+    pub fn all() -> Self {
+        Formats {
+            traceparent: true,
+            b3_single: true,
+            b3_multi: true,
+        }
+    }
+
+    /// The formats of the trace context headers in `headers`.
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Formats {
+            traceparent: headers.contains_key(TRACEPARENT),
+            b3_single: headers.contains_key(B3),
+            b3_multi: headers.contains_key(B3_TRACE_ID) || headers.contains_key(B3_SPAN_ID),
+        }
+    }
+}
+
+// ===== impl Span =====
+
+impl Span {
+    /// Builds a span from an event that completes a request.
+    ///
+    /// Returns `None` if the event does not complete a request or if the request
+    /// is not part of a sampled trace.
+    pub fn from_event(ev: &Event) -> Option<Self> {
+        match *ev {
+            Event::StreamRequestFail(ref req, ref fail) => Self::for_request(
+                req,
+                fail.since_request_open,
+                None,
+                Some(format!("{:?}", fail.error)),
+            ),
+            Event::StreamResponseFail(ref rsp, ref fail) => Self::for_request(
+                &rsp.request,
+                fail.since_request_open,
+                Some(rsp.status),
+                Some(format!("{:?}", fail.error)),
+            ),
+            Event::StreamResponseEnd(ref rsp, ref end) => Self::for_request(
+                &rsp.request,
+                end.since_request_open,
+                Some(rsp.status),
+                None,
+            ),
+            _ => None,
+        }
+    }
+
+    /// Describes a proxied request that has completed.
+    ///
+    /// Returns `None` if the request is not part of a sampled trace.
+    fn for_request(
+        req: &Arc<ctx::http::Request>,
+        since_request_open: Duration,
+        status: Option<http::StatusCode>,
+        error: Option<String>,
+    ) -> Option<Self> {
+        let context = match req.trace {
+            Some(ref t) if t.sampled => *t,
+            _ => return None,
+        };
+
+        let (kind, remote) = if req.server.proxy.is_inbound() {
+            (Kind::Server, req.server.remote)
+        } else {
+            (Kind::Client, req.client.remote)
+        };
+
+        let mut tags = vec![
+            ("http.method", req.method.as_str().to_owned()),
+            ("http.path", req.uri.path().to_owned()),
+        ];
+        if let Some(authority) = req.uri.authority_part() {
+            tags.push(("http.host", authority.as_str().to_owned()));
+        }
+        if let Some(status) = status {
+            tags.push(("http.status_code", status.as_u16().to_string()));
+        }
+        if let Some(error) = error {
+            tags.push(("error", error));
+        }
+
+        // The span's duration is known once the request has completed, so its
+        // start time is inferred from the current time.
+        let timestamp = SystemTime::now() - since_request_open;
+
+        Some(Span {
+            context,
+            kind,
+            name: req.method.as_str().to_lowercase(),
+            timestamp,
+            duration: since_request_open,
+            local: req.server.local,
+            remote,
+            tags,
+        })
+    }
+}
+
+// ===== helpers =====
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+fn insert(headers: &mut HeaderMap, name: &'static str, value: String) {
+    let value = HeaderValue::from_str(&value).expect("trace context is a valid header value");
+    headers.insert(name, value);
+}
+
+fn parse_span_id(s: &str) -> Option<u64> {
+    if s.len() != 16 {
+        return None;
+    }
+    match parse_hex_u64(s)? {
+        0 => None,
+        id => Some(id),
+    }
+}
+
+fn parse_hex_u64(s: &str) -> Option<u64> {
+    if !s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    u64::from_str_radix(s, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderMap;
+    use http::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut h = HeaderMap::new();
+        for &(k, v) in pairs {
+            h.insert(k, HeaderValue::from_static(v));
+        }
+        h
+    }
+
+    #[test]
+    fn parses_traceparent() {
+        let h = headers(&[(
+            "traceparent",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+        )]);
+        let ctx = SpanContext::from_headers(&h).expect("traceparent");
+        assert_eq!(ctx.trace_id.to_string(), "0af7651916cd43dd8448eb211c80319c");
+        assert_eq!(ctx.span_id, 0xb7ad6b7169203331);
+        assert!(ctx.sampled);
+
+        let h = headers(&[(
+            "traceparent",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-00",
+        )]);
+        assert!(!SpanContext::from_headers(&h).unwrap().sampled);
+    }
+
+    #[test]
+    fn rejects_invalid_traceparent() {
+        for v in &[
+            "ff-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+            "00-0af7651916cd43dd-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331",
+            "00-0af7651916cd43dd8448eb211c80319g-b7ad6b7169203331-01",
+        ] {
+            let h = headers(&[("traceparent", v)]);
+            assert_eq!(SpanContext::from_headers(&h), None, "{}", v);
+        }
+    }
+
+    #[test]
+    fn parses_b3_single() {
+        let h = headers(&[("b3", "80f198ee56343ba864fe8b2a57d3eff7-e457b5a2e4d86bd1-0")]);
+        let ctx = SpanContext::from_headers(&h).expect("b3");
+        assert_eq!(ctx.trace_id.to_string(), "80f198ee56343ba864fe8b2a57d3eff7");
+        assert_eq!(ctx.span_id, 0xe457b5a2e4d86bd1);
+        assert!(!ctx.sampled);
+
+        // A sampling decision alone does not identify a span.
+        assert_eq!(SpanContext::from_headers(&headers(&[("b3", "1")])), None);
+    }
+
+    #[test]
+    fn parses_b3_multi() {
+        let h = headers(&[
+            ("x-b3-traceid", "463ac35c9f6413ad"),
+            ("x-b3-spanid", "a2fb4a1d1a96d312"),
+        ]);
+        let ctx = SpanContext::from_headers(&h).expect("b3 multi");
+        assert_eq!(ctx.trace_id.to_string(), "0000000000000000463ac35c9f6413ad");
+        assert_eq!(ctx.span_id, 0xa2fb4a1d1a96d312);
+        assert!(ctx.sampled);
+
+        let h = headers(&[
+            ("x-b3-traceid", "463ac35c9f6413ad"),
+            ("x-b3-spanid", "a2fb4a1d1a96d312"),
+            ("x-b3-sampled", "0"),
+        ]);
+        assert!(!SpanContext::from_headers(&h).unwrap().sampled);
+    }
+
+    #[test]
+    fn injects_child_context() {
+        let parent = SpanContext::root();
+        let child = parent.child();
+        assert_eq!(child.trace_id, parent.trace_id);
+        assert_eq!(child.parent_id, Some(parent.span_id));
+
+        let mut h = HeaderMap::new();
+        child.inject(Formats::all(), &mut h);
+        assert_eq!(
+            h.get("x-b3-parentspanid").unwrap().to_str().unwrap(),
+            format!("{:016x}", parent.span_id)
+        );
+
+        let read = SpanContext::from_headers(&h).expect("injected context");
+        assert_eq!(read.trace_id, child.trace_id);
+        assert_eq!(read.span_id, child.span_id);
+        assert!(read.sampled);
+    }
+
+    #[test]
+    fn injects_only_propagated_formats() {
+        let mut h = headers(&[("b3", "80f198ee56343ba864fe8b2a57d3eff7-e457b5a2e4d86bd1-1")]);
+        let parent = SpanContext::from_headers(&h).expect("b3");
+        let child = parent.child();
+        child.inject(Formats::from_headers(&h), &mut h);
+
+        assert!(h.get("traceparent").is_none());
+        assert!(h.get("x-b3-traceid").is_none());
+        assert_eq!(
+            h.get("b3").unwrap().to_str().unwrap(),
+            format!(
+                "80f198ee56343ba864fe8b2a57d3eff7-{:016x}-1-e457b5a2e4d86bd1",
+                child.span_id
+            )
+        );
+
+        let read = SpanContext::from_headers(&h).expect("injected context");
+        assert_eq!(read.trace_id, parent.trace_id);
+        assert_eq!(read.span_id, child.span_id);
+    }
+}
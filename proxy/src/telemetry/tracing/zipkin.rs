@@ -0,0 +1,263 @@
+//! Reports spans to a collector as Zipkin v2 JSON over HTTP.
+
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use futures::{Async, Future, Poll, Stream};
+use futures_mpsc_lossy::Receiver;
+use hyper;
+use hyper::client::{FutureResponse, HttpConnector};
+use tokio_core::reactor::{Handle, Timeout};
+
+use ctx;
+use json::encode_str;
+use super::{Kind, Span};
+
+/// The maximum number of spans that are buffered while waiting for a flush.
+///
+/// Spans received while the buffer is full are dropped.
+const MAX_PENDING_SPANS: usize = 10_000;
+
+/// Sends batches of spans to a Zipkin-compatible collector.
+///
+/// Spans are accumulated from `rx` and posted to the collector at most once per
+/// `flush_interval`. Only one batch is in flight at a time.
+pub struct Reporter {
+    rx: Option<Receiver<Span>>,
+    pending: Vec<Span>,
+    in_flight: Option<(Instant, FutureResponse)>,
+    client: hyper::Client<HttpConnector>,
+    collector: hyper::Uri,
+    service_name: String,
+    flush_interval: Duration,
+    flush_timeout: Timeout,
+}
+
+impl Reporter {
+    pub fn new(
+        rx: Receiver<Span>,
+        collector: hyper::Uri,
+        process_ctx: &Arc<ctx::Process>,
+        flush_interval: Duration,
+        handle: &Handle,
+    ) -> Self {
+        let client = hyper::Client::configure()
+            .connector(HttpConnector::new(1, handle))
+            .build(handle);
+        let flush_timeout = Timeout::new(flush_interval, handle)
+            .expect("span reporter timeout");
+        let service_name = if process_ctx.scheduled_instance.is_empty() {
+            "conduit-proxy".to_owned()
+        } else {
+            process_ctx.scheduled_instance.clone()
+        };
+
+        Reporter {
+            rx: Some(rx),
+            pending: Vec::new(),
+            in_flight: None,
+            client,
+            collector,
+            service_name,
+            flush_interval,
+            flush_timeout,
+        }
+    }
+
+    /// Drains spans from `rx`, returning true if the channel is closed.
+    fn recv(&mut self) -> bool {
+        let mut dropped = 0;
+        loop {
+            let span = match self.rx.as_mut().map(|rx| rx.poll()) {
+                None | Some(Ok(Async::Ready(None))) | Some(Err(_)) => {
+                    self.rx = None;
+                    break;
+                }
+                Some(Ok(Async::NotReady)) => break,
+                Some(Ok(Async::Ready(Some(span)))) => span,
+            };
+
+            if self.pending.len() < MAX_PENDING_SPANS {
+                self.pending.push(span);
+            } else {
+                dropped += 1;
+            }
+        }
+
+        if dropped > 0 {
+            debug!("dropped {} spans; collector is not keeping up", dropped);
+        }
+
+        self.rx.is_none()
+    }
+
+    fn poll_in_flight(&mut self) {
+        if let Some((t0, mut fut)) = self.in_flight.take() {
+            match fut.poll() {
+                Ok(Async::NotReady) => {
+                    self.in_flight = Some((t0, fut));
+                }
+                Ok(Async::Ready(rsp)) => {
+                    if rsp.status().is_success() {
+                        trace!("spans sent to collector in {:?}", t0.elapsed());
+                    } else {
+                        warn!("collector responded with {}", rsp.status());
+                    }
+                }
+                Err(e) => warn!("collector error: {}", e),
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        let body = encode(&self.pending, &self.service_name);
+        trace!("sending {} spans to {}", self.pending.len(), self.collector);
+        self.pending.clear();
+
+        let mut req = hyper::Request::new(hyper::Method::Post, self.collector.clone());
+        req.headers_mut().set(hyper::header::ContentType::json());
+        req.set_body(body);
+        self.in_flight = Some((Instant::now(), self.client.request(req)));
+    }
+}
+
+impl Future for Reporter {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        let closed = self.recv();
+        self.poll_in_flight();
+
+        let expired = self.flush_timeout
+            .poll()
+            .ok()
+            .map(|r| r.is_ready())
+            .unwrap_or(false);
+
+        if (expired || closed) && self.in_flight.is_none() && !self.pending.is_empty() {
+            self.flush();
+            self.poll_in_flight();
+        }
+
+        if expired {
+            self.flush_timeout.reset(Instant::now() + self.flush_interval);
+            // Register interest in the new deadline.
+            let _ = self.flush_timeout.poll();
+        }
+
+        if closed && self.in_flight.is_none() && self.pending.is_empty() {
+            return Ok(Async::Ready(()));
+        }
+
+        Ok(Async::NotReady)
+    }
+}
+
+/// Encodes spans as a Zipkin v2 JSON array.
+fn encode(spans: &[Span], service_name: &str) -> String {
+    let mut out = String::with_capacity(spans.len() * 512);
+    out.push('[');
+    for (i, span) in spans.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        encode_span(&mut out, span, service_name);
+    }
+    out.push(']');
+    out
+}
+
+fn encode_span(out: &mut String, span: &Span, service_name: &str) {
+    let ctx = &span.context;
+    let timestamp = span.timestamp
+        .duration_since(UNIX_EPOCH)
+        .map(|d| micros(&d))
+        .unwrap_or(0);
+
+    let _ = write!(out, "{{\"traceId\":\"{}\",\"id\":\"{:016x}\"", ctx.trace_id, ctx.span_id);
+    if let Some(parent) = ctx.parent_id {
+        let _ = write!(out, ",\"parentId\":\"{:016x}\"", parent);
+    }
+    let kind = match span.kind {
+        Kind::Server => "SERVER",
+        Kind::Client => "CLIENT",
+    };
+    let _ = write!(out, ",\"kind\":\"{}\",\"name\":", kind);
+    encode_str(out, &span.name);
+    let _ = write!(
+        out,
+        ",\"timestamp\":{},\"duration\":{}",
+        timestamp,
+        micros(&span.duration)
+    );
+
+    out.push_str(",\"localEndpoint\":{\"serviceName\":");
+    encode_str(out, service_name);
+    out.push(',');
+    encode_addr(out, &span.local);
+    out.push_str("},\"remoteEndpoint\":{");
+    encode_addr(out, &span.remote);
+    out.push('}');
+
+    out.push_str(",\"tags\":{");
+    for (i, &(key, ref value)) in span.tags.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        encode_str(out, key);
+        out.push(':');
+        encode_str(out, value);
+    }
+    out.push_str("}}");
+}
+
+fn encode_addr(out: &mut String, addr: &SocketAddr) {
+    let _ = match *addr {
+        SocketAddr::V4(ref a) => write!(out, "\"ipv4\":\"{}\",\"port\":{}", a.ip(), a.port()),
+        SocketAddr::V6(ref a) => write!(out, "\"ipv6\":\"{}\",\"port\":{}", a.ip(), a.port()),
+    };
+}
+
+fn micros(d: &Duration) -> u64 {
+    d.as_secs()
+        .saturating_mul(1_000_000)
+        .saturating_add(u64::from(d.subsec_nanos() / 1_000))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::super::{Kind, Span, SpanContext};
+    use super::encode;
+
+    #[test]
+    fn encodes_zipkin_v2_json() {
+        let parent = SpanContext::root();
+        let context = parent.child();
+        let span = Span {
+            context,
+            kind: Kind::Server,
+            name: "get".into(),
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_500),
+            duration: Duration::from_millis(3),
+            local: ([10, 1, 2, 3], 4143).into(),
+            remote: ([10, 1, 2, 4], 55555).into(),
+            tags: vec![("http.path", "/a\"b".into())],
+        };
+
+        let json = encode(&[span], "web");
+        let expected = format!(
+            "[{{\"traceId\":\"{}\",\"id\":\"{:016x}\",\"parentId\":\"{:016x}\",\
+             \"kind\":\"SERVER\",\"name\":\"get\",\"timestamp\":1500000,\"duration\":3000,\
+             \"localEndpoint\":{{\"serviceName\":\"web\",\"ipv4\":\"10.1.2.3\",\"port\":4143}},\
+             \"remoteEndpoint\":{{\"ipv4\":\"10.1.2.4\",\"port\":55555}},\
+             \"tags\":{{\"http.path\":\"/a\\\"b\"}}}}]",
+            context.trace_id, context.span_id, parent.span_id,
+        );
+        assert_eq!(json, expected);
+    }
+}
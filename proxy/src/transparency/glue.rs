@@ -14,6 +14,7 @@ use tower::{Service, NewService};
 use tower_h2;
 
 use ctx::transport::{Server as ServerCtx};
+use mirror::Tee;
 use super::h1;
 
 /// Glue between `hyper::Body` and `tower_h2::RecvBody`.
@@ -21,6 +22,10 @@ use super::h1;
 pub enum HttpBody {
     Http1(hyper::Body),
     Http2(tower_h2::RecvBody),
+    /// A body that has already been read, such as that of a mirrored request.
+    Buffered(Option<Bytes>),
+    /// A body that is copied for a mirrored request as it is read.
+    Mirrored(Box<HttpBody>, Tee),
 }
 
 /// Glue for `tower_h2::Body`s to be used in hyper.
@@ -83,6 +88,8 @@ impl tower_h2::Body for HttpBody {
         match *self {
             HttpBody::Http1(ref b) => b.is_empty(),
             HttpBody::Http2(ref b) => b.is_end_stream(),
+            HttpBody::Buffered(ref b) => b.as_ref().map(|b| b.is_empty()).unwrap_or(true),
+            HttpBody::Mirrored(ref b, _) => b.is_end_stream(),
         }
     }
 
@@ -100,6 +107,12 @@ impl tower_h2::Body for HttpBody {
                 }
             },
             HttpBody::Http2(ref mut b) => b.poll_data().map(|async| async.map(|opt| opt.map(|data| data.into()))),
+            HttpBody::Buffered(ref mut b) => Ok(Async::Ready(b.take())),
+            HttpBody::Mirrored(ref mut b, ref mut tee) => {
+                let data = b.poll_data();
+                tee.observe(&data);
+                data
+            }
         }
     }
 
@@ -107,6 +120,8 @@ impl tower_h2::Body for HttpBody {
         match *self {
             HttpBody::Http1(_) => Ok(Async::Ready(None)),
             HttpBody::Http2(ref mut b) => b.poll_trailers(),
+            HttpBody::Buffered(_) => Ok(Async::Ready(None)),
+            HttpBody::Mirrored(ref mut b, _) => b.poll_trailers(),
         }
     }
 }
@@ -217,7 +232,7 @@ where
         let mut req: http::Request<hyper::Body> = req.into();
         req.extensions_mut().insert(self.srv_ctx.clone());
 
-        h1::strip_connection_headers(req.headers_mut());
+        h1::strip_connection_headers_except_upgrade(req.headers_mut());
 
         let req = req.map(|b| HttpBody::Http1(b));
         let f = HyperServerSvcFuture {
@@ -93,4 +93,31 @@ pub fn strip_connection_headers(headers: &mut http::HeaderMap) {
     }
 }
 
+/// Strips a request's connection headers, except for a request to upgrade
+/// the connection.
+///
+/// Upgrades are detected before connections are served as HTTP, but an
+/// upgrade on a reused connection is only seen here. It is still passed on,
+/// so that the application may refuse it, though hyper fails the request if
+/// the application switches protocols.
+pub fn strip_connection_headers_except_upgrade(headers: &mut http::HeaderMap) {
+    let is_upgrade = headers.get(http::header::CONNECTION)
+        .and_then(|val| val.to_str().ok())
+        .map(|val| val.split(',').any(|name| name.trim().eq_ignore_ascii_case("upgrade")))
+        .unwrap_or(false);
+    let protocol = if is_upgrade {
+        headers.remove(http::header::UPGRADE)
+    } else {
+        None
+    };
 
+    strip_connection_headers(headers);
+
+    if let Some(protocol) = protocol {
+        headers.insert(http::header::UPGRADE, protocol);
+        headers.insert(
+            http::header::CONNECTION,
+            http::header::HeaderValue::from_static("upgrade"),
+        );
+    }
+}
@@ -4,7 +4,9 @@ pub mod h1;
 mod protocol;
 mod server;
 mod tcp;
+mod tls;
 
 pub use self::client::Client;
 pub use self::glue::HttpBody;
-pub use self::server::Server;
+pub use self::server::{Connections, Server};
+pub use self::tcp::{ServerNames, Tunnels};
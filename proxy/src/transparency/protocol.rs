@@ -1,3 +1,6 @@
+use std::str;
+
+use http;
 use httparse;
 
 /// Known protocols that we proxy transparently.
@@ -7,6 +10,31 @@ pub enum Protocol {
     Http2,
 }
 
+/// An HTTP/1 request asking to upgrade its connection to another protocol,
+/// such as WebSocket.
+#[derive(Debug, PartialEq)]
+pub struct Upgrade {
+    /// The request's `Host`, if it has one.
+    pub host: Option<String>,
+    /// The protocol named by the request's `Upgrade` header.
+    pub protocol: String,
+    /// The length of the request's head, which precedes the upgraded bytes.
+    pub head_len: usize,
+}
+
+/// An HTTP/1 `CONNECT` request, asking for a tunnel to another host.
+#[derive(Debug, PartialEq)]
+pub struct Connect {
+    /// The target of the tunnel, as `host:port`.
+    pub authority: String,
+    /// The length of the request's head, which precedes the tunneled bytes.
+    pub head_len: usize,
+}
+
+/// The most headers that are read from a request when detecting an upgrade or
+/// a `CONNECT`.
+const MAX_HEADERS: usize = 64;
+
 const H2_PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
 
 impl Protocol {
@@ -42,4 +70,167 @@ impl Protocol {
 
         None
     }
+
+    /// Returns whether enough bytes have been peeked to detect the protocol.
+    ///
+    /// Upgrades and `CONNECT` requests can only be detected once the
+    /// request's whole head has been received, so more bytes are needed while
+    /// the bytes so far are the start of an HTTP/1 request.
+    pub fn is_detectable(bytes: &[u8]) -> bool {
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut req = httparse::Request::new(&mut headers);
+        match req.parse(bytes) {
+            Ok(httparse::Status::Partial) => false,
+            _ => true,
+        }
+    }
+}
+
+impl Upgrade {
+    /// Tries to detect a request to upgrade the connection in the peeked bytes.
+    ///
+    /// The request's head must have been received in full.
+    pub fn detect(bytes: &[u8]) -> Option<Upgrade> {
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut req = httparse::Request::new(&mut headers);
+        let head_len = match req.parse(bytes) {
+            Ok(httparse::Status::Complete(n)) => n,
+            _ => return None,
+        };
+
+        let mut host = None;
+        let mut protocol = None;
+        let mut connection_upgrade = false;
+        for header in req.headers.iter() {
+            let value = match str::from_utf8(header.value) {
+                Ok(value) => value.trim(),
+                Err(_) => continue,
+            };
+            if header.name.eq_ignore_ascii_case("host") {
+                host = Some(value.to_owned());
+            } else if header.name.eq_ignore_ascii_case("upgrade") {
+                protocol = Some(value.to_owned());
+            } else if header.name.eq_ignore_ascii_case("connection") {
+                connection_upgrade |= value
+                    .split(',')
+                    .any(|name| name.trim().eq_ignore_ascii_case("upgrade"));
+            }
+        }
+
+        if !connection_upgrade {
+            return None;
+        }
+        protocol.map(|protocol| Upgrade { host, protocol, head_len })
+    }
+
+    /// Parses the head of the upgrade request in the peeked bytes, so that it
+    /// may be checked like a request that is served as HTTP.
+    pub fn request(bytes: &[u8]) -> Option<http::Request<()>> {
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut req = httparse::Request::new(&mut headers);
+        match req.parse(bytes) {
+            Ok(httparse::Status::Complete(_)) => {},
+            _ => return None,
+        }
+
+        let mut head = http::Request::builder();
+        head.method(req.method?)
+            .uri(req.path?)
+            .version(match req.version? {
+                0 => http::Version::HTTP_10,
+                _ => http::Version::HTTP_11,
+            });
+        for header in req.headers.iter() {
+            head.header(header.name, header.value);
+        }
+        head.body(()).ok()
+    }
+}
+
+impl Connect {
+    /// Tries to detect a `CONNECT` request in the peeked bytes.
+    ///
+    /// The request's head must have been received in full.
+    pub fn detect(bytes: &[u8]) -> Option<Connect> {
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut req = httparse::Request::new(&mut headers);
+        let head_len = match req.parse(bytes) {
+            Ok(httparse::Status::Complete(n)) => n,
+            _ => return None,
+        };
+
+        if req.method != Some("CONNECT") {
+            return None;
+        }
+        req.path.map(|authority| Connect {
+            authority: authority.to_owned(),
+            head_len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http;
+
+    use super::{Connect, Protocol, Upgrade};
+
+    #[test]
+    fn detects_upgrade_requests() {
+        let req = b"\
+            GET /chat HTTP/1.1\r\n\
+            Host: chat.example.com\r\n\
+            Connection: keep-alive, Upgrade\r\n\
+            Upgrade: websocket\r\n\
+            \r\n";
+        assert_eq!(Upgrade::detect(req), Some(Upgrade {
+            host: Some("chat.example.com".to_owned()),
+            protocol: "websocket".to_owned(),
+            head_len: req.len(),
+        }));
+
+        let head = Upgrade::request(req).unwrap();
+        assert_eq!(head.method(), "GET");
+        assert_eq!(head.uri(), "/chat");
+        assert_eq!(head.version(), http::Version::HTTP_11);
+        assert_eq!(head.headers()["host"], "chat.example.com");
+        assert_eq!(head.headers()["upgrade"], "websocket");
+    }
+
+    #[test]
+    fn ignores_requests_that_do_not_upgrade() {
+        let req = b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        assert_eq!(Upgrade::detect(req), None);
+
+        // `Upgrade` is only a request to upgrade if `Connection` names it.
+        let req = b"GET / HTTP/1.1\r\nUpgrade: websocket\r\n\r\n";
+        assert_eq!(Upgrade::detect(req), None);
+
+        // Until the whole head is received, an upgrade can't be detected.
+        let req = b"GET / HTTP/1.1\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n";
+        assert_eq!(Upgrade::detect(req), None);
+    }
+
+    #[test]
+    fn waits_for_whole_http1_heads() {
+        assert!(!Protocol::is_detectable(b"GE"));
+        assert!(!Protocol::is_detectable(b"CONNECT api.example.com:443 HTTP/1.1\r\n"));
+        assert!(!Protocol::is_detectable(b"GET /chat HTTP/1.1\r\nHost: chat.example.com\r\n"));
+        assert!(Protocol::is_detectable(b"GET /chat HTTP/1.1\r\nHost: chat.example.com\r\n\r\n"));
+
+        assert!(Protocol::is_detectable(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n"));
+        assert!(Protocol::is_detectable(b"\x16\x03\x01"));
+    }
+
+    #[test]
+    fn detects_connect_requests() {
+        let req = b"CONNECT api.example.com:443 HTTP/1.1\r\nHost: api.example.com:443\r\n\r\n";
+        assert_eq!(Connect::detect(req), Some(Connect {
+            authority: "api.example.com:443".to_owned(),
+            head_len: req.len(),
+        }));
+
+        let req = b"GET / HTTP/1.1\r\nHost: api.example.com\r\n\r\n";
+        assert_eq!(Connect::detect(req), None);
+    }
 }
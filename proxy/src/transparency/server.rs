@@ -1,12 +1,12 @@
 use std::fmt;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use futures::Future;
+use futures::{future, Future};
 use http;
 use hyper;
-use tokio_core::reactor::Handle;
+use tokio_core::reactor::{Handle, Timeout as ReactorTimeout};
 use tower::NewService;
 use tower_h2;
 
@@ -14,11 +14,16 @@ use conduit_proxy_controller_grpc::common;
 use connection::Connection;
 use ctx::Proxy as ProxyCtx;
 use ctx::transport::{Server as ServerCtx};
+use policy::Policy;
+use rate_limit::{self, RateLimits};
+use rules::{self, Rules};
 use telemetry::Sensors;
 use transport::GetOriginalDst;
 use super::glue::{HttpBody, HttpBodyNewSvc, HyperServerSvc};
-use super::protocol::Protocol;
+use super::h1;
+use super::protocol::{Connect, Protocol, Upgrade};
 use super::tcp;
+use super::tls;
 
 /// A protocol-transparent Server!
 ///
@@ -36,11 +41,40 @@ where
     h2: tower_h2::Server<HttpBodyNewSvc<S>, Handle, B>,
     listen_addr: SocketAddr,
     new_service: S,
+    opaque_ports: Vec<u16>,
+    policy: Arc<Policy>,
     proxy_ctx: Arc<ProxyCtx>,
+    rate_limits: Arc<Mutex<RateLimits>>,
+    rules: Arc<Rules>,
     sensors: Sensors,
+    sniff_timeout: Duration,
     tcp: tcp::Proxy,
 }
 
+/// Configures how a `Server` handles connections that are not served as HTTP.
+#[derive(Debug)]
+pub struct Connections {
+    /// Authorizes connections, and requests to upgrade connections, that are
+    /// proxied as TCP.
+    pub policy: Arc<Policy>,
+    /// Limits requests to upgrade connections.
+    pub rate_limits: Arc<Mutex<RateLimits>>,
+    /// Routes requests to upgrade connections.
+    pub rules: Arc<Rules>,
+    /// Connections to these original destination ports are proxied as TCP
+    /// without detecting their protocol.
+    pub opaque_ports: Vec<u16>,
+    /// How long to wait for the first bytes of a connection before proxying it
+    /// as TCP.
+    pub sniff_timeout: Duration,
+    /// How long to wait for a TCP connection to the destination.
+    pub tcp_connect_timeout: Duration,
+    /// Routes TLS connections and upgraded HTTP/1 connections by name, if set.
+    pub tls_server_names: Option<tcp::ServerNames>,
+    /// Accepts `CONNECT` requests, if set.
+    pub tunnels: Option<tcp::Tunnels>,
+}
+
 impl<S, B, G> Server<S, B, G>
 where
     S: NewService<
@@ -59,11 +93,17 @@ where
         sensors: Sensors,
         get_orig_dst: G,
         stack: S,
-        tcp_connect_timeout: Duration,
+        connections: Connections,
         executor: Handle,
     ) -> Self {
         let recv_body_svc = HttpBodyNewSvc::new(stack.clone());
-        let tcp = tcp::Proxy::new(tcp_connect_timeout, sensors.clone(), &executor);
+        let tcp = tcp::Proxy::new(
+            connections.tcp_connect_timeout,
+            sensors.clone(),
+            connections.tls_server_names,
+            connections.tunnels,
+            &executor,
+        );
         Server {
             executor: executor.clone(),
             get_orig_dst,
@@ -71,8 +111,13 @@ where
             h2: tower_h2::Server::new(recv_body_svc, Default::default(), executor),
             listen_addr,
             new_service: stack,
+            opaque_ports: connections.opaque_ports,
+            policy: connections.policy,
             proxy_ctx,
+            rate_limits: connections.rate_limits,
+            rules: connections.rules,
             sensors,
+            sniff_timeout: connections.sniff_timeout,
             tcp,
         }
     }
@@ -83,6 +128,13 @@ where
     /// what protocol the connection is speaking. From there, the connection
     /// will be mapped into respective services, and spawned into an
     /// executor.
+    ///
+    /// Connections to opaque ports, and connections that send nothing before
+    /// the sniff timeout, are proxied as TCP. TLS connections, and HTTP/1
+    /// connections that request an upgrade, are proxied as TCP, too, but may
+    /// be routed by their server name or host. If tunnels are accepted,
+    /// `CONNECT` requests are answered and their connections are proxied as
+    /// TCP to the requested target.
     pub fn serve(&self, connection: Connection, remote_addr: SocketAddr) {
         let opened_at = Instant::now();
 
@@ -91,17 +143,104 @@ where
         let local_addr = connection.local_addr().unwrap_or(self.listen_addr);
         let proxy_ctx = self.proxy_ctx.clone();
 
-        // try to sniff protocol
-        let sniff = [0u8; 32];
+        let is_opaque = orig_dst
+            .map(|dst| self.opaque_ports.contains(&dst.port()))
+            .unwrap_or(false);
+        if is_opaque {
+            trace!("transparency skipping protocol detection for opaque port");
+
+            let srv_ctx = ServerCtx::new(
+                &proxy_ctx,
+                &local_addr,
+                &remote_addr,
+                &orig_dst,
+                common::Protocol::Tcp,
+            );
+            let fut = serve_tcp(
+                connection,
+                opened_at,
+                srv_ctx,
+                tcp::Peeked::Opaque,
+                &self.policy,
+                &self.sensors,
+                &self.tcp,
+            );
+            self.executor.spawn(fut);
+            return;
+        }
+
+        // try to sniff protocol; enough is read to find the server name in
+        // most TLS ClientHellos, and the heads of most HTTP/1 requests, which
+        // may arrive in several segments.
+        let sniff = vec![0u8; 4096];
+        let peek = connection.peek_future(sniff).until(Protocol::is_detectable);
+        let peek = match ReactorTimeout::new(self.sniff_timeout, &self.executor) {
+            Ok(timeout) => peek.with_timeout(timeout),
+            Err(e) => {
+                warn!("failed to set sniff timeout: {}", e);
+                peek
+            }
+        };
         let sensors = self.sensors.clone();
         let h1 = self.h1.clone();
         let h2 = self.h2.clone();
         let tcp = self.tcp.clone();
         let new_service = self.new_service.clone();
-        let fut = connection
-            .peek_future(sniff)
+        let policy = self.policy.clone();
+        let rate_limits = self.rate_limits.clone();
+        let rules = self.rules.clone();
+        let fut = peek
             .map_err(|_| ())
             .and_then(move |(connection, sniff, n)| -> Box<Future<Item=(), Error=()>> {
+                let connect = if tcp.accepts_tunnels() {
+                    Connect::detect(&sniff[..n])
+                } else {
+                    None
+                };
+                if let Some(connect) = connect {
+                    trace!("transparency detected CONNECT to {}", connect.authority);
+
+                    let srv_ctx = ServerCtx::new(
+                        &proxy_ctx,
+                        &local_addr,
+                        &remote_addr,
+                        &orig_dst,
+                        common::Protocol::Tcp,
+                    );
+                    let peeked = tcp::Peeked::Connect(connect);
+                    return serve_tcp(connection, opened_at, srv_ctx, peeked, &policy, &sensors, &tcp);
+                }
+
+                // Hyper can't hand over upgraded connections, so requests to
+                // upgrade are proxied as TCP, from the request onwards, once
+                // the server agrees to the upgrade. The request is still
+                // authorized, limited and routed like any other, though.
+                if let Some(mut upgrade) = Upgrade::detect(&sniff[..n]) {
+                    trace!("transparency detected HTTP/1 upgrade to {}", upgrade.protocol);
+
+                    let srv_ctx = ServerCtx::new(
+                        &proxy_ctx,
+                        &local_addr,
+                        &remote_addr,
+                        &orig_dst,
+                        common::Protocol::Tcp,
+                    );
+                    let checked = check_upgrade(
+                        &mut upgrade,
+                        &sniff[..n],
+                        &srv_ctx,
+                        &policy,
+                        &rate_limits,
+                        &rules,
+                        &sensors,
+                    );
+                    if let Err(response) = checked {
+                        return tcp::refuse(connection, upgrade.head_len, response);
+                    }
+                    let peeked = tcp::Peeked::Upgrade(upgrade);
+                    return serve_tcp(connection, opened_at, srv_ctx, peeked, &policy, &sensors, &tcp);
+                }
+
                 if let Some(proto) = Protocol::detect(&sniff[..n]) {
                     let srv_ctx = ServerCtx::new(
                         &proxy_ctx,
@@ -147,10 +286,15 @@ where
                         common::Protocol::Tcp,
                     );
 
-                    // record telemetry
-                    let tcp_in = sensors.accept(connection, opened_at, &srv_ctx);
+                    let peeked = match tls::server_name(&sniff[..n]) {
+                        Some(name) => {
+                            trace!("transparency detected TLS for {}", name);
+                            tcp::Peeked::Tls(name)
+                        }
+                        None => tcp::Peeked::Opaque,
+                    };
 
-                    tcp.serve(tcp_in, srv_ctx)
+                    serve_tcp(connection, opened_at, srv_ctx, peeked, &policy, &sensors, &tcp)
                 }
             });
 
@@ -158,3 +302,81 @@ where
     }
 }
 
+/// Checks a request to upgrade a connection as if it were served as HTTP.
+///
+/// The request is refused if the policy denies it or it exceeds a rate limit.
+/// Otherwise, the connection is routed to the authority that the rules route
+/// the request to. Rules can't change the request itself, since it is proxied
+/// as it was received, so their other actions are ignored.
+fn check_upgrade(
+    upgrade: &mut Upgrade,
+    peeked: &[u8],
+    srv_ctx: &Arc<ServerCtx>,
+    policy: &Policy,
+    rate_limits: &Mutex<RateLimits>,
+    rules: &Rules,
+    sensors: &Sensors,
+) -> Result<(), &'static [u8]> {
+    let mut request = match Upgrade::request(peeked) {
+        Some(request) => request,
+        None => {
+            debug!("upgrade request from {} could not be parsed", srv_ctx.remote);
+            return Err(tcp::BAD_REQUEST);
+        }
+    };
+    request.extensions_mut().insert(Arc::clone(srv_ctx));
+
+    if !policy.is_empty() {
+        let allowed = policy.allows_request(srv_ctx, &request);
+        sensors.authorized(srv_ctx, allowed);
+        if !allowed {
+            info!("denied {} {} from {}", request.method(), request.uri(), srv_ctx.remote);
+            return Err(tcp::FORBIDDEN);
+        }
+        debug!("allowed {} {} from {}", request.method(), request.uri(), srv_ctx.remote);
+    }
+
+    if !rate_limit::acquire(rate_limits, &request, sensors) {
+        return Err(tcp::TOO_MANY_REQUESTS);
+    }
+
+    rules.apply(&mut request);
+    let routed = request.extensions()
+        .get::<rules::Route>()
+        .map(|route| route.0.clone())
+        .or_else(|| h1::authority_from_host(&request));
+    if let Some(authority) = routed {
+        upgrade.host = Some(authority.as_str().to_owned());
+    }
+
+    Ok(())
+}
+
+/// Proxies a connection as TCP, unless the policy denies it.
+fn serve_tcp(
+    connection: Connection,
+    opened_at: Instant,
+    srv_ctx: Arc<ServerCtx>,
+    peeked: tcp::Peeked,
+    policy: &Policy,
+    sensors: &Sensors,
+    tcp: &tcp::Proxy,
+) -> Box<Future<Item=(), Error=()>> {
+    // HTTP requests are authorized individually, but TCP
+    // connections may only be authorized as a whole.
+    if !policy.is_empty() {
+        let allowed = policy.allows_connection(&srv_ctx);
+        sensors.authorized(&srv_ctx, allowed);
+        if !allowed {
+            info!("denied connection from {}", srv_ctx.remote);
+            return Box::new(future::ok(()));
+        }
+        debug!("allowed connection from {}", srv_ctx.remote);
+    }
+
+    // record telemetry
+    let tcp_in = sensors.accept(connection, opened_at, &srv_ctx);
+
+    tcp.serve(tcp_in, srv_ctx, peeked)
+}
+
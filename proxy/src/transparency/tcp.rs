@@ -1,18 +1,37 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
 use std::io;
+use std::mem;
+use std::net::SocketAddr;
+use std::rc::Rc;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use bytes::{Buf, BufMut};
 use futures::{future, Async, Future, Poll};
-use tokio_connect::Connect;
-use tokio_core::reactor::Handle;
-use tokio_io::{AsyncRead, AsyncWrite};
+use http;
+use httparse;
+use indexmap::IndexMap;
+use rand::{self, Rng};
+use tokio_connect::Connect as TokioConnect;
+use tokio_core::reactor::{Handle, Timeout as ReactorTimeout};
+use tokio_io::{self, AsyncRead, AsyncWrite};
+use tower::Service;
+use tower_discover::{Change, Discover};
 
 use conduit_proxy_controller_grpc::common;
+use connection::Connection;
+use control;
+use control::discovery::{self, Metadata, Watch};
+use convert::TryFrom;
 use ctx::transport::{Client as ClientCtx, Server as ServerCtx};
-use telemetry::Sensors;
+use dns;
+use fully_qualified_authority::FullyQualifiedAuthority;
+use telemetry::{sensor, Sensors};
 use timeout::Timeout;
-use transport;
+use transport::{self, Host, HostAndPort};
+use super::protocol::{Connect, Upgrade};
 
 /// TCP Server Proxy
 #[derive(Debug, Clone)]
@@ -20,23 +39,138 @@ pub struct Proxy {
     connect_timeout: Duration,
     executor: Handle,
     sensors: Sensors,
+    server_names: Option<ServerNames>,
+    tunnels: Option<Tunnels>,
+}
+
+/// What is known of a TCP connection from the bytes peeked from it.
+#[derive(Debug)]
+pub enum Peeked {
+    /// Nothing is known.
+    Opaque,
+    /// A TLS connection, with the server name indicated by its client.
+    Tls(String),
+    /// An HTTP/1 connection that is being upgraded to another protocol.
+    Upgrade(Upgrade),
+    /// An HTTP/1 connection asking for a tunnel with `CONNECT`.
+    Connect(Connect),
+}
+
+/// Accepts `CONNECT` requests, opening tunnels to the targets they name.
+#[derive(Clone, Debug)]
+pub struct Tunnels {
+    dns_resolver: dns::Resolver,
+    /// If set, tunnels may only be opened to targets that match a host suffix
+    /// and, if one is given, a port.
+    allowlist: Option<Arc<Vec<(String, Option<u16>)>>>,
+}
+
+/// Resolves the server names of TLS connections, and the hosts of upgraded
+/// HTTP/1 connections, with the Destination service, so that they may be
+/// balanced across the name's endpoints rather than sent to their original
+/// destination.
+#[derive(Clone)]
+pub struct ServerNames {
+    control: control::Control,
+    default_namespace: String,
+    /// Names are resolved once per thread and watched until the least recently
+    /// used name is evicted to make room for a new one.
+    resolutions: Rc<RefCell<HashMap<FullyQualifiedAuthority, Resolution>>>,
+}
+
+/// The most names that each thread watches.
+///
+/// Server names and `CONNECT` targets are chosen by clients, so the number of
+/// watches must be bounded regardless of how many names clients use.
+const MAX_RESOLUTIONS: usize = 1_000;
+
+/// The most bytes of an upgrade response's head that are read.
+const MAX_RESPONSE_HEAD: usize = 8 * 1024;
+
+/// The most headers that are read from an upgrade response.
+const MAX_RESPONSE_HEADERS: usize = 64;
+
+const TUNNEL_ESTABLISHED: &[u8] = b"HTTP/1.1 200 Connection established\r\n\r\n";
+pub const BAD_REQUEST: &[u8] = b"HTTP/1.1 400 Bad Request\r\ncontent-length: 0\r\n\r\n";
+pub const FORBIDDEN: &[u8] = b"HTTP/1.1 403 Forbidden\r\ncontent-length: 0\r\n\r\n";
+pub const TOO_MANY_REQUESTS: &[u8] =
+    b"HTTP/1.1 429 Too Many Requests\r\ncontent-length: 0\r\n\r\n";
+const BAD_GATEWAY: &[u8] = b"HTTP/1.1 502 Bad Gateway\r\ncontent-length: 0\r\n\r\n";
+
+type BindEndpoint = fn(&SocketAddr, &Metadata) -> Result<Endpoint, ()>;
+
+/// The endpoints discovered for a server name.
+struct Resolution {
+    watch: Watch<BindEndpoint>,
+    endpoints: IndexMap<SocketAddr, Metadata>,
+    /// When a connection last used the name.
+    used: Instant,
+}
+
+/// An endpoint discovered for a server name.
+///
+/// Connections are made to endpoints directly, so only their metadata is kept.
+struct Endpoint(Metadata);
+
+/// Chooses an endpoint for a server name, once one has been discovered.
+///
+/// Completes with `None` if none is discovered before the timeout.
+struct Resolve {
+    name: FullyQualifiedAuthority,
+    resolutions: Rc<RefCell<HashMap<FullyQualifiedAuthority, Resolution>>>,
+    timeout: ReactorTimeout,
+}
+
+/// Reads the head of the response to an upgrade request, and completes with
+/// the bytes read, which may include some following the head, and the
+/// response's status code.
+struct ResponseHead<T> {
+    io: Option<T>,
+    buf: Vec<u8>,
 }
 
 impl Proxy {
     /// Create a new TCP `Proxy`.
-    pub fn new(connect_timeout: Duration, sensors: Sensors, executor: &Handle) -> Self {
+    pub fn new(
+        connect_timeout: Duration,
+        sensors: Sensors,
+        server_names: Option<ServerNames>,
+        tunnels: Option<Tunnels>,
+        executor: &Handle,
+    ) -> Self {
         Self {
             connect_timeout,
             executor: executor.clone(),
             sensors,
+            server_names,
+            tunnels,
         }
     }
 
+    /// Returns true if `CONNECT` requests are accepted.
+    pub fn accepts_tunnels(&self) -> bool {
+        self.tunnels.is_some()
+    }
+
     /// Serve a TCP connection, trying to forward it to its destination.
-    pub fn serve<T>(&self, tcp_in: T, srv_ctx: Arc<ServerCtx>) -> Box<Future<Item=(), Error=()>>
+    ///
+    /// If the connection is TLS, or is an upgraded HTTP/1 connection, and its
+    /// server name is resolved by the Destination service, it is forwarded to
+    /// one of the name's endpoints instead.
+    pub fn serve<T>(
+        &self,
+        tcp_in: T,
+        srv_ctx: Arc<ServerCtx>,
+        peeked: Peeked,
+    ) -> Box<Future<Item=(), Error=()>>
     where
         T: AsyncRead + AsyncWrite + 'static,
     {
+        let peeked = match peeked {
+            Peeked::Connect(connect) => return self.tunnel(tcp_in, srv_ctx, connect),
+            peeked => peeked,
+        };
+
         let orig_dst = srv_ctx.orig_dst_if_not_local();
 
         // For TCP, we really have no extra information other than the
@@ -57,25 +191,504 @@ impl Proxy {
             return Box::new(future::ok(()));
         };
 
-        let client_ctx = ClientCtx::new(
-            &srv_ctx.proxy,
-            &orig_dst,
-            common::Protocol::Tcp,
-        );
+        // Server names are requested on the port of the original destination,
+        // unless they name a port.
+        let resolve = match (self.server_names.as_ref(), peeked.server_name()) {
+            (Some(names), Some(sni)) => names
+                .resolve(sni, orig_dst.port(), self.connect_timeout, &self.executor)
+                .map(|resolve| (resolve.name.clone(), resolve)),
+            _ => None,
+        };
+        let endpoint = match resolve {
+            Some((name, resolve)) => {
+                let endpoint = resolve.map(move |endpoint| match endpoint {
+                    Some((addr, metadata)) => (addr, metadata, Some(name)),
+                    None => {
+                        debug!("no endpoints for {:?}; forwarding to {}", name, orig_dst);
+                        (orig_dst, Metadata::default(), None)
+                    }
+                });
+                future::Either::A(endpoint)
+            }
+            None => future::Either::B(future::ok((orig_dst, Metadata::default(), None))),
+        };
+
+        let (tls_server_name, upgrade, head_len) = match peeked {
+            Peeked::Tls(name) => (Some(name), None, None),
+            Peeked::Upgrade(upgrade) => (None, Some(upgrade.protocol), Some(upgrade.head_len)),
+            _ => (None, None, None),
+        };
+        let proxy = self.clone();
+        let fut = endpoint
+            .and_then(move |(addr, metadata, destination_name)| {
+                debug!("tcp connecting to {} for {:?}", addr, destination_name);
+                let client_ctx = ClientCtx::new(
+                    &srv_ctx.proxy,
+                    &addr,
+                    &metadata,
+                    &destination_name,
+                    &tls_server_name,
+                    &upgrade,
+                    common::Protocol::Tcp,
+                );
+                proxy.connect(&client_ctx)
+            })
+            .and_then(move |tcp_out| -> Box<Future<Item=(), Error=()>> {
+                if let Some(head_len) = head_len {
+                    return switch_protocols(tcp_in, tcp_out, head_len);
+                }
+                let duplex = Duplex::new(tcp_in, tcp_out)
+                    .map_err(|e| debug!("tcp error: {}", e));
+                Box::new(duplex)
+            });
+        Box::new(fut)
+    }
+
+    /// Opens a tunnel to the target of a `CONNECT` request.
+    ///
+    /// Targets with names that are resolved by the Destination service are
+    /// balanced across the name's endpoints. Others are resolved with DNS.
+    fn tunnel<T>(
+        &self,
+        tcp_in: T,
+        srv_ctx: Arc<ServerCtx>,
+        connect: Connect,
+    ) -> Box<Future<Item=(), Error=()>>
+    where
+        T: AsyncRead + AsyncWrite + 'static,
+    {
+        let tunnels = match self.tunnels {
+            Some(ref tunnels) => tunnels.clone(),
+            None => return Box::new(future::ok(())),
+        };
+
+        // The request was only peeked, so it is read before it is answered.
+        let read_head = tokio_io::io::read_exact(tcp_in, vec![0; connect.head_len])
+            .map(|(tcp_in, _)| tcp_in)
+            .map_err(|e| debug!("tunnel read error: {}", e));
+
+        let target = match tunnels.target(&connect.authority) {
+            Ok(target) => target,
+            Err(response) => {
+                info!("refused tunnel to {} from {}", connect.authority, srv_ctx.remote);
+                return Box::new(read_head.and_then(move |tcp_in| respond(tcp_in, response)));
+            }
+        };
+        debug!("tunnel accepted, forwarding ({}) to {}", srv_ctx.remote, connect.authority);
+
+        let resolve = match (self.server_names.as_ref(), &target.host) {
+            (Some(names), &Host::DnsName(ref host)) => names
+                .resolve(host, target.port, self.connect_timeout, &self.executor)
+                .map(|resolve| (resolve.name.clone(), resolve)),
+            _ => None,
+        };
+        let named = match resolve {
+            Some((name, resolve)) => {
+                let endpoint = resolve.map(move |endpoint| {
+                    endpoint.map(|(addr, metadata)| (addr, metadata, Some(name)))
+                });
+                future::Either::A(endpoint)
+            }
+            None => future::Either::B(future::ok(None)),
+        };
+        let endpoint = named.and_then(move |endpoint| match endpoint {
+            Some(endpoint) => future::Either::A(future::ok(endpoint)),
+            None => {
+                let port = target.port;
+                let addr = tunnels.dns_resolver
+                    .resolve_host(&target.host)
+                    .map(move |ip| (SocketAddr::from((ip, port)), Metadata::default(), None))
+                    .map_err(move |_| debug!("tunnel target {:?} not resolved", target.host));
+                future::Either::B(addr)
+            }
+        });
+
+        let proxy = self.clone();
+        let tcp_out = endpoint.and_then(move |(addr, metadata, destination_name)| {
+            debug!("tunnel connecting to {} for {:?}", addr, destination_name);
+            let client_ctx = ClientCtx::new(
+                &srv_ctx.proxy,
+                &addr,
+                &metadata,
+                &destination_name,
+                &None,
+                &None,
+                common::Protocol::Tcp,
+            );
+            proxy.connect(&client_ctx)
+        });
+
+        let fut = read_head.and_then(move |tcp_in| {
+            tcp_out.then(move |tcp_out| match tcp_out {
+                Ok(tcp_out) => {
+                    let tunnel = tokio_io::io::write_all(tcp_in, TUNNEL_ESTABLISHED)
+                        .and_then(move |(tcp_in, _)| Duplex::new(tcp_in, tcp_out))
+                        .map_err(|e| debug!("tunnel error: {}", e));
+                    future::Either::A(tunnel)
+                }
+                Err(()) => future::Either::B(respond(tcp_in, BAD_GATEWAY)),
+            })
+        });
+        Box::new(fut)
+    }
+
+    /// Connects to the remote described by `client_ctx`, recording the
+    /// connection in telemetry.
+    fn connect(
+        &self,
+        client_ctx: &Arc<ClientCtx>,
+    ) -> Box<Future<Item=sensor::Transport<Connection>, Error=()>> {
         let c = Timeout::new(
-            transport::Connect::new(orig_dst, &self.executor),
+            transport::Connect::new(client_ctx.remote, &self.executor),
             self.connect_timeout,
             &self.executor,
         );
-        let connect = self.sensors.connect(c, &client_ctx);
+        let connect = self.sensors.connect(c, client_ctx);
+
+        Box::new(connect.connect().map_err(|e| debug!("tcp connect error: {:?}", e)))
+    }
+}
+
+/// Reads the head of a refused HTTP/1 request, which was only peeked, answers
+/// it and closes the connection.
+pub fn refuse<T>(
+    tcp_in: T,
+    head_len: usize,
+    response: &'static [u8],
+) -> Box<Future<Item=(), Error=()>>
+where
+    T: AsyncRead + AsyncWrite + 'static,
+{
+    let fut = tokio_io::io::read_exact(tcp_in, vec![0; head_len])
+        .map_err(|e| debug!("refused request read error: {}", e))
+        .and_then(move |(tcp_in, _)| respond(tcp_in, response));
+    Box::new(fut)
+}
+
+/// Answers a refused request and closes the connection.
+fn respond<T>(tcp_in: T, response: &'static [u8]) -> Box<Future<Item=(), Error=()>>
+where
+    T: AsyncWrite + 'static,
+{
+    let fut = tokio_io::io::write_all(tcp_in, response)
+        .and_then(|(mut tcp_in, _)| future::poll_fn(move || tcp_in.shutdown()))
+        .map_err(|e| debug!("tunnel response error: {}", e));
+    Box::new(fut)
+}
+
+/// Forwards the head of an upgrade request, which was only peeked, and the
+/// server's response to it.
+///
+/// The connection is only proxied as TCP from then on if the server switches
+/// protocols. Otherwise, the client is told that the connection closes after
+/// the response, so that its next requests are served as HTTP on a new
+/// connection, and the connection is closed once the server or the client
+/// closes it.
+fn switch_protocols<In, Out>(
+    tcp_in: In,
+    tcp_out: Out,
+    head_len: usize,
+) -> Box<Future<Item=(), Error=()>>
+where
+    In: AsyncRead + AsyncWrite + 'static,
+    Out: AsyncRead + AsyncWrite + 'static,
+{
+    let fut = tokio_io::io::read_exact(tcp_in, vec![0; head_len])
+        .and_then(move |(tcp_in, head)| {
+            tokio_io::io::write_all(tcp_out, head)
+                .map(move |(tcp_out, _)| (tcp_in, tcp_out))
+        })
+        .and_then(|(tcp_in, tcp_out)| {
+            ResponseHead::new(tcp_out)
+                .map(move |(tcp_out, head, status)| (tcp_in, tcp_out, head, status))
+        })
+        .and_then(|(tcp_in, tcp_out, head, status)| -> Box<Future<Item=(), Error=io::Error>> {
+            if status == 101 {
+                let fut = tokio_io::io::write_all(tcp_in, head)
+                    .and_then(move |(tcp_in, _)| Duplex::new(tcp_in, tcp_out));
+                return Box::new(fut);
+            }
+
+            debug!("upgrade refused with {}; closing connection", status);
+            let (in_read, in_write) = tcp_in.split();
+            let respond = tokio_io::io::write_all(in_write, close_after(head))
+                .and_then(move |(in_write, _)| tokio_io::io::copy(tcp_out, in_write))
+                .map(|_| ());
+            // Having been told to, the client closes the connection once it
+            // has the response.
+            let closed = tokio_io::io::read(in_read, [0u8; 1]).map(|_| ());
+            let fut = respond.select(closed)
+                .map(|_| ())
+                .map_err(|(e, _)| e);
+            Box::new(fut)
+        })
+        .map_err(|e| debug!("tcp upgrade error: {}", e));
+    Box::new(fut)
+}
 
-        let fut = connect.connect()
-            .map_err(|e| debug!("tcp connect error: {:?}", e))
-            .and_then(move |tcp_out| {
-                Duplex::new(tcp_in, tcp_out)
-                    .map_err(|e| debug!("tcp error: {}", e))
+/// Adds `connection: close` to a response's head, after its status line.
+fn close_after(head: Vec<u8>) -> Vec<u8> {
+    let status_line = head.windows(2)
+        .position(|w| w == b"\r\n")
+        .map(|i| i + 2)
+        .unwrap_or(0);
+    let mut closing = Vec::with_capacity(head.len() + 19);
+    closing.extend_from_slice(&head[..status_line]);
+    closing.extend_from_slice(b"connection: close\r\n");
+    closing.extend_from_slice(&head[status_line..]);
+    closing
+}
+
+// ===== impl Peeked =====
+
+impl Peeked {
+    fn server_name(&self) -> Option<&str> {
+        match *self {
+            Peeked::Opaque => None,
+            Peeked::Tls(ref name) => Some(name),
+            Peeked::Upgrade(ref upgrade) => upgrade.host.as_ref().map(String::as_str),
+            Peeked::Connect(_) => None,
+        }
+    }
+}
+
+// ===== impl Tunnels =====
+
+impl Tunnels {
+    pub fn new(
+        dns_resolver: dns::Resolver,
+        allowlist: Option<Vec<(String, Option<u16>)>>,
+    ) -> Self {
+        Tunnels {
+            dns_resolver,
+            allowlist: allowlist.map(Arc::new),
+        }
+    }
+
+    /// Returns the target of a `CONNECT` request, or the response refusing it.
+    fn target(&self, authority: &str) -> Result<HostAndPort, &'static [u8]> {
+        let target = authority
+            .parse::<http::uri::Authority>()
+            .ok()
+            .and_then(|a| HostAndPort::try_from(&a).ok())
+            .ok_or(BAD_REQUEST)?;
+
+        if let Some(ref allowlist) = self.allowlist {
+            let host = match target.host {
+                Host::DnsName(ref name) => name.clone(),
+                Host::Ip(ref ip) => ip.to_string(),
+            };
+            let allowed = allowlist.iter().any(|&(ref suffix, port)| {
+                let host_matches = host.eq_ignore_ascii_case(suffix)
+                    || discovery::host_has_suffix(&host, suffix);
+                host_matches && port.map(|p| p == target.port).unwrap_or(true)
             });
-        Box::new(fut)
+            if !allowed {
+                return Err(FORBIDDEN);
+            }
+        }
+
+        Ok(target)
+    }
+}
+
+// ===== impl ServerNames =====
+
+impl ServerNames {
+    pub fn new(control: control::Control, default_namespace: String) -> Self {
+        ServerNames {
+            control,
+            default_namespace,
+            resolutions: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+
+    /// Starts resolving `server_name`, unless it is not resolved by the
+    /// Destination service.
+    ///
+    /// The Destination service is given as long to respond as a connection is
+    /// given to be established.
+    fn resolve(
+        &self,
+        server_name: &str,
+        port: u16,
+        timeout: Duration,
+        executor: &Handle,
+    ) -> Option<Resolve> {
+        let authority = server_name.parse::<http::uri::Authority>().ok()?;
+        let authority = if authority.port().is_some() {
+            authority
+        } else {
+            format!("{}:{}", server_name, port).parse().ok()?
+        };
+        let name = self.control.resolvable_name(&authority, &self.default_namespace)?;
+
+        let timeout = match ReactorTimeout::new(timeout, executor) {
+            Ok(timeout) => timeout,
+            Err(e) => {
+                warn!("failed to set resolve timeout: {}", e);
+                return None;
+            }
+        };
+
+        let now = Instant::now();
+        let mut resolutions = self.resolutions.borrow_mut();
+        if !resolutions.contains_key(&name) && resolutions.len() >= MAX_RESOLUTIONS {
+            let lru = resolutions.iter()
+                .min_by_key(|&(_, r)| r.used)
+                .map(|(name, _)| name.clone());
+            if let Some(lru) = lru {
+                debug!("no longer resolving {:?}", lru);
+                resolutions.remove(&lru);
+            }
+        }
+        let control = &self.control;
+        resolutions
+            .entry(name.clone())
+            .or_insert_with(|| Resolution {
+                watch: control.resolve(&name, bind_endpoint as BindEndpoint),
+                endpoints: IndexMap::new(),
+                used: now,
+            })
+            .used = now;
+
+        Some(Resolve {
+            name,
+            resolutions: self.resolutions.clone(),
+            timeout,
+        })
+    }
+}
+
+impl fmt::Debug for ServerNames {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ServerNames")
+            .field("default_namespace", &self.default_namespace)
+            .field("resolutions", &self.resolutions.borrow().len())
+            .finish()
+    }
+}
+
+fn bind_endpoint(_: &SocketAddr, metadata: &Metadata) -> Result<Endpoint, ()> {
+    Ok(Endpoint(metadata.clone()))
+}
+
+// ===== impl Resolution =====
+
+impl Resolution {
+    /// Applies changes discovered since the last poll.
+    fn poll_endpoints(&mut self) -> Poll<(), ()> {
+        loop {
+            match try_ready!(self.watch.poll()) {
+                Change::Insert(addr, Endpoint(metadata)) => {
+                    self.endpoints.insert(addr, metadata);
+                }
+                Change::Remove(addr) => {
+                    self.endpoints.swap_remove(&addr);
+                }
+            }
+        }
+    }
+}
+
+// ===== impl Endpoint =====
+
+impl Service for Endpoint {
+    type Request = ();
+    type Response = ();
+    type Error = ();
+    type Future = future::FutureResult<(), ()>;
+
+    fn poll_ready(&mut self) -> Poll<(), ()> {
+        Ok(Async::Ready(()))
+    }
+
+    fn call(&mut self, _: ()) -> Self::Future {
+        future::ok(())
+    }
+}
+
+// ===== impl Resolve =====
+
+impl Future for Resolve {
+    type Item = Option<(SocketAddr, Metadata)>;
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let mut resolutions = self.resolutions.borrow_mut();
+        let resolution = match resolutions.get_mut(&self.name) {
+            Some(resolution) => resolution,
+            None => return Ok(Async::Ready(None)),
+        };
+
+        if resolution.poll_endpoints().is_err() {
+            warn!("resolving {:?} failed", self.name);
+            return Ok(Async::Ready(None));
+        }
+
+        if !resolution.endpoints.is_empty() {
+            let i = rand::thread_rng().gen_range(0, resolution.endpoints.len());
+            let (addr, metadata) = resolution.endpoints
+                .get_index(i)
+                .expect("index is in range");
+            return Ok(Async::Ready(Some((*addr, metadata.clone()))));
+        }
+
+        match self.timeout.poll() {
+            Ok(Async::NotReady) => Ok(Async::NotReady),
+            Ok(Async::Ready(())) | Err(_) => Ok(Async::Ready(None)),
+        }
+    }
+}
+
+// ===== impl ResponseHead =====
+
+impl<T: AsyncRead> ResponseHead<T> {
+    fn new(io: T) -> Self {
+        ResponseHead {
+            io: Some(io),
+            buf: Vec::new(),
+        }
+    }
+}
+
+impl<T: AsyncRead> Future for ResponseHead<T> {
+    type Item = (T, Vec<u8>, u16);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        loop {
+            let status = {
+                let mut headers = [httparse::EMPTY_HEADER; MAX_RESPONSE_HEADERS];
+                let mut rsp = httparse::Response::new(&mut headers);
+                match rsp.parse(&self.buf) {
+                    Ok(httparse::Status::Complete(_)) => rsp.code,
+                    Ok(httparse::Status::Partial) => None,
+                    Err(e) => {
+                        let msg = format!("invalid upgrade response: {:?}", e);
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+                    }
+                }
+            };
+            if let Some(status) = status {
+                let io = self.io.take().expect("polled after completed");
+                let head = mem::replace(&mut self.buf, Vec::new());
+                return Ok(Async::Ready((io, head, status)));
+            }
+
+            if self.buf.len() >= MAX_RESPONSE_HEAD {
+                let msg = "upgrade response head too large";
+                return Err(io::Error::new(io::ErrorKind::InvalidData, msg));
+            }
+            let n = try_ready!(self.io
+                .as_mut()
+                .expect("polled after completed")
+                .read_buf(&mut self.buf));
+            if n == 0 {
+                let msg = "closed before responding to upgrade";
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, msg));
+            }
+        }
     }
 }
 
@@ -0,0 +1,168 @@
+//! Reads the server name from a TLS ClientHello, so that TLS connections may be
+//! routed without terminating them.
+//!
+//! Only as much of the ClientHello as is needed is parsed. Anything that does
+//! not look like a ClientHello with a `server_name` extension (RFC 6066) in its
+//! first record has no name.
+
+use std::str;
+
+/// A TLS record containing a handshake message.
+const CONTENT_TYPE_HANDSHAKE: u8 = 22;
+const HANDSHAKE_TYPE_CLIENT_HELLO: u8 = 1;
+const EXTENSION_TYPE_SERVER_NAME: u16 = 0;
+const NAME_TYPE_HOST_NAME: u8 = 0;
+
+/// Returns the server name indicated by the ClientHello in `bytes`, if any.
+///
+/// The ClientHello must be contained entirely in the peeked bytes.
+pub fn server_name(bytes: &[u8]) -> Option<String> {
+    let mut record = Reader(bytes);
+    if record.u8()? != CONTENT_TYPE_HANDSHAKE {
+        return None;
+    }
+    let _version = record.u16()?;
+    let mut handshake = record.vec16()?;
+
+    if handshake.u8()? != HANDSHAKE_TYPE_CLIENT_HELLO {
+        return None;
+    }
+    let len = handshake.u24()?;
+    let mut hello = Reader(handshake.take(len)?);
+
+    let _version = hello.u16()?;
+    let _random = hello.take(32)?;
+    let _session_id = hello.vec8()?;
+    let _cipher_suites = hello.vec16()?;
+    let _compression_methods = hello.vec8()?;
+
+    let mut extensions = hello.vec16()?;
+    while !extensions.is_empty() {
+        let ty = extensions.u16()?;
+        let mut data = extensions.vec16()?;
+        if ty != EXTENSION_TYPE_SERVER_NAME {
+            continue;
+        }
+
+        let mut names = data.vec16()?;
+        while !names.is_empty() {
+            let name_type = names.u8()?;
+            let name = names.vec16()?;
+            if name_type == NAME_TYPE_HOST_NAME {
+                let name = str::from_utf8(name.0).ok()?;
+                return Some(name.to_owned());
+            }
+        }
+        return None;
+    }
+
+    None
+}
+
+/// Reads big-endian integers and length-prefixed vectors from a buffer.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.0.len() < n {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.take(2).map(|b| (u16::from(b[0]) << 8) | u16::from(b[1]))
+    }
+
+    fn u24(&mut self) -> Option<usize> {
+        self.take(3).map(|b| {
+            ((b[0] as usize) << 16) | ((b[1] as usize) << 8) | b[2] as usize
+        })
+    }
+
+    fn vec8(&mut self) -> Option<Reader<'a>> {
+        let len = self.u8()?;
+        self.take(len as usize).map(Reader)
+    }
+
+    fn vec16(&mut self) -> Option<Reader<'a>> {
+        let len = self.u16()?;
+        self.take(len as usize).map(Reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::server_name;
+
+    fn vec16(body: &[u8]) -> Vec<u8> {
+        let mut v = vec![(body.len() >> 8) as u8, body.len() as u8];
+        v.extend_from_slice(body);
+        v
+    }
+
+    /// Builds a ClientHello record with the given extensions.
+    fn client_hello(extensions: &[(u16, Vec<u8>)]) -> Vec<u8> {
+        let mut exts = Vec::new();
+        for &(ty, ref data) in extensions {
+            exts.extend_from_slice(&[(ty >> 8) as u8, ty as u8]);
+            exts.extend(vec16(data));
+        }
+
+        let mut hello = vec![3, 3];
+        hello.extend_from_slice(&[7; 32]);
+        hello.extend_from_slice(&[0]);
+        hello.extend(vec16(&[0x13, 0x01, 0xc0, 0x2f]));
+        hello.extend_from_slice(&[1, 0]);
+        hello.extend(vec16(&exts));
+
+        let len = hello.len();
+        let mut handshake = vec![1, (len >> 16) as u8, (len >> 8) as u8, len as u8];
+        handshake.extend(hello);
+
+        let mut record = vec![22, 3, 1];
+        record.extend(vec16(&handshake));
+        record
+    }
+
+    fn sni(name: &str) -> (u16, Vec<u8>) {
+        let mut entry = vec![0];
+        entry.extend(vec16(name.as_bytes()));
+        (0, vec16(&entry))
+    }
+
+    #[test]
+    fn reads_server_name() {
+        let hello = client_hello(&[(10, vec![0, 2, 0, 29]), sni("web.example.com")]);
+        assert_eq!(server_name(&hello), Some("web.example.com".to_owned()));
+    }
+
+    #[test]
+    fn no_server_name_without_extension() {
+        let hello = client_hello(&[(10, vec![0, 2, 0, 29])]);
+        assert_eq!(server_name(&hello), None);
+    }
+
+    #[test]
+    fn no_server_name_in_truncated_hello() {
+        let hello = client_hello(&[sni("web.example.com")]);
+        assert_eq!(server_name(&hello[..hello.len() - 4]), None);
+    }
+
+    #[test]
+    fn no_server_name_in_other_protocols() {
+        assert_eq!(server_name(b"GET / HTTP/1.1\r\n\r\n"), None);
+        assert_eq!(server_name(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n"), None);
+        assert_eq!(server_name(&[]), None);
+    }
+}
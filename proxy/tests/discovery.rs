@@ -108,6 +108,26 @@ macro_rules! generate_tests {
             assert_eq!(client.get("/"), "hello");
             assert_eq!(client.get("/bye"), "bye");
         }
+
+        #[test]
+        fn outbound_uses_static_destinations_without_controller() {
+            let _ = env_logger::try_init();
+
+            let srv = $make_server().route("/", "hello").run();
+            let mut env = config::TestEnv::new();
+            env.put(config::ENV_REPORT_PATH, "/dev/null".to_owned());
+            env.put(
+                config::ENV_STATIC_DESTINATIONS,
+                format!("disco={}", srv.addr),
+            );
+            let proxy = proxy::new()
+                // don't set srv as outbound(), so that SO_ORIGINAL_DST isn't
+                // used as a backup
+                .run_with_test_env(env);
+            let client = $make_client(proxy.outbound, "disco.test.svc.cluster.local");
+
+            assert_eq!(client.get("/"), "hello");
+        }
     }
 }
 
@@ -158,3 +178,338 @@ fn outbound_updates_newer_services() {
     let client2 = client::http1(proxy.outbound, "disco.test.svc.cluster.local");
     assert_eq!(client2.get("/h1"), "hello h1");
 }
+
+#[test]
+fn outbound_watches_destinations_file() {
+    use std::io::Write;
+    let _ = env_logger::try_init();
+
+    let srv1 = server::new().route("/", "one").run();
+    let srv2 = server::new().route("/", "two").run();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-destinations");
+    let write = |addr| {
+        ::std::fs::File::create(&path)
+            .and_then(|mut f| write!(f, "disco {}\n", addr))
+            .expect("write destinations");
+    };
+    write(srv1.addr);
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_REPORT_PATH, "/dev/null".to_owned());
+    env.put(config::ENV_DESTINATIONS_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new().run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    assert_eq!(client.get("/"), "one");
+
+    // The file is re-read every second; srv1 is removed and srv2 inserted.
+    write(srv2.addr);
+    let mut body = String::new();
+    for _ in 0..30 {
+        ::std::thread::sleep(Duration::from_millis(100));
+        body = client.get("/");
+        if body == "two" {
+            break;
+        }
+    }
+    assert_eq!(body, "two");
+}
+
+#[test]
+fn outbound_asks_controller_with_configured_scheme() {
+    let _ = env_logger::try_init();
+
+    let srv = server::new().route("/", "hello").run();
+    let mut ctrl = controller::new()
+        .destination("web.service.consul", srv.addr);
+    let requests = ctrl.destination_requests();
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_DESTINATION_SCHEMES, "consul=consul".to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        // don't set srv as outbound(), so that SO_ORIGINAL_DST isn't
+        // used as a backup
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "web.service.consul");
+
+    assert_eq!(client.get("/"), "hello");
+
+    let req = requests.wait().next().unwrap().unwrap();
+    assert_eq!(req.scheme, "consul");
+    assert_eq!(req.path, "web.service.consul");
+}
+
+#[test]
+fn outbound_prefers_endpoints_in_its_zone() {
+    let _ = env_logger::try_init();
+
+    let local = server::new().route("/", "local").run();
+    let remote = server::new().route("/", "remote").run();
+    let (local_addr, remote_addr) = (local.addr, remote.addr);
+    let ctrl = controller::new()
+        .destination_fn("disco.test.svc.cluster.local", move || {
+            Some(controller::destination_add(vec![
+                controller::weighted_addr(local_addr, &[("zone", "zone-a")]),
+                controller::weighted_addr(remote_addr, &[("zone", "zone-b")]),
+            ]))
+        })
+        .run();
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_ZONE, "zone-a".to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    // Either endpoint may be used until both have connected.
+    for _ in 0..5 {
+        client.get("/");
+    }
+    for _ in 0..10 {
+        assert_eq!(client.get("/"), "local");
+    }
+}
+
+#[test]
+fn outbound_uses_configured_balance_strategy() {
+    let _ = env_logger::try_init();
+
+    let one = server::new().route("/", "one").run();
+    let two = server::new().route("/", "two").run();
+    let (one_addr, two_addr) = (one.addr, two.addr);
+    let ctrl = controller::new()
+        .destination_fn("disco.test.svc.cluster.local", move || {
+            Some(controller::destination_add(vec![
+                controller::weighted_addr(one_addr, &[]),
+                controller::weighted_addr(two_addr, &[]),
+            ]))
+        })
+        .run();
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_BALANCE_STRATEGIES, "test.svc.cluster.local=round-robin".to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    // Either endpoint may be used until both have connected.
+    for _ in 0..5 {
+        client.get("/");
+    }
+    let mut last = client.get("/");
+    for _ in 0..6 {
+        let body = client.get("/");
+        assert_ne!(body, last);
+        last = body;
+    }
+}
+
+#[test]
+fn outbound_routes_by_consistent_hash() {
+    let _ = env_logger::try_init();
+
+    let one = server::new()
+        .route("/users/alice", "one")
+        .route("/users/bob", "one")
+        .run();
+    let two = server::new()
+        .route("/users/alice", "two")
+        .route("/users/bob", "two")
+        .run();
+    let (one_addr, two_addr) = (one.addr, two.addr);
+    let ctrl = controller::new()
+        .destination_fn("disco.test.svc.cluster.local", move || {
+            Some(controller::destination_add(vec![
+                controller::weighted_addr(one_addr, &[]),
+                controller::weighted_addr(two_addr, &[]),
+            ]))
+        })
+        .run();
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_HASH_KEYS, "disco.test.svc.cluster.local=path:1".to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    // Requests may skip an endpoint until both have connected.
+    for _ in 0..5 {
+        client.get("/users/alice");
+        client.get("/users/bob");
+    }
+    for user in &["/users/alice", "/users/bob"] {
+        let first = client.get(user);
+        for _ in 0..5 {
+            assert_eq!(client.get(user), first);
+        }
+    }
+}
+
+#[test]
+fn outbound_splits_traffic_between_backends() {
+    let _ = env_logger::try_init();
+
+    let srv = server::new().route("/", "v2").run();
+    let mut ctrl = controller::new()
+        .destination("disco-v2.test.svc.cluster.local", srv.addr);
+    let requests = ctrl.destination_requests();
+
+    let mut env = config::TestEnv::new();
+    env.put(
+        config::ENV_TRAFFIC_SPLITS,
+        "disco.test.svc.cluster.local=disco-v1:0,disco-v2:1".to_owned(),
+    );
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    assert_eq!(client.get("/"), "v2");
+
+    let req = requests.wait().next().unwrap().unwrap();
+    assert_eq!(req.path, "disco-v2.test.svc.cluster.local");
+}
+
+#[test]
+fn outbound_applies_routing_rules() {
+    use std::io::Write;
+    let _ = env_logger::try_init();
+
+    let srv = server::new().route("/v2/users", "beta").run();
+    let mut ctrl = controller::new()
+        .destination("disco-beta.test.svc.cluster.local", srv.addr);
+    let requests = ctrl.destination_requests();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-routing-rules");
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| {
+            f.write_all(b"path=/api header:x-beta=1 -> route=disco-beta rewrite-path=/v2\n")
+        })
+        .expect("write routing rules");
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_ROUTING_RULES_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    let mut req = client.request_builder("/api/users");
+    let rsp = client.request(req.header("x-beta", "1"));
+    assert_eq!(rsp.status(), http::StatusCode::OK);
+
+    let req = requests.wait().next().unwrap().unwrap();
+    assert_eq!(req.path, "disco-beta.test.svc.cluster.local");
+}
+
+#[test]
+fn outbound_mirrors_requests_to_shadow() {
+    use std::io::Write;
+    use std::sync::Mutex;
+    use std::sync::mpsc;
+    let _ = env_logger::try_init();
+
+    let (tx, rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+    let primary = server::http1().route("/", "primary").run();
+    let shadow = server::http1()
+        .route_fn("/", move |req| {
+            let host = req.headers()["host"].to_str().unwrap().to_owned();
+            tx.lock().unwrap().send(host).unwrap();
+            Response::new("shadow".into())
+        })
+        .run();
+    let ctrl = controller::new()
+        .destination("disco.test.svc.cluster.local", primary.addr)
+        .destination("disco-shadow.test.svc.cluster.local", shadow.addr)
+        .run();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-mirror-rules");
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(b"path=/ -> mirror=disco-shadow\n"))
+        .expect("write routing rules");
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_ROUTING_RULES_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .run_with_test_env(env);
+    let client = client::http1(proxy.outbound, "disco.test.svc.cluster.local");
+
+    assert_eq!(client.get("/"), "primary");
+
+    let host = rx.recv_timeout(Duration::from_secs(5)).expect("mirrored request");
+    assert_eq!(host, "disco-shadow");
+}
+
+#[test]
+fn outbound_injects_faults() {
+    use std::io::Write;
+    let _ = env_logger::try_init();
+
+    let srv = server::new().route("/", "hello").run();
+    let ctrl = controller::new()
+        .destination("disco.test.svc.cluster.local", srv.addr)
+        .run();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-faults");
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(b"header:x-chaos=abort -> abort=503\n"))
+        .expect("write faults");
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_OUTBOUND_FAULTS_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    let mut req = client.request_builder("/");
+    let rsp = client.request(req.header("x-chaos", "abort"));
+    assert_eq!(rsp.status(), http::StatusCode::SERVICE_UNAVAILABLE);
+
+    assert_eq!(client.get("/"), "hello");
+}
+
+#[test]
+fn outbound_delays_requests_before_sending_them() {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+    let _ = env_logger::try_init();
+
+    let received = Arc::new(Mutex::new(None));
+    let srv_received = received.clone();
+    let srv = server::new()
+        .route_fn("/", move |_| {
+            *srv_received.lock().unwrap() = Some(Instant::now());
+            Response::new("hello".into())
+        })
+        .run();
+    let ctrl = controller::new()
+        .destination("disco.test.svc.cluster.local", srv.addr)
+        .run();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-fault-delays");
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(b"path=/ -> delay-ms=200\n"))
+        .expect("write faults");
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_OUTBOUND_FAULTS_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .run_with_test_env(env);
+    let client = client::new(proxy.outbound, "disco.test.svc.cluster.local");
+
+    let sent = Instant::now();
+    assert_eq!(client.get("/"), "hello");
+
+    let received = received.lock().unwrap().expect("request was received");
+    assert!(received.duration_since(sent) >= Duration::from_millis(200));
+}
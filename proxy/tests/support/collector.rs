@@ -0,0 +1,107 @@
+use support::*;
+
+use self::futures::sync::mpsc;
+
+pub fn new() -> Collector {
+    Collector::new()
+}
+
+/// A stand-in for a trace collector that accepts Zipkin v2 JSON over HTTP/1.
+#[derive(Debug)]
+pub struct Collector {
+    spans: Option<mpsc::UnboundedSender<String>>,
+}
+
+#[derive(Debug)]
+pub struct Listening {
+    pub addr: SocketAddr,
+    shutdown: Shutdown,
+}
+
+impl Collector {
+    pub fn new() -> Self {
+        Collector {
+            spans: None,
+        }
+    }
+
+    /// Returns a stream of the request bodies posted to the collector.
+    pub fn spans(&mut self) -> mpsc::UnboundedReceiver<String> {
+        let (tx, rx) = mpsc::unbounded();
+        self.spans = Some(tx);
+        rx
+    }
+
+    pub fn run(self) -> Listening {
+        run(self)
+    }
+}
+
+impl Listening {
+    pub fn url(&self) -> String {
+        format!("http://{}/api/v2/spans", self.addr)
+    }
+}
+
+struct Svc(Option<mpsc::UnboundedSender<String>>);
+
+impl hyper::server::Service for Svc {
+    type Request = hyper::server::Request;
+    type Response = hyper::server::Response;
+    type Error = hyper::Error;
+    type Future = Box<Future<Item = Self::Response, Error = Self::Error>>;
+
+    fn call(&self, req: Self::Request) -> Self::Future {
+        let spans = self.0.clone();
+        let fut = req.body()
+            .concat2()
+            .map(move |body| {
+                if let Some(spans) = spans {
+                    let _ = spans.unbounded_send(s(&body).to_owned());
+                }
+                hyper::server::Response::new().with_status(hyper::StatusCode::Accepted)
+            });
+        Box::new(fut)
+    }
+}
+
+fn run(collector: Collector) -> Listening {
+    let (tx, rx) = shutdown_signal();
+    let (addr_tx, addr_rx) = oneshot::channel();
+    ::std::thread::Builder::new().name("support collector".into()).spawn(move || {
+        let mut core = Core::new().unwrap();
+        let reactor = core.handle();
+
+        let addr = ([127, 0, 0, 1], 0).into();
+        let bind = TcpListener::bind(&addr, &reactor).expect("bind");
+
+        let local_addr = bind.local_addr().expect("local_addr");
+        let _ = addr_tx.send(local_addr);
+
+        let h1 = hyper::server::Http::<hyper::Chunk>::new();
+        let spans = collector.spans;
+        let serve = bind.incoming()
+            .fold(reactor.clone(), move |reactor, (sock, _)| {
+                let conn = h1.serve_connection(sock, Svc(spans.clone()))
+                    .map(|_| ())
+                    .map_err(|e| println!("collector error: {}", e));
+                reactor.spawn(conn);
+                Ok::<_, ::std::io::Error>(reactor)
+            });
+
+        core.handle().spawn(
+            serve
+                .map(|_| ())
+                .map_err(|e| println!("collector error: {}", e)),
+        );
+
+        core.run(rx).unwrap();
+    }).unwrap();
+
+    let addr = addr_rx.wait().expect("addr");
+
+    Listening {
+        addr,
+        shutdown: tx,
+    }
+}
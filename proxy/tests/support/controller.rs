@@ -20,6 +20,7 @@ struct Destination(Box<Fn() -> Option<pb::destination::Update> + Send>);
 #[derive(Debug)]
 pub struct Controller {
     destinations: Vec<(String, Destination)>,
+    destination_requests: Option<mpsc::UnboundedSender<pb::common::Destination>>,
     reports: Option<mpsc::UnboundedSender<pb::telemetry::ReportRequest>>,
 }
 
@@ -33,6 +34,7 @@ impl Controller {
     pub fn new() -> Self {
         Controller {
             destinations: Vec::new(),
+            destination_requests: None,
             reports: None,
         }
     }
@@ -55,6 +57,12 @@ impl Controller {
         self.destination_fn(dest, || None)
     }
 
+    pub fn destination_requests(&mut self) -> mpsc::UnboundedReceiver<pb::common::Destination> {
+        let (tx, rx) = mpsc::unbounded();
+        self.destination_requests = Some(tx);
+        rx
+    }
+
     pub fn reports(&mut self) -> mpsc::UnboundedReceiver<pb::telemetry::ReportRequest> {
         let (tx, rx) = mpsc::unbounded();
         self.reports = Some(tx);
@@ -81,6 +89,7 @@ impl fmt::Debug for Destination {
 #[derive(Debug)]
 struct Svc {
     destinations: Destinations,
+    destination_requests: Option<mpsc::UnboundedSender<pb::common::Destination>>,
     reports: Option<mpsc::UnboundedSender<pb::telemetry::ReportRequest>>,
 }
 
@@ -96,7 +105,12 @@ impl Svc {
         match path {
             DESTINATION_GET => {
                 let destinations = self.destinations.clone();
-                Box::new(body.concat2().and_then(move |_bytes| {
+                let mut requests = self.destination_requests.clone();
+                Box::new(body.concat2().and_then(move |mut bytes| {
+                    if let Some(ref mut requests) = requests {
+                        let req = Message::decode(bytes.split_off(5)).unwrap();
+                        let _ = requests.unbounded_send(req);
+                    }
                     let update = {
                         let mut vec = destinations.lock().unwrap();
                         //TODO: decode `_bytes` and compare with `.0`
@@ -197,6 +211,7 @@ impl Body for GrpcBody {
 #[derive(Debug)]
 struct NewSvc {
     destinations: Destinations,
+    destination_requests: Option<mpsc::UnboundedSender<pb::common::Destination>>,
     reports: Option<mpsc::UnboundedSender<pb::telemetry::ReportRequest>>,
 }
 impl NewService for NewSvc {
@@ -210,6 +225,7 @@ impl NewService for NewSvc {
     fn new_service(&self) -> Self::Future {
         future::ok(Svc {
             destinations: self.destinations.clone(),
+            destination_requests: self.destination_requests.clone(),
             reports: self.reports.clone(),
         })
     }
@@ -227,6 +243,7 @@ fn run(controller: Controller) -> Listening {
 
             let factory = NewSvc {
                 destinations: Arc::new(Mutex::new(controller.destinations)),
+                destination_requests: controller.destination_requests,
                 reports: controller.reports,
             };
             let h2 = tower_h2::Server::new(factory, Default::default(), reactor.clone());
@@ -267,23 +284,33 @@ fn run(controller: Controller) -> Listening {
 }
 
 pub fn destination_update(addr: SocketAddr) -> pb::destination::Update {
+    destination_add(vec![weighted_addr(addr, &[])])
+}
+
+pub fn destination_add(addrs: Vec<pb::destination::WeightedAddr>) -> pb::destination::Update {
     pb::destination::Update {
         update: Some(pb::destination::update::Update::Add(
             pb::destination::WeightedAddrSet {
-                addrs: vec![
-                    pb::destination::WeightedAddr {
-                        addr: Some(pb::common::TcpAddress {
-                            ip: Some(ip_conv(addr.ip())),
-                            port: u32::from(addr.port()),
-                        }),
-                        weight: 0,
-                    },
-                ],
+                addrs,
             },
         )),
     }
 }
 
+pub fn weighted_addr(addr: SocketAddr, metadata: &[(&str, &str)]) -> pb::destination::WeightedAddr {
+    pb::destination::WeightedAddr {
+        addr: Some(pb::common::TcpAddress {
+            ip: Some(ip_conv(addr.ip())),
+            port: u32::from(addr.port()),
+        }),
+        weight: 0,
+        metadata: metadata
+            .iter()
+            .map(|&(k, v)| (k.to_owned(), v.to_owned()))
+            .collect(),
+    }
+}
+
 fn ip_conv(ip: IpAddr) -> pb::common::IpAddress {
     match ip {
         IpAddr::V4(v4) => pb::common::IpAddress {
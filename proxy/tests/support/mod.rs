@@ -31,6 +31,7 @@ use std::net::SocketAddr;
 pub use std::time::Duration;
 
 pub mod client;
+pub mod collector;
 pub mod controller;
 pub mod proxy;
 pub mod server;
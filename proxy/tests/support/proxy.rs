@@ -101,12 +101,15 @@ impl conduit_proxy::GetOriginalDst for MockOriginalDst {
 fn run(proxy: Proxy, mut env: config::TestEnv) -> Listening {
     use self::conduit_proxy::config;
 
-    let controller = proxy.controller.expect("proxy controller missing");
+    // Without a controller, the test must configure the proxy to run standalone.
+    let controller = proxy.controller;
     let inbound = proxy.inbound;
     let outbound = proxy.outbound;
     let mut mock_orig_dst = DstInner::default();
 
-    env.put(config::ENV_CONTROL_URL, format!("tcp://{}", controller.addr));
+    if let Some(ref controller) = controller {
+        env.put(config::ENV_CONTROL_URL, format!("tcp://{}", controller.addr));
+    }
     env.put(config::ENV_PRIVATE_LISTENER, "tcp://127.0.0.1:0".to_owned());
     if let Some(ref inbound) = inbound {
         env.put(config::ENV_PRIVATE_FORWARD, format!("tcp://{}", inbound.addr));
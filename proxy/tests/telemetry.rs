@@ -51,6 +51,18 @@ fn inbound_sends_telemetry() {
     // ends
     let ends = &res.ends[0];
     assert_eq!(ends.streams, 1);
+    // stream durations use the same buckets as response latencies.
+    assert_eq!(
+        res.stream_duration_counts.len(),
+        report.histogram_bucket_bounds_tenth_ms.len()
+    );
+    assert_eq!(res.stream_duration_counts.iter().sum::<u32>(), 1);
+    // response body
+    assert_eq!(res.bytes_sent, "hello".len() as u64);
+    assert_eq!(res.frames_sent, 1);
+    // request body
+    assert_eq!(res.request_bytes_sent, 0);
+    assert_eq!(res.request_frames_sent, 0);
 }
 
 
@@ -110,13 +122,17 @@ fn inbound_aggregates_telemetry_over_several_requests() {
         .route("/hi", "good morning")
         .run();
 
+    // Requests are aggregated by route, so each path needs its own route.
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_ROUTES_PATH, routes_file("several", "GET /hey hey\nGET /hi hi\n"));
+
     let mut ctrl = controller::new();
     let reports = ctrl.reports();
     let proxy = proxy::new()
         .controller(ctrl.run())
         .inbound(srv)
         .metrics_flush_interval(Duration::from_millis(500))
-        .run();
+        .run_with_test_env(env);
     let client = client::new(proxy.inbound, "tele.test.svc.cluster.local");
 
     info!("client.get(/hey)");
@@ -137,6 +153,7 @@ fn inbound_aggregates_telemetry_over_several_requests() {
     // -- first request -----------------
     let req = &report.requests[0];
     assert_eq!(req.ctx.as_ref().unwrap().authority, "tele.test.svc.cluster.local");
+    assert_eq!(req.ctx.as_ref().unwrap().route, "hey");
     assert_eq!(req.count, 1);
     assert_eq!(req.responses.len(), 1);
     // ---- response --------------------
@@ -192,13 +209,17 @@ fn records_latency_statistics() {
         .route_with_latency("/hi", "good morning", Duration::from_millis(40))
         .run();
 
+    // Requests are aggregated by route, so each path needs its own route.
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_ROUTES_PATH, routes_file("latency", "GET /hey hey\nGET /hi hi\n"));
+
     let mut ctrl = controller::new();
     let reports = ctrl.reports();
     let proxy = proxy::new()
         .controller(ctrl.run())
         .inbound(srv)
         .metrics_flush_interval(Duration::from_secs(5))
-        .run();
+        .run_with_test_env(env);
     let client = client::new(proxy.inbound, "tele.test.svc.cluster.local");
 
     info!("client.get(/hey)");
@@ -236,6 +257,7 @@ fn records_latency_statistics() {
     // second request
     let req = &report.requests.get(1).expect("second report");
     assert_eq!(req.ctx.as_ref().unwrap().authority, "tele.test.svc.cluster.local");
+    assert_eq!(req.ctx.as_ref().unwrap().route, "hi");
     assert_eq!(req.count, 2);
     assert_eq!(req.responses.len(), 1);
     let res = req.responses.get(0).expect("responses[0]");
@@ -257,6 +279,132 @@ fn records_latency_statistics() {
 
 }
 
+#[test]
+fn inbound_aggregates_requests_by_route() {
+    let _ = env_logger::try_init();
+
+    info!("running test server");
+    let srv = server::new()
+        .route("/users/1", "alice")
+        .route("/users/2", "bob")
+        .route("/health", "ok")
+        .run();
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_ROUTES_PATH, routes_file("users", "GET /users/{id} get-user\n"));
+
+    let mut ctrl = controller::new();
+    let reports = ctrl.reports();
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        .inbound(srv)
+        .metrics_flush_interval(Duration::from_millis(500))
+        .run_with_test_env(env);
+    let client = client::new(proxy.inbound, "tele.test.svc.cluster.local");
+
+    assert_eq!(client.get("/users/1"), "alice");
+    assert_eq!(client.get("/users/2"), "bob");
+    assert_eq!(client.get("/health"), "ok");
+
+    info!("awaiting report");
+    let report = reports.wait().next().unwrap().unwrap();
+    assert_eq!(report.requests.len(), 2);
+
+    let users = &report.requests[0];
+    assert_eq!(users.ctx.as_ref().unwrap().route, "get-user");
+    assert_eq!(users.count, 2);
+
+    // Requests that match no route share a default bucket.
+    let other = &report.requests[1];
+    assert_eq!(other.ctx.as_ref().unwrap().route, "");
+    assert_eq!(other.count, 1);
+}
+
+#[test]
+fn inbound_does_not_trust_mirrored_header_from_clients() {
+    let _ = env_logger::try_init();
+
+    info!("running test server");
+    let srv = server::new().route("/hey", "hello").run();
+
+    let mut ctrl = controller::new();
+    let reports = ctrl.reports();
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        .inbound(srv)
+        .metrics_flush_interval(Duration::from_millis(500))
+        .run();
+    let client = client::new(proxy.inbound, "tele.test.svc.cluster.local");
+
+    let mut req = client.request_builder("/hey");
+    let rsp = client.request(req.header("conduit-mirrored", "true"));
+    assert_eq!(rsp.status(), http::StatusCode::OK);
+
+    info!("awaiting report");
+    let report = reports.wait().next().unwrap().unwrap();
+    assert_eq!(report.requests.len(), 1);
+    let req = &report.requests[0];
+    assert!(!req.ctx.as_ref().unwrap().mirrored);
+    assert_eq!(req.count, 1);
+}
+
+#[test]
+fn inbound_writes_reports_without_controller() {
+    use std::io::Read;
+
+    let _ = env_logger::try_init();
+
+    let srv = server::new().route("/hey", "hello").run();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-reports");
+    let _ = ::std::fs::remove_file(&path);
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_REPORT_PATH, path.to_str().expect("report path").to_owned());
+
+    let proxy = proxy::new()
+        .inbound(srv)
+        .metrics_flush_interval(Duration::from_millis(100))
+        .run_with_test_env(env);
+    let client = client::new(proxy.inbound, "tele.test.svc.cluster.local");
+
+    assert_eq!(client.get("/hey"), "hello");
+
+    info!("awaiting report");
+    let mut report = None;
+    for _ in 0..50 {
+        let mut contents = String::new();
+        let _ = ::std::fs::File::open(&path)
+            .and_then(|mut f| f.read_to_string(&mut contents));
+        report = contents
+            .lines()
+            .find(|line| line.contains("\"authority\":\"tele.test.svc.cluster.local\""))
+            .map(String::from);
+        if report.is_some() {
+            break;
+        }
+        ::std::thread::sleep(Duration::from_millis(100));
+    }
+
+    // Each report is written as a single line of JSON.
+    let report = report.expect("report written");
+    assert!(report.starts_with("{\"process\":"), "{}", report);
+    assert!(report.contains("\"proxy\":\"INBOUND\""), "{}", report);
+    assert!(report.contains("\"count\":1"), "{}", report);
+    assert!(report.contains("\"http_status_code\":200"), "{}", report);
+}
+
 #[test]
 fn telemetry_report_errors_are_ignored() {}
 
+/// Writes route definitions to a temporary file, returning its path.
+fn routes_file(name: &str, routes: &str) -> String {
+    use std::io::Write;
+
+    let path = ::std::env::temp_dir()
+        .join(format!("conduit-proxy-test-routes-{}", name));
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(routes.as_bytes()))
+        .expect("write routes");
+    path.to_str().expect("routes path").to_owned()
+}
+
@@ -0,0 +1,118 @@
+#[macro_use]
+extern crate log;
+
+mod support;
+use self::support::*;
+
+const TRACE_ID: &str = "0af7651916cd43dd8448eb211c80319c";
+const PARENT_ID: &str = "b7ad6b7169203331";
+
+#[test]
+fn inbound_propagates_trace_and_reports_span() {
+    let _ = env_logger::try_init();
+
+    let srv = server::http1()
+        .route_fn("/hey", |req| {
+            let traceparent = req.headers()["traceparent"].to_str().unwrap();
+            let parts = traceparent.split('-').collect::<Vec<_>>();
+            assert_eq!(parts[1], TRACE_ID, "trace id must be preserved");
+            assert_ne!(parts[2], PARENT_ID, "proxy must introduce its own span");
+            assert!(req.headers().get("x-b3-traceid").is_none(), "only traceparent was propagated");
+            Response::new("hello".into())
+        })
+        .run();
+
+    let mut collector = collector::new();
+    let spans = collector.spans();
+    let collector = collector.run();
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_TRACE_COLLECTOR_URL, collector.url());
+
+    let ctrl = controller::new();
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        .inbound(srv)
+        .metrics_flush_interval(Duration::from_millis(500))
+        .run_with_test_env(env);
+    let client = client::http1(proxy.inbound, "tracing.test.svc.cluster.local");
+
+    info!("client.get(/hey)");
+    let mut req = client.request_builder("/hey");
+    req.header("traceparent", format!("00-{}-{}-01", TRACE_ID, PARENT_ID).as_str());
+    let rsp = client.request(&mut req);
+    assert_eq!(rsp.status(), StatusCode::OK);
+
+    info!("awaiting spans");
+    let body = spans.wait().next().unwrap().unwrap();
+    assert!(body.contains(&format!("\"traceId\":\"{}\"", TRACE_ID)), "{}", body);
+    assert!(body.contains(&format!("\"parentId\":\"{}\"", PARENT_ID)), "{}", body);
+    assert!(body.contains("\"kind\":\"SERVER\""), "{}", body);
+}
+
+#[test]
+fn unsampled_trace_is_not_reported() {
+    let _ = env_logger::try_init();
+
+    let srv = server::http1()
+        .route_fn("/hey", |req| {
+            assert!(req.headers()["traceparent"].to_str().unwrap().ends_with("-00"));
+            Response::new("hello".into())
+        })
+        .route("/sampled", "hello")
+        .run();
+
+    let mut collector = collector::new();
+    let spans = collector.spans();
+    let collector = collector.run();
+
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_TRACE_COLLECTOR_URL, collector.url());
+
+    let ctrl = controller::new();
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        .inbound(srv)
+        .metrics_flush_interval(Duration::from_millis(500))
+        .run_with_test_env(env);
+    let client = client::http1(proxy.inbound, "tracing.test.svc.cluster.local");
+
+    let mut req = client.request_builder("/hey");
+    req.header("traceparent", format!("00-{}-{}-00", TRACE_ID, PARENT_ID).as_str());
+    assert_eq!(client.request(&mut req).status(), StatusCode::OK);
+
+    // Requests without trace context start new, sampled traces.
+    assert_eq!(client.get("/sampled"), "hello");
+
+    let body = spans.wait().next().unwrap().unwrap();
+    assert!(!body.contains(TRACE_ID), "{}", body);
+    assert!(body.contains("\"http.path\":\"/sampled\""), "{}", body);
+}
+
+#[test]
+fn trace_headers_are_untouched_without_a_collector() {
+    let _ = env_logger::try_init();
+
+    let srv = server::http1()
+        .route_fn("/hey", |req| {
+            let traceparent = req.headers()["traceparent"].to_str().unwrap();
+            assert_eq!(traceparent, format!("00-{}-{}-01", TRACE_ID, PARENT_ID));
+            assert_eq!(req.headers()["x-b3-spanid"], PARENT_ID);
+            assert!(req.headers().get("x-b3-parentspanid").is_none());
+            Response::new("hello".into())
+        })
+        .run();
+
+    let ctrl = controller::new();
+    let proxy = proxy::new()
+        .controller(ctrl.run())
+        .inbound(srv)
+        .run();
+    let client = client::http1(proxy.inbound, "tracing.test.svc.cluster.local");
+
+    let mut req = client.request_builder("/hey");
+    req.header("traceparent", format!("00-{}-{}-01", TRACE_ID, PARENT_ID).as_str());
+    req.header("x-b3-traceid", TRACE_ID);
+    req.header("x-b3-spanid", PARENT_ID);
+    assert_eq!(client.request(&mut req).status(), StatusCode::OK);
+}
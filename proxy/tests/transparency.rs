@@ -30,6 +30,71 @@ fn inbound_http1() {
     assert_eq!(client.get("/"), "hello h1");
 }
 
+#[test]
+fn inbound_assigns_request_id() {
+    let _ = env_logger::try_init();
+
+    let srv = server::http1()
+        .route_fn("/", |req| {
+            let id = req.headers()["x-request-id"].to_str().unwrap();
+            assert_eq!(id.len(), 36, "{}", id);
+            Response::new("hello h1".into())
+        })
+        .run();
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run();
+    let client = client::http1(proxy.inbound, "transparency.test.svc.cluster.local");
+
+    // The assigned ID is echoed back, so that the client may correlate the
+    // response, too.
+    let res = client.request(&mut client.request_builder("/"));
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.headers()["x-request-id"].len(), 36);
+}
+
+#[test]
+fn inbound_preserves_request_id() {
+    let _ = env_logger::try_init();
+
+    let srv = server::http1()
+        .route_fn("/", |req| {
+            assert_eq!(req.headers()["x-request-id"], "abc123");
+            Response::new("hello h1".into())
+        })
+        .run();
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run();
+    let client = client::http1(proxy.inbound, "transparency.test.svc.cluster.local");
+
+    let res = client.request(client.request_builder("/")
+        .header("x-request-id", "abc123"));
+    assert_eq!(res.status(), http::StatusCode::OK);
+}
+
+#[test]
+fn inbound_error_response_has_request_id() {
+    let _ = env_logger::try_init();
+
+    // Without a forward address or an original destination, the inbound proxy
+    // cannot route the request.
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .run();
+    let client = client::http1(proxy.inbound, "transparency.test.svc.cluster.local");
+
+    let res = client.request(client.request_builder("/")
+        .header("x-request-id", "abc123"));
+    assert_eq!(res.status(), http::StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(res.headers()["x-request-id"], "abc123");
+}
+
 #[test]
 fn http1_connect_not_supported() {
     let _ = env_logger::try_init();
@@ -51,6 +116,98 @@ fn http1_connect_not_supported() {
     assert_eq!(s(&tcp_client.read()[..expected.len()]), expected);
 }
 
+#[test]
+fn outbound_http1_connect_tunnel() {
+    let _ = env_logger::try_init();
+
+    let msg1 = "custom tcp hello";
+    let msg2 = "custom tcp bye";
+
+    let srv = server::tcp()
+        .accept(move |read| {
+            assert_eq!(read, msg1.as_bytes());
+            msg2
+        })
+        .run();
+    let target = srv.addr;
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .outbound(srv)
+        .run();
+
+    let client = client::tcp(proxy.outbound);
+
+    let tcp_client = client.connect();
+    tcp_client.write(format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n\r\n", target));
+
+    let expected = "HTTP/1.1 200 Connection established\r\n\r\n";
+    assert_eq!(s(&tcp_client.read()), expected);
+
+    tcp_client.write(msg1);
+    assert_eq!(tcp_client.read(), msg2.as_bytes());
+}
+
+#[test]
+fn outbound_http1_connect_tunnel_head_in_parts() {
+    let _ = env_logger::try_init();
+
+    let msg1 = "custom tcp hello";
+    let msg2 = "custom tcp bye";
+
+    let srv = server::tcp()
+        .accept(move |read| {
+            assert_eq!(read, msg1.as_bytes());
+            msg2
+        })
+        .run();
+    let target = srv.addr;
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .outbound(srv)
+        .run();
+
+    let client = client::tcp(proxy.outbound);
+
+    let tcp_client = client.connect();
+
+    // The head is only detected as a CONNECT once it has all arrived.
+    tcp_client.write(format!("CONNECT {} HTTP/1.1\r\n", target));
+    ::std::thread::sleep(Duration::from_millis(100));
+    tcp_client.write(format!("Host: {}\r\n\r\n", target));
+
+    let expected = "HTTP/1.1 200 Connection established\r\n\r\n";
+    assert_eq!(s(&tcp_client.read()), expected);
+
+    tcp_client.write(msg1);
+    assert_eq!(tcp_client.read(), msg2.as_bytes());
+}
+
+#[test]
+fn outbound_http1_connect_denied_by_allowlist() {
+    let _ = env_logger::try_init();
+
+    let srv = server::tcp()
+        .run();
+    let target = srv.addr;
+    let ctrl = controller::new().run();
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_OUTBOUND_CONNECT_ALLOWLIST, "example.com:443".to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .outbound(srv)
+        .run_with_test_env(env);
+
+    let client = client::tcp(proxy.outbound);
+
+    let tcp_client = client.connect();
+    tcp_client.write(format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n\r\n", target));
+
+    let expected = "HTTP/1.1 403 Forbidden\r\n";
+    assert_eq!(s(&tcp_client.read()[..expected.len()]), expected);
+}
+
 #[test]
 fn http1_removes_connection_headers() {
     let _ = env_logger::try_init();
@@ -201,6 +358,67 @@ fn outbound_tcp() {
     assert_eq!(tcp_client.read(), msg2.as_bytes());
 }
 
+#[test]
+fn outbound_tls_routed_by_server_name() {
+    let _ = env_logger::try_init();
+
+    let hello = tls_client_hello("tls.test.svc.cluster.local");
+    let expected = hello.clone();
+
+    let orig = server::tcp()
+        .accept(|_| "original destination")
+        .run();
+    let srv = server::tcp()
+        .accept(move |read| {
+            assert_eq!(read, expected);
+            "server name destination"
+        })
+        .run();
+    let ctrl = controller::new()
+        .destination("tls.test.svc.cluster.local", srv.addr)
+        .run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .outbound(orig)
+        .run();
+
+    let client = client::tcp(proxy.outbound);
+
+    let tcp_client = client.connect();
+
+    tcp_client.write(hello);
+    assert_eq!(tcp_client.read(), b"server name destination");
+}
+
+/// Builds a TLS ClientHello record with a `server_name` extension.
+fn tls_client_hello(server_name: &str) -> Vec<u8> {
+    fn vec16(body: &[u8]) -> Vec<u8> {
+        let mut v = vec![(body.len() >> 8) as u8, body.len() as u8];
+        v.extend_from_slice(body);
+        v
+    }
+
+    let mut name = vec![0];
+    name.extend(vec16(server_name.as_bytes()));
+    let mut extension = vec![0, 0];
+    extension.extend(vec16(&vec16(&name)));
+
+    let mut hello = vec![3, 3];
+    hello.extend_from_slice(&[7; 32]);
+    hello.push(0);
+    hello.extend(vec16(&[0x13, 0x01]));
+    hello.extend_from_slice(&[1, 0]);
+    hello.extend(vec16(&extension));
+
+    let len = hello.len();
+    let mut handshake = vec![1, (len >> 16) as u8, (len >> 8) as u8, len as u8];
+    handshake.extend(hello);
+
+    let mut record = vec![22, 3, 1];
+    record.extend(vec16(&handshake));
+    record
+}
+
 #[test]
 fn inbound_tcp() {
     let _ = env_logger::try_init();
@@ -228,6 +446,65 @@ fn inbound_tcp() {
     assert_eq!(tcp_client.read(), msg2.as_bytes());
 }
 
+#[test]
+fn inbound_tcp_opaque_port_server_speaks_first() {
+    let _ = env_logger::try_init();
+
+    let greeting = "custom tcp greeting";
+
+    let srv = server::tcp()
+        .accept_fut(move |sock| {
+            tokio_io::io::write_all(sock, greeting.as_bytes())
+                .map(|_| ())
+                .map_err(|e| panic!("tcp server error: {}", e))
+        })
+        .run();
+    let ctrl = controller::new().run();
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_INBOUND_OPAQUE_PORTS, srv.addr.port().to_string());
+    // Without the opaque port, the connection would wait for this long before
+    // it is proxied.
+    env.put(config::ENV_SNIFF_TIMEOUT, "60000".to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run_with_test_env(env);
+
+    let client = client::tcp(proxy.inbound);
+
+    let tcp_client = client.connect();
+
+    assert_eq!(tcp_client.read(), greeting.as_bytes());
+}
+
+#[test]
+fn inbound_tcp_server_speaks_first_after_sniff_timeout() {
+    let _ = env_logger::try_init();
+
+    let greeting = "custom tcp greeting";
+
+    let srv = server::tcp()
+        .accept_fut(move |sock| {
+            tokio_io::io::write_all(sock, greeting.as_bytes())
+                .map(|_| ())
+                .map_err(|e| panic!("tcp server error: {}", e))
+        })
+        .run();
+    let ctrl = controller::new().run();
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_SNIFF_TIMEOUT, "100".to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run_with_test_env(env);
+
+    let client = client::tcp(proxy.inbound);
+
+    let tcp_client = client.connect();
+
+    assert_eq!(tcp_client.read(), greeting.as_bytes());
+}
+
 #[test]
 fn tcp_with_no_orig_dst() {
     let _ = env_logger::try_init();
@@ -304,11 +581,9 @@ fn tcp_connections_close_if_client_closes() {
 }
 
 #[test]
-fn http11_upgrade_not_supported() {
+fn inbound_http11_upgrade() {
     let _ = env_logger::try_init();
 
-    // our h1 proxy will strip the Connection header
-    // and headers it mentions
     let msg1 = "\
         GET /chat HTTP/1.1\r\n\
         Host: foo.bar\r\n\
@@ -317,8 +592,6 @@ fn http11_upgrade_not_supported() {
         \r\n\
         ";
 
-    // but let's pretend the server tries to upgrade
-    // anyways
     let msg2 = "\
         HTTP/1.1 101 Switching Protocols\r\n\
         Upgrade: websocket\r\n\
@@ -328,8 +601,8 @@ fn http11_upgrade_not_supported() {
 
     let srv = server::tcp()
         .accept(move |read| {
-            let head = s(&read);
-            assert!(!head.contains("Upgrade: websocket"));
+            // The upgrade request is forwarded as it was sent.
+            assert_eq!(s(&read), msg1);
             msg2
         })
         .run();
@@ -344,9 +617,300 @@ fn http11_upgrade_not_supported() {
     let tcp_client = client.connect();
 
     tcp_client.write(msg1);
+    assert_eq!(s(&tcp_client.read()), msg2);
+}
 
-    let expected = "HTTP/1.1 500 ";
-    assert_eq!(s(&tcp_client.read()[..expected.len()]), expected);
+#[test]
+fn outbound_http11_upgrade_routed_by_host() {
+    let _ = env_logger::try_init();
+
+    let msg1 = "\
+        GET /chat HTTP/1.1\r\n\
+        Host: transparency.test.svc.cluster.local\r\n\
+        Connection: Upgrade\r\n\
+        Upgrade: websocket\r\n\
+        \r\n\
+        ";
+
+    let msg2 = "\
+        HTTP/1.1 101 Switching Protocols\r\n\
+        Upgrade: websocket\r\n\
+        Connection: Upgrade\r\n\
+        \r\n\
+        ";
+
+    let orig = server::tcp()
+        .accept(|_| "original destination")
+        .run();
+    let srv = server::tcp()
+        .accept(move |read| {
+            assert_eq!(s(&read), msg1);
+            msg2
+        })
+        .run();
+    let ctrl = controller::new()
+        .destination("transparency.test.svc.cluster.local", srv.addr)
+        .run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .outbound(orig)
+        .run();
+
+    let client = client::tcp(proxy.outbound);
+
+    let tcp_client = client.connect();
+
+    tcp_client.write(msg1);
+    assert_eq!(s(&tcp_client.read()), msg2);
+}
+
+#[test]
+fn inbound_http11_upgrade_head_in_parts() {
+    let _ = env_logger::try_init();
+
+    let msg1 = "\
+        GET /chat HTTP/1.1\r\n\
+        Host: foo.bar\r\n\
+        Connection: Upgrade\r\n\
+        Upgrade: websocket\r\n\
+        \r\n\
+        ";
+
+    let msg2 = "\
+        HTTP/1.1 101 Switching Protocols\r\n\
+        Upgrade: websocket\r\n\
+        Connection: Upgrade\r\n\
+        \r\n\
+        ";
+
+    let srv = server::tcp()
+        .accept(move |read| {
+            assert_eq!(s(&read), msg1);
+            msg2
+        })
+        .run();
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run();
+
+    let client = client::tcp(proxy.inbound);
+
+    let tcp_client = client.connect();
+
+    // The head is only detected as an upgrade once it has all arrived.
+    let (part1, part2) = msg1.split_at(20);
+    tcp_client.write(part1);
+    ::std::thread::sleep(Duration::from_millis(100));
+    tcp_client.write(part2);
+    assert_eq!(s(&tcp_client.read()), msg2);
+}
+
+#[test]
+fn inbound_http11_upgrade_refused_closes_connection() {
+    let _ = env_logger::try_init();
+
+    let msg1 = "\
+        GET /chat HTTP/1.1\r\n\
+        Host: foo.bar\r\n\
+        Connection: Upgrade\r\n\
+        Upgrade: websocket\r\n\
+        \r\n\
+        ";
+
+    let msg2 = "\
+        HTTP/1.1 426 Upgrade Required\r\n\
+        content-length: 0\r\n\
+        \r\n\
+        ";
+
+    let srv = server::tcp()
+        .accept(move |read| {
+            assert_eq!(s(&read), msg1);
+            msg2
+        })
+        .run();
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run();
+
+    let client = client::tcp(proxy.inbound);
+
+    let tcp_client = client.connect();
+
+    // Since the server didn't switch protocols, the client is told to make
+    // its next requests on a new connection, rather than have them proxied
+    // as TCP.
+    tcp_client.write(msg1);
+    assert_eq!(
+        s(&tcp_client.read()),
+        "\
+        HTTP/1.1 426 Upgrade Required\r\n\
+        connection: close\r\n\
+        content-length: 0\r\n\
+        \r\n\
+        "
+    );
+
+    let read = tcp_client
+        .try_read()
+        .unwrap_or_else(|_| Vec::new());
+    assert_eq!(read, b"");
+}
+
+#[test]
+fn inbound_http11_upgrade_on_reused_connection_reaches_app() {
+    let _ = env_logger::try_init();
+
+    let srv = server::http1()
+        .route("/", "hello")
+        .route_fn("/chat", |req| {
+            let is_upgrade = req.headers().get("upgrade").map(|v| v == "websocket")
+                == Some(true)
+                && req.headers().get("connection").map(|v| v == "upgrade") == Some(true);
+            let mut res = Response::new("".into());
+            if !is_upgrade {
+                *res.status_mut() = StatusCode::BAD_REQUEST;
+            }
+            res
+        })
+        .run();
+    let ctrl = controller::new().run();
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run();
+
+    let client = client::tcp(proxy.inbound);
+
+    let tcp_client = client.connect();
+
+    tcp_client.write("GET / HTTP/1.1\r\nHost: foo.bar\r\n\r\n");
+    assert!(s(&tcp_client.read()).starts_with("HTTP/1.1 200 OK\r\n"));
+
+    // The upgrade can't be proxied as TCP anymore, but the application still
+    // sees it, and may refuse it.
+    tcp_client.write("\
+        GET /chat HTTP/1.1\r\n\
+        Host: foo.bar\r\n\
+        Connection: Upgrade\r\n\
+        Upgrade: websocket\r\n\
+        \r\n\
+        ");
+    assert!(s(&tcp_client.read()).starts_with("HTTP/1.1 200 OK\r\n"));
+}
+
+#[test]
+fn outbound_http1_upgraded_to_meshed_endpoint() {
+    let _ = env_logger::try_init();
+
+    let srv = server::http2()
+        .route_fn("/", |req| {
+            assert_eq!(req.version(), http::Version::HTTP_2);
+            assert_eq!(req.headers()["conduit-orig-proto"], "HTTP/1.1");
+            Response::new("hello h2".into())
+        })
+        .run();
+    let addr = srv.addr;
+    let ctrl = controller::new()
+        .destination_fn("transparency.test.svc.cluster.local", move || {
+            Some(controller::destination_add(vec![
+                controller::weighted_addr(addr, &[("meshed", "true")]),
+            ]))
+        })
+        .run();
+    let proxy = proxy::new().controller(ctrl).outbound(srv).run();
+    let client = client::http1(proxy.outbound, "transparency.test.svc.cluster.local");
+
+    let res = client.request(&mut client.request_builder("/"));
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.version(), http::Version::HTTP_11);
+}
+
+#[test]
+fn http10_carried_over_http2_between_proxies() {
+    let _ = env_logger::try_init();
+
+    let host = "transparency.test.svc.cluster.local";
+    let srv = server::http1()
+        .route_fn("/", move |req| {
+            assert_eq!(req.version(), http::Version::HTTP_10);
+            assert_eq!(req.uri().to_string(), "/");
+            assert_eq!(req.headers().get("host").unwrap(), host);
+            assert!(!req.headers().contains_key("conduit-orig-proto"));
+            Response::builder()
+                .version(http::Version::HTTP_10)
+                .body("".into())
+                .unwrap()
+        })
+        .run();
+    let inbound = proxy::new()
+        .controller(controller::new().run())
+        .inbound(srv)
+        .run();
+
+    let addr = inbound.inbound;
+    let ctrl = controller::new()
+        .destination_fn(host, move || {
+            Some(controller::destination_add(vec![
+                controller::weighted_addr(addr, &[("meshed", "true")]),
+            ]))
+        })
+        .run();
+    let outbound = proxy::new().controller(ctrl).run();
+    let client = client::http1(outbound.outbound, host);
+
+    let res = client.request(client.request_builder("/")
+        .version(http::Version::HTTP_10)
+        .header("host", host));
+
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.version(), http::Version::HTTP_10);
+}
+
+#[test]
+fn http1_connection_headers_in_responses_carried_over_http2_between_proxies() {
+    let _ = env_logger::try_init();
+
+    let host = "transparency.test.svc.cluster.local";
+    let srv = server::http1()
+        .route_fn("/", |_| {
+            Response::builder()
+                .header("transfer-encoding", "chunked")
+                .header("connection", "keep-alive")
+                .header("keep-alive", "timeout=5")
+                .body("hello".into())
+                .unwrap()
+        })
+        .run();
+    let inbound = proxy::new()
+        .controller(controller::new().run())
+        .inbound(srv)
+        .run();
+
+    let addr = inbound.inbound;
+    let ctrl = controller::new()
+        .destination_fn(host, move || {
+            Some(controller::destination_add(vec![
+                controller::weighted_addr(addr, &[("meshed", "true")]),
+            ]))
+        })
+        .run();
+    let outbound = proxy::new().controller(ctrl).run();
+    let client = client::http1(outbound.outbound, host);
+
+    let res = client.request(client.request_builder("/")
+        .header("host", host));
+
+    assert_eq!(res.status(), http::StatusCode::OK);
+    assert_eq!(res.version(), http::Version::HTTP_11);
+    assert!(!res.headers().contains_key("keep-alive"));
+    assert!(!res.headers().contains_key("conduit-orig-proto"));
+    let body = res.into_parts().1.concat2().wait().unwrap();
+    assert_eq!(&body[..], b"hello");
 }
 
 #[test]
@@ -723,3 +1287,99 @@ fn http1_requests_without_host_have_unique_connections() {
     assert_eq!(res.version(), http::Version::HTTP_11);
     assert_eq!(inbound.connections(), 4);
 }
+
+#[test]
+fn inbound_rate_limits_requests() {
+    use std::io::Write;
+    let _ = env_logger::try_init();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-rate-limits");
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(b"authority 0.001 2\n"))
+        .expect("write rate limits");
+
+    let srv = server::http1().route("/", "hello h1").run();
+    let ctrl = controller::new().run();
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_INBOUND_RATE_LIMITS_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run_with_test_env(env);
+    let client = client::http1(proxy.inbound, "transparency.test.svc.cluster.local");
+
+    assert_eq!(client.get("/"), "hello h1");
+    assert_eq!(client.get("/"), "hello h1");
+
+    let rsp = client.request(&mut client.request_builder("/"));
+    assert_eq!(rsp.status(), http::StatusCode::TOO_MANY_REQUESTS);
+    // Refused requests are still assigned an ID, so they may be correlated.
+    assert_eq!(rsp.headers()["x-request-id"].len(), 36);
+}
+
+#[test]
+fn inbound_denies_requests_by_policy() {
+    use std::io::Write;
+    let _ = env_logger::try_init();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-policy");
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(b"deny path=/admin\nallow source=127.0.0.1\ndeny\n"))
+        .expect("write policy");
+
+    let srv = server::http1()
+        .route("/", "hello h1")
+        .route("/admin", "secrets")
+        .run();
+    let ctrl = controller::new().run();
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_INBOUND_POLICY_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run_with_test_env(env);
+    let client = client::http1(proxy.inbound, "transparency.test.svc.cluster.local");
+
+    assert_eq!(client.get("/"), "hello h1");
+
+    let rsp = client.request(&mut client.request_builder("/admin"));
+    assert_eq!(rsp.status(), http::StatusCode::FORBIDDEN);
+    // Denied requests are still assigned an ID, so they may be correlated.
+    assert_eq!(rsp.headers()["x-request-id"].len(), 36);
+}
+
+#[test]
+fn inbound_denies_upgrade_requests_by_policy() {
+    use std::io::Write;
+    let _ = env_logger::try_init();
+
+    let path = ::std::env::temp_dir().join("conduit-proxy-test-upgrade-policy");
+    ::std::fs::File::create(&path)
+        .and_then(|mut f| f.write_all(b"deny path=/admin\n"))
+        .expect("write policy");
+
+    let srv = server::tcp()
+        .accept(move |_| "HTTP/1.1 101 Switching Protocols\r\n\r\n")
+        .run();
+    let ctrl = controller::new().run();
+    let mut env = config::TestEnv::new();
+    env.put(config::ENV_INBOUND_POLICY_PATH, path.to_str().unwrap().to_owned());
+    let proxy = proxy::new()
+        .controller(ctrl)
+        .inbound(srv)
+        .run_with_test_env(env);
+
+    // Upgrade requests are proxied as TCP, but are still authorized.
+    let tcp_client = client::tcp(proxy.inbound).connect();
+    tcp_client.write("\
+        GET /admin/chat HTTP/1.1\r\n\
+        Host: transparency.test.svc.cluster.local\r\n\
+        Connection: Upgrade\r\n\
+        Upgrade: websocket\r\n\
+        \r\n\
+        ");
+    assert_eq!(
+        s(&tcp_client.read()),
+        "HTTP/1.1 403 Forbidden\r\ncontent-length: 0\r\n\r\n"
+    );
+}